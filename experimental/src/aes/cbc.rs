@@ -0,0 +1,103 @@
+//! CBC mode (NIST SP 800-38A section 6.2) with PKCS #7 padding (RFC 5652
+//! section 6.3), as used by PBES2 and CMS.
+
+use super::{Aes, AesError, BLOCK_LEN};
+
+/// Pads `plaintext` to a whole number of blocks and encrypts it. The
+/// output is always longer than the input, by 1 to 16 bytes.
+pub fn encrypt(aes: &Aes, iv: &[u8; BLOCK_LEN], plaintext: &[u8]) -> Vec<u8> {
+    let pad = BLOCK_LEN - plaintext.len() % BLOCK_LEN;
+    let mut padded = plaintext.to_vec();
+    padded.resize(plaintext.len() + pad, pad as u8);
+
+    let mut chain = *iv;
+    for chunk in padded.chunks_mut(BLOCK_LEN) {
+        for (c, p) in chain.iter_mut().zip(chunk.iter()) {
+            *c ^= p;
+        }
+        aes.encrypt_block(&mut chain);
+        chunk.copy_from_slice(&chain);
+    }
+    padded
+}
+
+/// Decrypts and removes the padding. The padding is checked without
+/// branching on its bytes, though the result of the check is of course
+/// visible to the caller.
+pub fn decrypt(aes: &Aes, iv: &[u8; BLOCK_LEN], ciphertext: &[u8]) -> Result<Vec<u8>, AesError> {
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(BLOCK_LEN) {
+        return Err(AesError::InvalidLength);
+    }
+    let mut blocks: Vec<[u8; BLOCK_LEN]> = ciphertext
+        .chunks(BLOCK_LEN)
+        .map(|c| c.try_into().unwrap())
+        .collect();
+    aes.decrypt_blocks(&mut blocks);
+    let mut plaintext = Vec::with_capacity(ciphertext.len());
+    let previous = std::iter::once(&iv[..]).chain(ciphertext.chunks(BLOCK_LEN));
+    for (block, prev) in blocks.iter().zip(previous) {
+        plaintext.extend(block.iter().zip(prev).map(|(b, p)| b ^ p));
+    }
+
+    let last = &plaintext[plaintext.len() - BLOCK_LEN..];
+    let pad = last[BLOCK_LEN - 1];
+    // bad is non-zero if pad is 0 or above 16, or any of the last pad
+    // bytes differs from it.
+    let mut bad = u32::from(pad == 0) | u32::from(pad as usize > BLOCK_LEN);
+    for (i, &b) in last.iter().rev().enumerate() {
+        let in_pad = 0u8.wrapping_sub(u8::from((i as u8) < pad));
+        bad |= u32::from((b ^ pad) & in_pad);
+    }
+    if bad != 0 {
+        return Err(AesError::InvalidPadding);
+    }
+    plaintext.truncate(plaintext.len() - pad as usize);
+    Ok(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::tests::vectors;
+
+    #[test]
+    fn test_sp800_38a() {
+        // The vectors are unpadded, so a whole block of padding follows.
+        for v in vectors("cbc") {
+            let aes = Aes::new(&v.key).unwrap();
+            let iv: [u8; 16] = v.iv.as_slice().try_into().unwrap();
+            let ciphertext = encrypt(&aes, &iv, &v.plaintext);
+            assert_eq!(ciphertext[..64], v.ciphertext);
+            assert_eq!(ciphertext.len(), 80);
+            assert_eq!(decrypt(&aes, &iv, &ciphertext), Ok(v.plaintext));
+        }
+    }
+
+    #[test]
+    fn test_padding() {
+        let aes = Aes::new(&[1; 32]).unwrap();
+        let iv = [2; 16];
+        for len in 0..40 {
+            let plaintext = vec![0xa5; len];
+            let ciphertext = encrypt(&aes, &iv, &plaintext);
+            assert_eq!(ciphertext.len(), (len / 16 + 1) * 16);
+            assert_eq!(decrypt(&aes, &iv, &ciphertext), Ok(plaintext));
+        }
+
+        // Forge final blocks whose padding decrypts to each bad pattern.
+        for last in [[0u8; 16], [17; 16], {
+            let mut b = [3; 16];
+            b[13] = 4;
+            b
+        }] {
+            let mut block = last;
+            for (b, v) in block.iter_mut().zip(&iv) {
+                *b ^= v;
+            }
+            aes.encrypt_block(&mut block);
+            assert_eq!(decrypt(&aes, &iv, &block), Err(AesError::InvalidPadding));
+        }
+        assert_eq!(decrypt(&aes, &iv, &[]), Err(AesError::InvalidLength));
+        assert_eq!(decrypt(&aes, &iv, &[0; 17]), Err(AesError::InvalidLength));
+    }
+}
@@ -0,0 +1,59 @@
+//! CTR mode (NIST SP 800-38A section 6.5).
+
+use super::{Aes, BLOCK_LEN};
+
+/// XORs `data` with the keystream E(T), E(T + 1), ... where T is
+/// `counter` read as a 128-bit big-endian integer. Encryption and
+/// decryption are the same operation.
+pub fn apply_keystream(aes: &Aes, counter: &[u8; BLOCK_LEN], data: &mut [u8]) {
+    xor_keystream(aes, u128::from_be_bytes(*counter), u128::MAX, data);
+}
+
+/// The keystream with only the bits of the counter in `mask` incremented,
+/// so GCM can pass the low 32 bits (inc32 in SP 800-38D).
+pub(crate) fn xor_keystream(aes: &Aes, mut counter: u128, mask: u128, data: &mut [u8]) {
+    // Four blocks fill the bitsliced state.
+    for chunk in data.chunks_mut(4 * BLOCK_LEN) {
+        let mut blocks = [[0u8; BLOCK_LEN]; 4];
+        let blocks = &mut blocks[..chunk.len().div_ceil(BLOCK_LEN)];
+        for block in blocks.iter_mut() {
+            *block = counter.to_be_bytes();
+            counter = (counter & !mask) | (counter.wrapping_add(1) & mask);
+        }
+        aes.encrypt_blocks(blocks);
+        for (byte, key) in chunk.iter_mut().zip(blocks.iter().flatten()) {
+            *byte ^= key;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::tests::vectors;
+
+    #[test]
+    fn test_sp800_38a() {
+        for v in vectors("ctr") {
+            let aes = Aes::new(&v.key).unwrap();
+            let counter: [u8; 16] = v.iv.as_slice().try_into().unwrap();
+            let mut data = v.plaintext.clone();
+            apply_keystream(&aes, &counter, &mut data);
+            assert_eq!(data, v.ciphertext);
+            // A partial final block uses a prefix of the keystream.
+            let mut data = v.plaintext[..37].to_vec();
+            apply_keystream(&aes, &counter, &mut data);
+            assert_eq!(data, v.ciphertext[..37]);
+        }
+    }
+
+    #[test]
+    fn test_counter_wraps() {
+        let aes = Aes::new(&[9; 16]).unwrap();
+        let mut data = [0u8; 32];
+        apply_keystream(&aes, &[0xff; 16], &mut data);
+        let mut expected = [[0xff; 16], [0; 16]];
+        aes.encrypt_blocks(&mut expected);
+        assert_eq!(data[..], expected.concat());
+    }
+}
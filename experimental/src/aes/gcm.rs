@@ -0,0 +1,217 @@
+//! GCM (NIST SP 800-38D): CTR encryption authenticated with GHASH. The
+//! GF(2^128) multiplication in GHASH goes bit by bit under masks rather
+//! than through the usual per-key tables.
+
+use super::ctr::xor_keystream;
+use super::{Aes, AesError, BLOCK_LEN};
+use crate::ct::ct_eq;
+
+pub const TAG_LEN: usize = 16;
+
+/// inc32 only counts in the low 32 bits of the counter block.
+const INC32: u128 = 0xffff_ffff;
+
+/// AES-GCM, with 16-byte tags unless truncated by `with_tag_len`.
+#[derive(Clone)]
+pub struct Gcm {
+    aes: Aes,
+    /// The hash subkey E(0^128).
+    h: u128,
+    tag_len: usize,
+}
+
+impl std::fmt::Debug for Gcm {
+    // The hash subkey is as secret as the key.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gcm")
+            .field("aes", &self.aes)
+            .field("tag_len", &self.tag_len)
+            .finish()
+    }
+}
+
+impl Gcm {
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        Self::with_tag_len(key, TAG_LEN)
+    }
+
+    /// Tags cut to their first `tag_len` bytes, which must be 12 to 16
+    /// (SP 800-38D section 5.2.1.2; the shorter lengths there are left
+    /// out). Fails with `InvalidLength` otherwise.
+    pub fn with_tag_len(key: &[u8], tag_len: usize) -> Result<Self, AesError> {
+        if !(12..=TAG_LEN).contains(&tag_len) {
+            return Err(AesError::InvalidLength);
+        }
+        let aes = Aes::new(key)?;
+        let mut h = [0u8; BLOCK_LEN];
+        aes.encrypt_block(&mut h);
+        Ok(Self {
+            aes,
+            h: u128::from_be_bytes(h),
+            tag_len,
+        })
+    }
+
+    pub fn tag_len(&self) -> usize {
+        self.tag_len
+    }
+
+    /// Returns the ciphertext followed by the tag. A nonce must never be
+    /// used twice with one key; 12 bytes is the standard length, and any
+    /// other non-empty length is hashed into the initial counter.
+    ///
+    /// Panics if `nonce` is empty.
+    pub fn encrypt(&self, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let j0 = self.initial_counter(nonce);
+        let mut out = plaintext.to_vec();
+        xor_keystream(&self.aes, increment(j0), INC32, &mut out);
+        let tag = self.tag(j0, aad, &out);
+        out.extend_from_slice(&tag[..self.tag_len]);
+        out
+    }
+
+    /// Checks the tag on `sealed` (ciphertext then tag) and only then
+    /// decrypts. Any mismatch, including a truncated input, is
+    /// `AuthenticationFailed`.
+    ///
+    /// Panics if `nonce` is empty.
+    pub fn decrypt(&self, nonce: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, AesError> {
+        let Some(split) = sealed.len().checked_sub(self.tag_len) else {
+            return Err(AesError::AuthenticationFailed);
+        };
+        let (ciphertext, tag) = sealed.split_at(split);
+        let j0 = self.initial_counter(nonce);
+        if !ct_eq(&self.tag(j0, aad, ciphertext)[..self.tag_len], tag) {
+            return Err(AesError::AuthenticationFailed);
+        }
+        let mut out = ciphertext.to_vec();
+        xor_keystream(&self.aes, increment(j0), INC32, &mut out);
+        Ok(out)
+    }
+
+    /// J0: the nonce followed by 0^31 || 1 for 96-bit nonces, otherwise
+    /// GHASH of the nonce.
+    fn initial_counter(&self, nonce: &[u8]) -> u128 {
+        assert!(!nonce.is_empty(), "GCM nonce is empty");
+        if nonce.len() == 12 {
+            let mut block = [0u8; BLOCK_LEN];
+            block[..12].copy_from_slice(nonce);
+            block[15] = 1;
+            u128::from_be_bytes(block)
+        } else {
+            self.ghash(&[], nonce)
+        }
+    }
+
+    fn tag(&self, j0: u128, aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+        let mut mask = j0.to_be_bytes();
+        self.aes.encrypt_block(&mut mask);
+        (u128::from_be_bytes(mask) ^ self.ghash(aad, ciphertext)).to_be_bytes()
+    }
+
+    /// GHASH of A and C, each zero-padded to a block, then the block
+    /// holding their lengths in bits.
+    fn ghash(&self, aad: &[u8], ciphertext: &[u8]) -> u128 {
+        let mut y = 0;
+        for data in [aad, ciphertext] {
+            for chunk in data.chunks(BLOCK_LEN) {
+                let mut block = [0u8; BLOCK_LEN];
+                block[..chunk.len()].copy_from_slice(chunk);
+                y = gf_mul(y ^ u128::from_be_bytes(block), self.h);
+            }
+        }
+        let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+        gf_mul(y ^ lengths, self.h)
+    }
+}
+
+fn increment(counter: u128) -> u128 {
+    (counter & !INC32) | (counter.wrapping_add(1) & INC32)
+}
+
+/// Multiplication in GF(2^128) with GCM's reflected bit order, where the
+/// most significant bit is the coefficient of x^0 (SP 800-38D algorithm 1).
+fn gf_mul(x: u128, y: u128) -> u128 {
+    const R: u128 = 0xe1 << 120;
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        z ^= v & 0u128.wrapping_sub(x >> i & 1);
+        v = (v >> 1) ^ (R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::tests::vectors;
+
+    #[test]
+    fn test_gcm_spec_vectors() {
+        let vectors = vectors("gcm");
+        assert_eq!(vectors.len(), 18);
+        for v in vectors {
+            let gcm = Gcm::new(&v.key).unwrap();
+            let sealed = gcm.encrypt(&v.iv, &v.aad, &v.plaintext);
+            assert_eq!(sealed[..v.plaintext.len()], v.ciphertext);
+            assert_eq!(sealed[v.plaintext.len()..], v.tag);
+            assert_eq!(gcm.decrypt(&v.iv, &v.aad, &sealed), Ok(v.plaintext));
+        }
+    }
+
+    #[test]
+    fn test_truncated_tags() {
+        for v in vectors("gcm") {
+            for tag_len in 12..=TAG_LEN {
+                let gcm = Gcm::with_tag_len(&v.key, tag_len).unwrap();
+                let sealed = gcm.encrypt(&v.iv, &v.aad, &v.plaintext);
+                assert_eq!(sealed[..v.plaintext.len()], v.ciphertext);
+                assert_eq!(sealed[v.plaintext.len()..], v.tag[..tag_len]);
+                assert_eq!(gcm.decrypt(&v.iv, &v.aad, &sealed), Ok(v.plaintext.clone()));
+                // Untruncated tags do not verify under a shorter tag length.
+                if tag_len < TAG_LEN {
+                    let mut full = v.ciphertext.clone();
+                    full.extend_from_slice(&v.tag);
+                    assert_eq!(
+                        gcm.decrypt(&v.iv, &v.aad, &full),
+                        Err(AesError::AuthenticationFailed)
+                    );
+                }
+            }
+        }
+        for tag_len in [0, 8, 11, 17] {
+            assert_eq!(
+                Gcm::with_tag_len(&[0; 16], tag_len).unwrap_err(),
+                AesError::InvalidLength
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_forgeries() {
+        let gcm = Gcm::new(&[7; 32]).unwrap();
+        let nonce = [1; 12];
+        let sealed = gcm.encrypt(&nonce, b"header", b"payload");
+        for i in 0..sealed.len() {
+            let mut forged = sealed.clone();
+            forged[i] ^= 0x80;
+            assert_eq!(
+                gcm.decrypt(&nonce, b"header", &forged),
+                Err(AesError::AuthenticationFailed)
+            );
+        }
+        assert_eq!(
+            gcm.decrypt(&nonce, b"Header", &sealed),
+            Err(AesError::AuthenticationFailed)
+        );
+        assert_eq!(
+            gcm.decrypt(&[2; 12], b"header", &sealed),
+            Err(AesError::AuthenticationFailed)
+        );
+        assert_eq!(
+            gcm.decrypt(&nonce, b"header", &sealed[..15]),
+            Err(AesError::AuthenticationFailed)
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! AES (FIPS 197) with 128, 192 and 256-bit keys.
+//!
+//! The cipher is bitsliced: the state is held as eight bit planes, so
+//! every step is a fixed sequence of AND, XOR and shift instructions and
+//! no table is ever indexed by key or data. Each `u64` plane carries one
+//! bit of every byte of four blocks, bit `16 * b + j` belonging to byte
+//! `j` of block `b`. SubBytes computes the inverse in GF(2^8) directly
+//! (as x^254) instead of looking it up.
+//!
+//! The modes live in submodules: `cbc` with PKCS #7 padding, `ctr` and the
+//! authenticated `gcm`.
+
+pub mod cbc;
+pub mod ctr;
+pub mod gcm;
+
+pub const BLOCK_LEN: usize = 16;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AesError {
+    /// The key is not 16, 24 or 32 bytes long.
+    InvalidKeyLength,
+    /// A CBC ciphertext that is not a whole number of blocks.
+    InvalidLength,
+    /// Decryption produced bad PKCS #7 padding, which usually means the
+    /// wrong key.
+    InvalidPadding,
+    /// A GCM tag did not verify: the key, nonce, associated data,
+    /// ciphertext or tag is wrong.
+    AuthenticationFailed,
+}
+
+type State = [u64; 8];
+
+/// An expanded AES key.
+#[derive(Clone)]
+pub struct Aes {
+    /// Nr + 1 round keys, each replicated into all four block lanes.
+    round_keys: Vec<State>,
+}
+
+impl std::fmt::Debug for Aes {
+    // Never print the key schedule.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Aes{}", (self.round_keys.len() - 7) * 32)
+    }
+}
+
+impl Aes {
+    pub fn new(key: &[u8]) -> Result<Self, AesError> {
+        let nk = match key.len() {
+            16 | 24 | 32 => key.len() / 4,
+            _ => return Err(AesError::InvalidKeyLength),
+        };
+        let rounds = nk + 6;
+        // KeyExpansion (FIPS 197 section 5.2), one 32-bit word at a time.
+        let mut words: Vec<[u8; 4]> = key
+            .chunks(4)
+            .map(|w| w.try_into().expect("four-byte words"))
+            .collect();
+        let mut rcon = 1u8;
+        for i in nk..4 * (rounds + 1) {
+            let mut t = words[i - 1];
+            if i % nk == 0 {
+                t.rotate_left(1);
+                t = sub_word(t);
+                t[0] ^= rcon;
+                rcon = xtime_byte(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                t = sub_word(t);
+            }
+            let prev = words[i - nk];
+            words.push(std::array::from_fn(|k| prev[k] ^ t[k]));
+        }
+
+        let round_keys = words
+            .chunks(4)
+            .map(|rk| {
+                let block: [u8; BLOCK_LEN] = std::array::from_fn(|j| rk[j / 4][j % 4]);
+                pack(&[block; 4])
+            })
+            .collect();
+        Ok(Self { round_keys })
+    }
+
+    pub fn encrypt_block(&self, block: &mut [u8; BLOCK_LEN]) {
+        self.encrypt_blocks(std::slice::from_mut(block));
+    }
+
+    pub fn decrypt_block(&self, block: &mut [u8; BLOCK_LEN]) {
+        self.decrypt_blocks(std::slice::from_mut(block));
+    }
+
+    /// Encrypts each block independently (ECB), four at a time.
+    pub fn encrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        let rounds = self.round_keys.len() - 1;
+        for chunk in blocks.chunks_mut(4) {
+            let mut q = pack(chunk);
+            add_round_key(&mut q, &self.round_keys[0]);
+            for round_key in &self.round_keys[1..rounds] {
+                sub_bytes(&mut q);
+                shift_rows(&mut q);
+                mix_columns(&mut q);
+                add_round_key(&mut q, round_key);
+            }
+            sub_bytes(&mut q);
+            shift_rows(&mut q);
+            add_round_key(&mut q, &self.round_keys[rounds]);
+            unpack(&q, chunk);
+        }
+    }
+
+    /// The inverse cipher (FIPS 197 section 5.3), four blocks at a time.
+    pub fn decrypt_blocks(&self, blocks: &mut [[u8; BLOCK_LEN]]) {
+        let rounds = self.round_keys.len() - 1;
+        for chunk in blocks.chunks_mut(4) {
+            let mut q = pack(chunk);
+            add_round_key(&mut q, &self.round_keys[rounds]);
+            for round_key in self.round_keys[1..rounds].iter().rev() {
+                inv_shift_rows(&mut q);
+                inv_sub_bytes(&mut q);
+                add_round_key(&mut q, round_key);
+                inv_mix_columns(&mut q);
+            }
+            inv_shift_rows(&mut q);
+            inv_sub_bytes(&mut q);
+            add_round_key(&mut q, &self.round_keys[0]);
+            unpack(&q, chunk);
+        }
+    }
+}
+
+/// Transposes up to four blocks into bit planes; missing blocks are zero.
+fn pack(blocks: &[[u8; BLOCK_LEN]]) -> State {
+    let mut q = [0u64; 8];
+    for (b, block) in blocks.iter().enumerate() {
+        for (j, &byte) in block.iter().enumerate() {
+            for (i, plane) in q.iter_mut().enumerate() {
+                *plane |= u64::from(byte >> i & 1) << (16 * b + j);
+            }
+        }
+    }
+    q
+}
+
+fn unpack(q: &State, blocks: &mut [[u8; BLOCK_LEN]]) {
+    for (b, block) in blocks.iter_mut().enumerate() {
+        for (j, byte) in block.iter_mut().enumerate() {
+            *byte = 0;
+            for (i, plane) in q.iter().enumerate() {
+                *byte |= ((plane >> (16 * b + j)) as u8 & 1) << i;
+            }
+        }
+    }
+}
+
+fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    let mut block = [0u8; BLOCK_LEN];
+    block[..4].copy_from_slice(&word);
+    let mut q = pack(&[block]);
+    sub_bytes(&mut q);
+    let mut out = [[0u8; BLOCK_LEN]];
+    unpack(&q, &mut out);
+    out[0][..4].try_into().unwrap()
+}
+
+fn xtime_byte(b: u8) -> u8 {
+    (b << 1) ^ (0x1b & 0u8.wrapping_sub(b >> 7))
+}
+
+fn add_round_key(q: &mut State, round_key: &State) {
+    for (plane, k) in q.iter_mut().zip(round_key) {
+        *plane ^= k;
+    }
+}
+
+/// Bitsliced multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1.
+fn gf_mul(a: &State, b: &State) -> State {
+    let mut product = [0u64; 15];
+    for i in 0..8 {
+        for j in 0..8 {
+            product[i + j] ^= a[i] & b[j];
+        }
+    }
+    // x^8 = x^4 + x^3 + x + 1, folded from the top down.
+    for k in (8..15).rev() {
+        let high = product[k];
+        product[k - 4] ^= high;
+        product[k - 5] ^= high;
+        product[k - 7] ^= high;
+        product[k - 8] ^= high;
+    }
+    product[..8].try_into().unwrap()
+}
+
+/// x^254, which is x^-1 for x != 0 and maps 0 to 0.
+fn gf_inverse(x: &State) -> State {
+    let x2 = gf_mul(x, x);
+    let x3 = gf_mul(&x2, x);
+    let x6 = gf_mul(&x3, &x3);
+    let x12 = gf_mul(&x6, &x6);
+    let x15 = gf_mul(&x12, &x3);
+    let mut x240 = x15;
+    for _ in 0..4 {
+        x240 = gf_mul(&x240, &x240);
+    }
+    let x252 = gf_mul(&x240, &x12);
+    gf_mul(&x252, &x2)
+}
+
+/// Multiplication by x, bit plane i moving to i + 1.
+fn xtime(q: &State) -> State {
+    let top = q[7];
+    [
+        top,
+        q[0] ^ top,
+        q[1],
+        q[2] ^ top,
+        q[3] ^ top,
+        q[4],
+        q[5],
+        q[6],
+    ]
+}
+
+fn sub_bytes(q: &mut State) {
+    let inv = gf_inverse(q);
+    // The affine map b_i ^ b_(i+4) ^ b_(i+5) ^ b_(i+6) ^ b_(i+7) ^ c_i with
+    // c = 0x63, indices mod 8.
+    for i in 0..8 {
+        let c = 0u64.wrapping_sub(0x63 >> i & 1);
+        q[i] =
+            inv[i] ^ inv[(i + 4) % 8] ^ inv[(i + 5) % 8] ^ inv[(i + 6) % 8] ^ inv[(i + 7) % 8] ^ c;
+    }
+}
+
+fn inv_sub_bytes(q: &mut State) {
+    // The inverse affine map b_(i+2) ^ b_(i+5) ^ b_(i+7) ^ d_i with d = 0x05.
+    let s = *q;
+    for i in 0..8 {
+        let d = 0u64.wrapping_sub(0x05 >> i & 1);
+        q[i] = s[(i + 2) % 8] ^ s[(i + 5) % 8] ^ s[(i + 7) % 8] ^ d;
+    }
+    *q = gf_inverse(q);
+}
+
+/// `x` masked by `mask` repeated in each 16-bit lane.
+fn lane(x: u64, mask: u64) -> u64 {
+    x & (mask * 0x0001_0001_0001_0001)
+}
+
+/// Byte j of a block sits at row j % 4, column j / 4, so row r occupies
+/// bits r, r + 4, r + 8 and r + 12 of each 16-bit lane. Row r rotates left
+/// by r columns, which is a right rotation by 4r bits within the lane.
+fn shift_rows(q: &mut State) {
+    for plane in q.iter_mut() {
+        let x = *plane;
+        *plane = lane(x, 0x1111)
+            | lane(x, 0x2220) >> 4
+            | lane(x, 0x0002) << 12
+            | lane(x, 0x4400) >> 8
+            | lane(x, 0x0044) << 8
+            | lane(x, 0x8000) >> 12
+            | lane(x, 0x0888) << 4;
+    }
+}
+
+fn inv_shift_rows(q: &mut State) {
+    for plane in q.iter_mut() {
+        let x = *plane;
+        *plane = lane(x, 0x1111)
+            | lane(x, 0x0222) << 4
+            | lane(x, 0x2000) >> 12
+            | lane(x, 0x4400) >> 8
+            | lane(x, 0x0044) << 8
+            | lane(x, 0x8880) >> 4
+            | lane(x, 0x0008) << 12;
+    }
+}
+
+/// Moves every byte up `n` rows within its column, so row r receives the
+/// byte from row r + n.
+fn rotate_rows(q: &State, n: u32) -> State {
+    let low = 0x1111u64 * ((1 << (4 - n)) - 1) * 0x0001_0001_0001_0001;
+    q.map(|x| (x >> n & low) | (x << (4 - n) & !low))
+}
+
+fn mix_columns(q: &mut State) {
+    // 2 a_r + 3 a_(r+1) + a_(r+2) + a_(r+3)
+    //   = 2 (a_r + a_(r+1)) + a_(r+1) + a_(r+2) + a_(r+3).
+    let r1 = rotate_rows(q, 1);
+    let r2 = rotate_rows(q, 2);
+    let r3 = rotate_rows(q, 3);
+    let mut sum = *q;
+    add_round_key(&mut sum, &r1);
+    let doubled = xtime(&sum);
+    for i in 0..8 {
+        q[i] = doubled[i] ^ r1[i] ^ r2[i] ^ r3[i];
+    }
+}
+
+fn inv_mix_columns(q: &mut State) {
+    // InvMixColumns is MixColumns after adding 4 (a_r + a_(r+2)) to each
+    // byte a_r.
+    let mut sum = rotate_rows(q, 2);
+    add_round_key(&mut sum, q);
+    let quadrupled = xtime(&xtime(&sum));
+    add_round_key(q, &quadrupled);
+    mix_columns(q);
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::hex;
+
+    pub(crate) struct Vector {
+        pub key: Vec<u8>,
+        pub iv: Vec<u8>,
+        pub aad: Vec<u8>,
+        pub plaintext: Vec<u8>,
+        pub ciphertext: Vec<u8>,
+        pub tag: Vec<u8>,
+    }
+
+    /// The lines of testdata/aes_vectors.txt for `mode`.
+    pub(crate) fn vectors(mode: &str) -> Vec<Vector> {
+        let field = |s: &str| {
+            if s == "-" {
+                Vec::new()
+            } else {
+                hex::decode(s).unwrap()
+            }
+        };
+        include_str!("../../testdata/aes_vectors.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .filter(|f| f[0] == mode)
+            .map(|f| Vector {
+                key: field(f[1]),
+                iv: field(f[2]),
+                aad: field(f[3]),
+                plaintext: field(f[4]),
+                ciphertext: field(f[5]),
+                tag: field(f[6]),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sp800_38a_ecb() {
+        let vectors = vectors("ecb");
+        assert_eq!(vectors.len(), 3);
+        for v in vectors {
+            let aes = Aes::new(&v.key).unwrap();
+            let mut blocks: Vec<[u8; 16]> = v
+                .plaintext
+                .chunks(16)
+                .map(|b| b.try_into().unwrap())
+                .collect();
+            aes.encrypt_blocks(&mut blocks);
+            assert_eq!(blocks.concat(), v.ciphertext);
+            aes.decrypt_blocks(&mut blocks);
+            assert_eq!(blocks.concat(), v.plaintext);
+        }
+    }
+
+    #[test]
+    fn test_fips197_appendix_c() {
+        let plaintext: [u8; 16] = hex::decode("00112233445566778899aabbccddeeff")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let key: Vec<u8> = (0..32).collect();
+        for (len, expected) in [
+            (16, "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (24, "dda97ca4864cdfe06eaf70a0ec0d7191"),
+            (32, "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            let aes = Aes::new(&key[..len]).unwrap();
+            let mut block = plaintext;
+            aes.encrypt_block(&mut block);
+            assert_eq!(hex::encode(&block), expected);
+            aes.decrypt_block(&mut block);
+            assert_eq!(block, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_sbox() {
+        // Every byte value through SubBytes and back, against a few known
+        // entries of the FIPS 197 table.
+        let mut blocks: Vec<[u8; 16]> = (0..16)
+            .map(|b| std::array::from_fn(|j| (16 * b + j) as u8))
+            .collect();
+        for chunk in blocks.chunks_mut(4) {
+            let mut q = pack(chunk);
+            sub_bytes(&mut q);
+            unpack(&q, chunk);
+        }
+        let sbox: Vec<u8> = blocks.concat();
+        assert_eq!(&sbox[..4], [0x63, 0x7c, 0x77, 0x7b]);
+        assert_eq!(sbox[0x53], 0xed);
+        assert_eq!(sbox[0xff], 0x16);
+        let mut sorted = sbox.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..=255).collect::<Vec<u8>>());
+        for chunk in blocks.chunks_mut(4) {
+            let mut q = pack(chunk);
+            inv_sub_bytes(&mut q);
+            unpack(&q, chunk);
+        }
+        assert_eq!(blocks.concat(), (0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_multiple_blocks() {
+        // Blocks are independent, whatever lane they land in.
+        let aes = Aes::new(&[0x2b; 16]).unwrap();
+        let mut blocks: Vec<[u8; 16]> = (0..7u8).map(|b| [b; 16]).collect();
+        let expected: Vec<[u8; 16]> = blocks
+            .iter()
+            .map(|block| {
+                let mut block = *block;
+                aes.encrypt_block(&mut block);
+                block
+            })
+            .collect();
+        aes.encrypt_blocks(&mut blocks);
+        assert_eq!(blocks, expected);
+        aes.decrypt_blocks(&mut blocks);
+        assert_eq!(blocks[6], [6; 16]);
+        assert_eq!(Aes::new(&[0; 20]).err(), Some(AesError::InvalidKeyLength));
+    }
+}
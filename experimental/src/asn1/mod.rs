@@ -0,0 +1,181 @@
+pub mod oid;
+pub mod reader;
+pub mod time;
+pub mod value;
+pub mod writer;
+
+pub use oid::ObjectIdentifier;
+pub use reader::DerReader;
+pub use time::DateTime;
+pub use value::{ber_to_der, Value};
+pub use writer::DerWriter;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Asn1Error {
+    /// A string contains characters its type does not allow.
+    InvalidString,
+    /// A date or time is out of range for its type.
+    InvalidTime,
+    /// An object identifier has fewer than two arcs, an out-of-range first
+    /// or second arc, or an arc that is non-minimal or over 64 bits.
+    InvalidOid,
+    /// The input ends inside a TLV.
+    Truncated,
+    /// An indefinite length, which DER forbids.
+    IndefiniteLength,
+    /// A length in long form where a shorter encoding exists.
+    NonMinimalLength,
+    /// A tag number in high-tag-number form that fits in five bits or
+    /// has leading zero groups.
+    NonMinimalTag,
+    /// A value has a different tag from the one the caller asked for.
+    UnexpectedTag { expected: Tag, found: Tag },
+    /// An INTEGER that is empty or starts with a redundant sign octet.
+    NonMinimalInteger,
+    /// A negative INTEGER where only non-negative values are allowed.
+    NegativeInteger,
+    /// An INTEGER too large for the requested type.
+    IntegerOverflow,
+    /// A BOOLEAN whose contents are not exactly 0x00 or 0xff.
+    InvalidBoolean,
+    /// A NULL with contents.
+    InvalidNull,
+    /// A BIT STRING with more than seven unused bits or non-zero padding.
+    InvalidBitString,
+    /// SET components not in ascending tag order, or SET OF elements not
+    /// in ascending order of their encodings.
+    UnsortedSet,
+    /// Bytes left over after the last expected value.
+    TrailingData,
+    /// A primitive encoding of a type that must be constructed or the
+    /// reverse, a reserved length octet, or the reserved universal tag 0.
+    InvalidForm,
+    /// Constructed values nested more than `reader::MAX_DEPTH` deep.
+    TooDeep,
+}
+
+/// The class bits of an identifier octet (X.690 section 8.1.2.2).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Class {
+    Universal,
+    Application,
+    ContextSpecific,
+    Private,
+}
+
+/// An ASN.1 tag: class, constructed flag and tag number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tag {
+    pub class: Class,
+    pub constructed: bool,
+    pub number: u32,
+}
+
+impl Tag {
+    pub const BOOLEAN: Tag = Tag::universal(1, false);
+    pub const INTEGER: Tag = Tag::universal(2, false);
+    pub const BIT_STRING: Tag = Tag::universal(3, false);
+    pub const OCTET_STRING: Tag = Tag::universal(4, false);
+    pub const NULL: Tag = Tag::universal(5, false);
+    pub const OBJECT_IDENTIFIER: Tag = Tag::universal(6, false);
+    pub const UTF8_STRING: Tag = Tag::universal(12, false);
+    pub const SEQUENCE: Tag = Tag::universal(16, true);
+    pub const SET: Tag = Tag::universal(17, true);
+    pub const PRINTABLE_STRING: Tag = Tag::universal(19, false);
+    pub const IA5_STRING: Tag = Tag::universal(22, false);
+    pub const UTC_TIME: Tag = Tag::universal(23, false);
+    pub const GENERALIZED_TIME: Tag = Tag::universal(24, false);
+
+    const fn universal(number: u32, constructed: bool) -> Self {
+        Self {
+            class: Class::Universal,
+            constructed,
+            number,
+        }
+    }
+
+    /// A context-specific tag [number].
+    pub const fn context(number: u32, constructed: bool) -> Self {
+        Self {
+            class: Class::ContextSpecific,
+            constructed,
+            number,
+        }
+    }
+
+    /// Appends the identifier octets, using the high-tag-number form for
+    /// numbers of 31 and above.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let class = match self.class {
+            Class::Universal => 0x00,
+            Class::Application => 0x40,
+            Class::ContextSpecific => 0x80,
+            Class::Private => 0xc0,
+        };
+        let first = class | if self.constructed { 0x20 } else { 0 };
+        if self.number < 31 {
+            out.push(first | self.number as u8);
+        } else {
+            out.push(first | 0x1f);
+            push_base128(out, self.number as u64);
+        }
+    }
+}
+
+/// Appends `value` in big-endian base 128 with the continuation bit set on
+/// all but the last octet, as tag numbers and OID arcs are encoded.
+pub(crate) fn push_base128(out: &mut Vec<u8>, value: u64) {
+    let groups = (64 - value.leading_zeros() as usize).div_ceil(7).max(1);
+    for i in (0..groups).rev() {
+        let byte = ((value >> (7 * i)) & 0x7f) as u8;
+        out.push(if i > 0 { byte | 0x80 } else { byte });
+    }
+}
+
+/// Appends a definite length in the shortest form (X.690 section 10.1).
+pub(crate) fn push_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_encoding() {
+        let encode = |tag: Tag| {
+            let mut out = Vec::new();
+            tag.encode(&mut out);
+            out
+        };
+        assert_eq!(encode(Tag::SEQUENCE), [0x30]);
+        assert_eq!(encode(Tag::context(0, true)), [0xa0]);
+        assert_eq!(encode(Tag::context(2, false)), [0x82]);
+        assert_eq!(encode(Tag::context(30, false)), [0x9e]);
+        assert_eq!(encode(Tag::context(31, false)), [0x9f, 0x1f]);
+        assert_eq!(encode(Tag::context(201, true)), [0xbf, 0x81, 0x49]);
+    }
+
+    #[test]
+    fn test_length_encoding() {
+        for (len, expected) in [
+            (0usize, &[0x00][..]),
+            (127, &[0x7f]),
+            (128, &[0x81, 0x80]),
+            (255, &[0x81, 0xff]),
+            (256, &[0x82, 0x01, 0x00]),
+            (65536, &[0x83, 0x01, 0x00, 0x00]),
+        ] {
+            let mut out = Vec::new();
+            push_length(&mut out, len);
+            assert_eq!(out, expected, "{}", len);
+        }
+    }
+}
@@ -0,0 +1,471 @@
+use super::Asn1Error;
+use crate::bigint::BigInt;
+use std::fmt;
+use std::str::FromStr;
+
+/// Longest OID contents accepted, in bytes. Real OIDs stay well under
+/// this; the longest in the registry below is 11.
+pub const MAX_LEN: usize = 63;
+
+/// An OBJECT IDENTIFIER, held as its DER contents octets so that
+/// comparison and hashing are a byte compare and the value is `Copy`.
+/// Build constants with `from_arcs`, parse dotted text with `parse`, and
+/// decode DER with `from_der_contents`.
+///
+/// Bytes past `len` are always zero, so the derived comparisons agree
+/// with comparing contents, and the registry constants can be used as
+/// `match` patterns.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectIdentifier {
+    len: u8,
+    bytes: [u8; MAX_LEN],
+}
+
+impl ObjectIdentifier {
+    /// From arcs. The first arc must be 0, 1 or 2, and the second below
+    /// 40 unless the first is 2 (X.690 section 8.19).
+    pub const fn new(arcs: &[u64]) -> Result<Self, Asn1Error> {
+        if arcs.len() < 2 || arcs[0] > 2 || (arcs[0] < 2 && arcs[1] >= 40) {
+            return Err(Asn1Error::InvalidOid);
+        }
+        let Some(first) = (arcs[0] * 40).checked_add(arcs[1]) else {
+            return Err(Asn1Error::InvalidOid);
+        };
+        let mut oid = Self {
+            len: 0,
+            bytes: [0; MAX_LEN],
+        };
+        if !oid.push(first) {
+            return Err(Asn1Error::InvalidOid);
+        }
+        let mut i = 2;
+        while i < arcs.len() {
+            if !oid.push(arcs[i]) {
+                return Err(Asn1Error::InvalidOid);
+            }
+            i += 1;
+        }
+        Ok(oid)
+    }
+
+    /// As `new`, for constants; panics (at compile time in a `const`) on
+    /// invalid arcs.
+    pub const fn from_arcs(arcs: &[u64]) -> Self {
+        match Self::new(arcs) {
+            Ok(oid) => oid,
+            Err(_) => panic!("invalid object identifier"),
+        }
+    }
+
+    /// From DER contents octets, rejecting empty contents, arcs with
+    /// leading 0x80 padding and a truncated last arc. Arcs may be of any
+    /// width, such as the 128-bit UUIDs under 2.25 (X.667).
+    pub fn from_der_contents(contents: &[u8]) -> Result<Self, Asn1Error> {
+        if contents.is_empty() || contents.len() > MAX_LEN {
+            return Err(Asn1Error::InvalidOid);
+        }
+        if contents.last().unwrap() & 0x80 != 0 {
+            return Err(Asn1Error::InvalidOid);
+        }
+        // Each arc starts at the head or after a byte without the
+        // continuation bit, and must not start with 0x80.
+        let mut start = true;
+        for &b in contents {
+            if start && b == 0x80 {
+                return Err(Asn1Error::InvalidOid);
+            }
+            start = b & 0x80 == 0;
+        }
+        let mut bytes = [0; MAX_LEN];
+        bytes[..contents.len()].copy_from_slice(contents);
+        Ok(Self {
+            len: contents.len() as u8,
+            bytes,
+        })
+    }
+
+    /// The DER contents octets.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// The arcs, or `InvalidOid` if one is wider than 64 bits. `Display`
+    /// has no such limit.
+    pub fn arcs(&self) -> Result<Vec<u64>, Asn1Error> {
+        self.wide_arcs()
+            .iter()
+            .map(|arc| match arc.to_bytes_be() {
+                bytes if bytes.len() <= 8 => {
+                    Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | b as u64))
+                }
+                _ => Err(Asn1Error::InvalidOid),
+            })
+            .collect()
+    }
+
+    /// The arcs at full width, splitting the first subidentifier into
+    /// two arcs.
+    fn wide_arcs(&self) -> Vec<BigInt> {
+        let mut arcs = Vec::new();
+        let mut value = BigInt::from_u64(0);
+        for &b in self.as_bytes() {
+            value = &(&value << 7) + &BigInt::from_u64((b & 0x7f) as u64);
+            if b & 0x80 != 0 {
+                continue;
+            }
+            if arcs.is_empty() {
+                let first = if value >= 80u64 {
+                    2
+                } else if value >= 40u64 {
+                    1
+                } else {
+                    0
+                };
+                arcs.push(BigInt::from_u64(first));
+                value = &value - &BigInt::from_u64(40 * first);
+            }
+            arcs.push(std::mem::replace(&mut value, BigInt::from_u64(0)));
+        }
+        arcs
+    }
+
+    /// The registered name, such as `commonName` for 2.5.4.3.
+    pub fn name(&self) -> Option<&'static str> {
+        REGISTRY
+            .iter()
+            .find(|(oid, _)| oid == self)
+            .map(|&(_, name)| name)
+    }
+
+    /// The OID registered under `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        REGISTRY
+            .iter()
+            .find(|&&(_, n)| n == name)
+            .map(|&(oid, _)| oid)
+    }
+
+    /// Appends `arc` in base 128; returns false if that would exceed
+    /// `MAX_LEN`.
+    const fn push(&mut self, arc: u64) -> bool {
+        let bits = 64 - arc.leading_zeros() as usize;
+        let groups = if bits == 0 { 1 } else { bits.div_ceil(7) };
+        if self.len as usize + groups > MAX_LEN {
+            return false;
+        }
+        let mut i = groups;
+        while i > 0 {
+            i -= 1;
+            let byte = ((arc >> (7 * i)) & 0x7f) as u8;
+            self.bytes[self.len as usize] = if i > 0 { byte | 0x80 } else { byte };
+            self.len += 1;
+        }
+        true
+    }
+}
+
+/// Dotted notation: decimal arcs without signs or leading zeros.
+impl FromStr for ObjectIdentifier {
+    type Err = Asn1Error;
+
+    fn from_str(s: &str) -> Result<Self, Asn1Error> {
+        let arcs = s
+            .split('.')
+            .map(|arc| {
+                // MAX_LEN bytes of base 128 hold under 3 * MAX_LEN digits,
+                // so longer arcs need not be converted.
+                let canonical = !arc.is_empty()
+                    && arc.len() < 3 * MAX_LEN
+                    && arc.bytes().all(|b| b.is_ascii_digit())
+                    && (arc == "0" || !arc.starts_with('0'));
+                if !canonical {
+                    return Err(Asn1Error::InvalidOid);
+                }
+                let ten = BigInt::from_u64(10);
+                Ok(arc.bytes().fold(BigInt::from_u64(0), |acc, b| {
+                    &(&acc * &ten) + &BigInt::from_u64((b - b'0') as u64)
+                }))
+            })
+            .collect::<Result<Vec<BigInt>, _>>()?;
+        if arcs.len() < 2 || arcs[0] > 2u64 || (arcs[0] < 2u64 && arcs[1] >= 40u64) {
+            return Err(Asn1Error::InvalidOid);
+        }
+        let first = &(&arcs[0] * &BigInt::from_u64(40)) + &arcs[1];
+        let mut contents = Vec::new();
+        for arc in std::iter::once(&first).chain(&arcs[2..]) {
+            let groups = arc.bit_length().div_ceil(7).max(1);
+            for i in (0..groups).rev() {
+                let byte = (0..7).fold(0u8, |acc, bit| acc | (arc.bit(7 * i + bit) as u8) << bit);
+                contents.push(if i > 0 { byte | 0x80 } else { byte });
+            }
+        }
+        Self::from_der_contents(&contents)
+    }
+}
+
+impl fmt::Display for ObjectIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Decimal in 19-digit chunks, the most that fit in a u64.
+        let chunk = BigInt::from_u64(10_000_000_000_000_000_000);
+        for (i, mut arc) in self.wide_arcs().into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            let mut chunks = Vec::new();
+            loop {
+                chunks.push(&arc % 10_000_000_000_000_000_000);
+                arc = &arc / &chunk;
+                if arc.is_zero() {
+                    break;
+                }
+            }
+            write!(f, "{}", chunks.pop().unwrap())?;
+            for chunk in chunks.iter().rev() {
+                write!(f, "{:019}", chunk)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ObjectIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "ObjectIdentifier({} {})", self, name),
+            None => write!(f, "ObjectIdentifier({})", self),
+        }
+    }
+}
+
+macro_rules! registry {
+    ($($(#[$doc:meta])* $constant:ident = [$($arc:expr),+] => $name:literal;)*) => {
+        $(
+            $(#[$doc])*
+            pub const $constant: ObjectIdentifier = ObjectIdentifier::from_arcs(&[$($arc),+]);
+        )*
+
+        const REGISTRY: &[(ObjectIdentifier, &str)] = &[$(($constant, $name)),*];
+    };
+}
+
+// Names are the ASN.1 value names from the defining RFCs.
+registry! {
+    // Hash functions (RFC 3279, RFC 5754, NIST CSOR).
+    SHA1 = [1, 3, 14, 3, 2, 26] => "sha1";
+    SHA224 = [2, 16, 840, 1, 101, 3, 4, 2, 4] => "sha224";
+    SHA256 = [2, 16, 840, 1, 101, 3, 4, 2, 1] => "sha256";
+    SHA384 = [2, 16, 840, 1, 101, 3, 4, 2, 2] => "sha384";
+    SHA512 = [2, 16, 840, 1, 101, 3, 4, 2, 3] => "sha512";
+    SHA3_224 = [2, 16, 840, 1, 101, 3, 4, 2, 7] => "sha3-224";
+    SHA3_256 = [2, 16, 840, 1, 101, 3, 4, 2, 8] => "sha3-256";
+    SHA3_384 = [2, 16, 840, 1, 101, 3, 4, 2, 9] => "sha3-384";
+    SHA3_512 = [2, 16, 840, 1, 101, 3, 4, 2, 10] => "sha3-512";
+
+    // RSA (RFC 8017).
+    RSA_ENCRYPTION = [1, 2, 840, 113549, 1, 1, 1] => "rsaEncryption";
+    RSAES_OAEP = [1, 2, 840, 113549, 1, 1, 7] => "id-RSAES-OAEP";
+    MGF1 = [1, 2, 840, 113549, 1, 1, 8] => "id-mgf1";
+    RSASSA_PSS = [1, 2, 840, 113549, 1, 1, 10] => "id-RSASSA-PSS";
+    SHA1_WITH_RSA = [1, 2, 840, 113549, 1, 1, 5] => "sha1WithRSAEncryption";
+    SHA256_WITH_RSA = [1, 2, 840, 113549, 1, 1, 11] => "sha256WithRSAEncryption";
+    SHA384_WITH_RSA = [1, 2, 840, 113549, 1, 1, 12] => "sha384WithRSAEncryption";
+    SHA512_WITH_RSA = [1, 2, 840, 113549, 1, 1, 13] => "sha512WithRSAEncryption";
+    SHA224_WITH_RSA = [1, 2, 840, 113549, 1, 1, 14] => "sha224WithRSAEncryption";
+
+    // Elliptic curves (RFC 5480, RFC 5758, RFC 8410).
+    EC_PUBLIC_KEY = [1, 2, 840, 10045, 2, 1] => "id-ecPublicKey";
+    SECP256R1 = [1, 2, 840, 10045, 3, 1, 7] => "secp256r1";
+    SECP384R1 = [1, 3, 132, 0, 34] => "secp384r1";
+    SECP521R1 = [1, 3, 132, 0, 35] => "secp521r1";
+    SECP256K1 = [1, 3, 132, 0, 10] => "secp256k1";
+    ECDSA_WITH_SHA1 = [1, 2, 840, 10045, 4, 1] => "ecdsa-with-SHA1";
+    ECDSA_WITH_SHA224 = [1, 2, 840, 10045, 4, 3, 1] => "ecdsa-with-SHA224";
+    ECDSA_WITH_SHA256 = [1, 2, 840, 10045, 4, 3, 2] => "ecdsa-with-SHA256";
+    ECDSA_WITH_SHA384 = [1, 2, 840, 10045, 4, 3, 3] => "ecdsa-with-SHA384";
+    ECDSA_WITH_SHA512 = [1, 2, 840, 10045, 4, 3, 4] => "ecdsa-with-SHA512";
+    X25519 = [1, 3, 101, 110] => "id-X25519";
+    ED25519 = [1, 3, 101, 112] => "id-Ed25519";
+
+    // DSA and Diffie-Hellman (RFC 3279, RFC 5758).
+    DSA = [1, 2, 840, 10040, 4, 1] => "id-dsa";
+    DSA_WITH_SHA1 = [1, 2, 840, 10040, 4, 3] => "id-dsa-with-sha1";
+    DSA_WITH_SHA224 = [2, 16, 840, 1, 101, 3, 4, 3, 1] => "id-dsa-with-sha224";
+    DSA_WITH_SHA256 = [2, 16, 840, 1, 101, 3, 4, 3, 2] => "id-dsa-with-sha256";
+    DH_PUBLIC_NUMBER = [1, 2, 840, 10046, 2, 1] => "dhpublicnumber";
+
+    // Password-based encryption and ciphers (RFC 8018, RFC 7914, NIST
+    // CSOR).
+    PBKDF2 = [1, 2, 840, 113549, 1, 5, 12] => "id-PBKDF2";
+    PBES2 = [1, 2, 840, 113549, 1, 5, 13] => "id-PBES2";
+    HMAC_WITH_SHA1 = [1, 2, 840, 113549, 2, 7] => "id-hmacWithSHA1";
+    HMAC_WITH_SHA256 = [1, 2, 840, 113549, 2, 9] => "id-hmacWithSHA256";
+    HMAC_WITH_SHA384 = [1, 2, 840, 113549, 2, 10] => "id-hmacWithSHA384";
+    HMAC_WITH_SHA512 = [1, 2, 840, 113549, 2, 11] => "id-hmacWithSHA512";
+    SCRYPT = [1, 3, 6, 1, 4, 1, 11591, 4, 11] => "id-scrypt";
+    AES128_CBC = [2, 16, 840, 1, 101, 3, 4, 1, 2] => "aes128-CBC";
+    AES192_CBC = [2, 16, 840, 1, 101, 3, 4, 1, 22] => "aes192-CBC";
+    AES256_CBC = [2, 16, 840, 1, 101, 3, 4, 1, 42] => "aes256-CBC";
+    AES128_GCM = [2, 16, 840, 1, 101, 3, 4, 1, 6] => "aes128-GCM";
+    AES192_GCM = [2, 16, 840, 1, 101, 3, 4, 1, 26] => "aes192-GCM";
+    AES256_GCM = [2, 16, 840, 1, 101, 3, 4, 1, 46] => "aes256-GCM";
+
+    // PKCS #7 / CMS content types and PKCS #9 attributes (RFC 5652,
+    // RFC 2985).
+    DATA = [1, 2, 840, 113549, 1, 7, 1] => "id-data";
+    SIGNED_DATA = [1, 2, 840, 113549, 1, 7, 2] => "id-signedData";
+    ENVELOPED_DATA = [1, 2, 840, 113549, 1, 7, 3] => "id-envelopedData";
+    ENCRYPTED_DATA = [1, 2, 840, 113549, 1, 7, 6] => "id-encryptedData";
+    EMAIL_ADDRESS = [1, 2, 840, 113549, 1, 9, 1] => "emailAddress";
+    CONTENT_TYPE = [1, 2, 840, 113549, 1, 9, 3] => "contentType";
+    MESSAGE_DIGEST = [1, 2, 840, 113549, 1, 9, 4] => "messageDigest";
+    SIGNING_TIME = [1, 2, 840, 113549, 1, 9, 5] => "signingTime";
+
+    // Distinguished name attributes (RFC 5280 appendix A, RFC 4519).
+    COMMON_NAME = [2, 5, 4, 3] => "commonName";
+    SURNAME = [2, 5, 4, 4] => "surname";
+    SERIAL_NUMBER = [2, 5, 4, 5] => "serialNumber";
+    COUNTRY_NAME = [2, 5, 4, 6] => "countryName";
+    LOCALITY_NAME = [2, 5, 4, 7] => "localityName";
+    STATE_OR_PROVINCE_NAME = [2, 5, 4, 8] => "stateOrProvinceName";
+    STREET_ADDRESS = [2, 5, 4, 9] => "streetAddress";
+    ORGANIZATION_NAME = [2, 5, 4, 10] => "organizationName";
+    ORGANIZATIONAL_UNIT_NAME = [2, 5, 4, 11] => "organizationalUnitName";
+    TITLE = [2, 5, 4, 12] => "title";
+    GIVEN_NAME = [2, 5, 4, 42] => "givenName";
+    DN_QUALIFIER = [2, 5, 4, 46] => "dnQualifier";
+    DOMAIN_COMPONENT = [0, 9, 2342, 19200300, 100, 1, 25] => "domainComponent";
+    USER_ID = [0, 9, 2342, 19200300, 100, 1, 1] => "userId";
+
+    // Certificate extensions (RFC 5280 section 4.2, RFC 6962).
+    SUBJECT_KEY_IDENTIFIER = [2, 5, 29, 14] => "subjectKeyIdentifier";
+    KEY_USAGE = [2, 5, 29, 15] => "keyUsage";
+    SUBJECT_ALT_NAME = [2, 5, 29, 17] => "subjectAltName";
+    ISSUER_ALT_NAME = [2, 5, 29, 18] => "issuerAltName";
+    BASIC_CONSTRAINTS = [2, 5, 29, 19] => "basicConstraints";
+    CRL_NUMBER = [2, 5, 29, 20] => "cRLNumber";
+    NAME_CONSTRAINTS = [2, 5, 29, 30] => "nameConstraints";
+    CRL_DISTRIBUTION_POINTS = [2, 5, 29, 31] => "cRLDistributionPoints";
+    CERTIFICATE_POLICIES = [2, 5, 29, 32] => "certificatePolicies";
+    POLICY_CONSTRAINTS = [2, 5, 29, 36] => "policyConstraints";
+    AUTHORITY_KEY_IDENTIFIER = [2, 5, 29, 35] => "authorityKeyIdentifier";
+    EXT_KEY_USAGE = [2, 5, 29, 37] => "extKeyUsage";
+    AUTHORITY_INFO_ACCESS = [1, 3, 6, 1, 5, 5, 7, 1, 1] => "authorityInfoAccess";
+    SCT_LIST = [1, 3, 6, 1, 4, 1, 11129, 2, 4, 2] => "signedCertificateTimestampList";
+    AD_OCSP = [1, 3, 6, 1, 5, 5, 7, 48, 1] => "id-ad-ocsp";
+    AD_CA_ISSUERS = [1, 3, 6, 1, 5, 5, 7, 48, 2] => "id-ad-caIssuers";
+
+    // Extended key usages (RFC 5280 section 4.2.1.12).
+    ANY_EXTENDED_KEY_USAGE = [2, 5, 29, 37, 0] => "anyExtendedKeyUsage";
+    SERVER_AUTH = [1, 3, 6, 1, 5, 5, 7, 3, 1] => "serverAuth";
+    CLIENT_AUTH = [1, 3, 6, 1, 5, 5, 7, 3, 2] => "clientAuth";
+    CODE_SIGNING = [1, 3, 6, 1, 5, 5, 7, 3, 3] => "codeSigning";
+    EMAIL_PROTECTION = [1, 3, 6, 1, 5, 5, 7, 3, 4] => "emailProtection";
+    TIME_STAMPING = [1, 3, 6, 1, 5, 5, 7, 3, 8] => "timeStamping";
+    OCSP_SIGNING = [1, 3, 6, 1, 5, 5, 7, 3, 9] => "OCSPSigning";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    #[test]
+    fn test_dotted_round_trip() {
+        for (dotted, der) in [
+            ("1.2.840.113549.1.1.11", "2a864886f70d01010b"),
+            ("2.999.3", "883703"),
+            ("0.0", "00"),
+            ("2.5.4.3", "550403"),
+            ("1.3.6.1.4.1.11129.2.4.2", "2b06010401d679020402"),
+            ("2.25.18446744073709551615", "6981ffffffffffffffff7f"),
+            ("1.2.18446744073709551616", "2a82808080808080808000"),
+            ("2.18446744073709551536", "82808080808080808000"),
+            // A UUID arc (X.667 section 6.3).
+            (
+                "2.25.329800735698586629295641978511506172918",
+                "6983f09da7ebcfdee0c7a1a7b2c0948cc8f9d776",
+            ),
+        ] {
+            let oid: ObjectIdentifier = dotted.parse().unwrap();
+            assert_eq!(hex::encode(oid.as_bytes()), der, "{}", dotted);
+            assert_eq!(oid.to_string(), dotted);
+            let decoded = ObjectIdentifier::from_der_contents(oid.as_bytes()).unwrap();
+            assert_eq!(decoded, oid);
+        }
+        for dotted in [
+            "", "1", "3.1", "1.40", "0.40.1", "1..2", "1.2.", ".1.2", "1.02", "1.+2", "1.2.a",
+            "00.1",
+        ] {
+            assert_eq!(
+                dotted.parse::<ObjectIdentifier>(),
+                Err(Asn1Error::InvalidOid),
+                "{}",
+                dotted
+            );
+        }
+        // Arcs are limited only by MAX_LEN.
+        let wide = format!("2.25.{}", "9".repeat(3 * MAX_LEN - 1));
+        assert_eq!(wide.parse::<ObjectIdentifier>(), Err(Asn1Error::InvalidOid));
+    }
+
+    #[test]
+    fn test_der_contents_validation() {
+        for der in ["", "8055", "5580", "55048003", "550480"] {
+            assert_eq!(
+                ObjectIdentifier::from_der_contents(&hex::decode(der).unwrap()),
+                Err(Asn1Error::InvalidOid),
+                "{}",
+                der
+            );
+        }
+        assert_eq!(
+            ObjectIdentifier::from_der_contents(&[0x2b; MAX_LEN + 1]),
+            Err(Asn1Error::InvalidOid)
+        );
+    }
+
+    #[test]
+    fn test_wide_arcs() {
+        let oid =
+            ObjectIdentifier::from_der_contents(&hex::decode("6981ffffffffffffffff7f").unwrap())
+                .unwrap();
+        assert_eq!(oid.arcs(), Ok(vec![2, 25, u64::MAX]));
+        let uuid: ObjectIdentifier = "2.25.329800735698586629295641978511506172918"
+            .parse()
+            .unwrap();
+        assert_eq!(uuid.arcs(), Err(Asn1Error::InvalidOid));
+        assert_eq!(
+            format!("{:?}", uuid),
+            "ObjectIdentifier(2.25.329800735698586629295641978511506172918)"
+        );
+        // Chunks of the decimal conversion keep their zeros.
+        let round: ObjectIdentifier = "1.2.100000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            round.to_string(),
+            "1.2.100000000000000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(COMMON_NAME.name(), Some("commonName"));
+        assert_eq!(
+            ObjectIdentifier::from_name("ecdsa-with-SHA256"),
+            Some(ECDSA_WITH_SHA256)
+        );
+        assert_eq!(
+            format!("{:?}", SERVER_AUTH),
+            "ObjectIdentifier(1.3.6.1.5.5.7.3.1 serverAuth)"
+        );
+        let unknown: ObjectIdentifier = "1.2.3.4".parse().unwrap();
+        assert_eq!(unknown.name(), None);
+        assert_eq!(format!("{:?}", unknown), "ObjectIdentifier(1.2.3.4)");
+        for (i, (a, name)) in REGISTRY.iter().enumerate() {
+            for (b, other) in &REGISTRY[i + 1..] {
+                assert!(a != b && name != other, "{} / {}", name, other);
+            }
+        }
+    }
+}
@@ -0,0 +1,736 @@
+use super::{Asn1Error, Class, DateTime, ObjectIdentifier, Tag};
+use crate::bigint::BigInt;
+
+/// How deeply constructed values may nest before parsing gives up, so a
+/// hostile input cannot exhaust the stack. Certificates nest about ten
+/// levels deep.
+pub const MAX_DEPTH: usize = 32;
+
+/// One TLV as it sits in the input: its tag, its contents and the whole
+/// encoding including the header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tlv<'a> {
+    pub tag: Tag,
+    pub contents: &'a [u8],
+    pub encoded: &'a [u8],
+}
+
+/// Strict DER reader over a borrowed slice.
+///
+/// Each `read_*`/typed method consumes one TLV from the front and fails
+/// with a specific `Asn1Error` on anything DER does not allow: long-form
+/// or indefinite lengths where the short form fits, non-minimal integers
+/// and tags, unsorted SETs, primitive values of the wrong shape, and data
+/// left over inside a constructed value or after the outermost one.
+/// Nothing is copied; strings and contents borrow from the input.
+#[derive(Clone, Debug)]
+pub struct DerReader<'a> {
+    data: &'a [u8],
+    depth: usize,
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, depth: 0 }
+    }
+
+    /// Runs `f` over `data` and requires it to consume everything.
+    pub fn parse<R>(
+        data: &'a [u8],
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        let mut reader = Self::new(data);
+        let result = f(&mut reader)?;
+        reader.finish()?;
+        Ok(result)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Fails with `TrailingData` unless everything has been read.
+    pub fn finish(&self) -> Result<(), Asn1Error> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(Asn1Error::TrailingData)
+        }
+    }
+
+    /// The tag of the next value, or `None` at the end of the input.
+    pub fn peek_tag(&self) -> Result<Option<Tag>, Asn1Error> {
+        if self.data.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(read_tag(self.data)?.0))
+    }
+
+    /// Reads the next TLV whatever its tag.
+    pub fn read_any(&mut self) -> Result<Tlv<'a>, Asn1Error> {
+        let (tlv, rest) = split_tlv(self.data)?;
+        self.data = rest;
+        Ok(tlv)
+    }
+
+    /// Reads the next TLV, which must have tag `tag`, and returns its
+    /// contents.
+    pub fn read(&mut self, tag: Tag) -> Result<&'a [u8], Asn1Error> {
+        let (tlv, rest) = split_tlv(self.data)?;
+        if tlv.tag != tag {
+            return Err(Asn1Error::UnexpectedTag {
+                expected: tag,
+                found: tlv.tag,
+            });
+        }
+        self.data = rest;
+        Ok(tlv.contents)
+    }
+
+    /// Reads the next value only if it has tag `tag`.
+    pub fn read_optional(&mut self, tag: Tag) -> Result<Option<&'a [u8]>, Asn1Error> {
+        if self.peek_tag()? == Some(tag) {
+            return self.read(tag).map(Some);
+        }
+        Ok(None)
+    }
+
+    pub fn boolean(&mut self) -> Result<bool, Asn1Error> {
+        match self.read(Tag::BOOLEAN)? {
+            [0x00] => Ok(false),
+            [0xff] => Ok(true),
+            _ => Err(Asn1Error::InvalidBoolean),
+        }
+    }
+
+    /// A non-negative INTEGER.
+    pub fn integer(&mut self) -> Result<BigInt, Asn1Error> {
+        let contents = integer_contents(self.read(Tag::INTEGER)?)?;
+        if contents[0] & 0x80 != 0 {
+            return Err(Asn1Error::NegativeInteger);
+        }
+        Ok(BigInt::from_bytes_be(contents))
+    }
+
+    /// An INTEGER that fits in an i64, such as a version number.
+    pub fn integer_i64(&mut self) -> Result<i64, Asn1Error> {
+        let contents = integer_contents(self.read(Tag::INTEGER)?)?;
+        if contents.len() > 8 {
+            return Err(Asn1Error::IntegerOverflow);
+        }
+        let sign = if contents[0] & 0x80 != 0 { 0xff } else { 0x00 };
+        let mut bytes = [sign; 8];
+        bytes[8 - contents.len()..].copy_from_slice(contents);
+        Ok(i64::from_be_bytes(bytes))
+    }
+
+    /// A BIT STRING as (bytes, number of unused bits in the last byte).
+    pub fn bit_string(&mut self) -> Result<(&'a [u8], u8), Asn1Error> {
+        let contents = self.read(Tag::BIT_STRING)?;
+        let Some((&unused, bytes)) = contents.split_first() else {
+            return Err(Asn1Error::InvalidBitString);
+        };
+        let padding = bytes
+            .last()
+            .map_or(0, |b| b & ((1u16 << unused.min(8)) - 1) as u8);
+        if unused > 7 || (bytes.is_empty() && unused != 0) || padding != 0 {
+            return Err(Asn1Error::InvalidBitString);
+        }
+        Ok((bytes, unused))
+    }
+
+    pub fn octet_string(&mut self) -> Result<&'a [u8], Asn1Error> {
+        self.read(Tag::OCTET_STRING)
+    }
+
+    pub fn null(&mut self) -> Result<(), Asn1Error> {
+        if !self.read(Tag::NULL)?.is_empty() {
+            return Err(Asn1Error::InvalidNull);
+        }
+        Ok(())
+    }
+
+    pub fn oid(&mut self) -> Result<ObjectIdentifier, Asn1Error> {
+        ObjectIdentifier::from_der_contents(self.read(Tag::OBJECT_IDENTIFIER)?)
+    }
+
+    pub fn utf8_string(&mut self) -> Result<&'a str, Asn1Error> {
+        std::str::from_utf8(self.read(Tag::UTF8_STRING)?).map_err(|_| Asn1Error::InvalidString)
+    }
+
+    pub fn printable_string(&mut self) -> Result<&'a str, Asn1Error> {
+        let contents = self.read(Tag::PRINTABLE_STRING)?;
+        if !contents.iter().all(|&b| super::writer::is_printable(b)) {
+            return Err(Asn1Error::InvalidString);
+        }
+        Ok(std::str::from_utf8(contents).expect("printable is ASCII"))
+    }
+
+    pub fn ia5_string(&mut self) -> Result<&'a str, Asn1Error> {
+        let contents = self.read(Tag::IA5_STRING)?;
+        if !contents.is_ascii() {
+            return Err(Asn1Error::InvalidString);
+        }
+        Ok(std::str::from_utf8(contents).expect("ASCII"))
+    }
+
+    pub fn utc_time(&mut self) -> Result<DateTime, Asn1Error> {
+        DateTime::from_utc_time(self.read(Tag::UTC_TIME)?)
+    }
+
+    pub fn generalized_time(&mut self) -> Result<DateTime, Asn1Error> {
+        DateTime::from_generalized_time(self.read(Tag::GENERALIZED_TIME)?)
+    }
+
+    /// Reads a constructed value with tag `tag` and runs `f` over its
+    /// contents, which `f` must consume entirely.
+    pub fn constructed<R>(
+        &mut self,
+        tag: Tag,
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        let contents = self.read(tag)?;
+        let mut inner = self.nested(contents)?;
+        let result = f(&mut inner)?;
+        inner.finish()?;
+        Ok(result)
+    }
+
+    pub fn sequence<R>(
+        &mut self,
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        self.constructed(Tag::SEQUENCE, f)
+    }
+
+    /// A SET OF, whose elements must be sorted by encoding.
+    pub fn set_of<R>(
+        &mut self,
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        let contents = self.read(Tag::SET)?;
+        check_sorted(contents)?;
+        let mut inner = self.nested(contents)?;
+        let result = f(&mut inner)?;
+        inner.finish()?;
+        Ok(result)
+    }
+
+    /// A SET, whose components must be in ascending tag order, by class
+    /// and then number; see `DerWriter::set`.
+    pub fn set<R>(
+        &mut self,
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        let contents = self.read(Tag::SET)?;
+        check_tag_order(contents)?;
+        let mut inner = self.nested(contents)?;
+        let result = f(&mut inner)?;
+        inner.finish()?;
+        Ok(result)
+    }
+
+    /// An EXPLICIT [number] wrapper; `f` reads what is inside.
+    pub fn explicit<R>(
+        &mut self,
+        number: u32,
+        f: impl FnOnce(&mut DerReader<'a>) -> Result<R, Asn1Error>,
+    ) -> Result<R, Asn1Error> {
+        self.constructed(Tag::context(number, true), f)
+    }
+
+    /// `AlgorithmIdentifier ::= SEQUENCE { algorithm OID, parameters ANY
+    /// OPTIONAL }`, with the parameters left for the caller to interpret.
+    pub fn algorithm_identifier(
+        &mut self,
+    ) -> Result<(ObjectIdentifier, Option<Tlv<'a>>), Asn1Error> {
+        self.sequence(|r| {
+            let oid = r.oid()?;
+            let params = if r.is_empty() {
+                None
+            } else {
+                Some(r.read_any()?)
+            };
+            Ok((oid, params))
+        })
+    }
+
+    fn nested(&self, data: &'a [u8]) -> Result<DerReader<'a>, Asn1Error> {
+        if self.depth >= MAX_DEPTH {
+            return Err(Asn1Error::TooDeep);
+        }
+        Ok(DerReader {
+            data,
+            depth: self.depth + 1,
+        })
+    }
+}
+
+/// INTEGER contents, which must be non-empty and minimal: the first nine
+/// bits may not all be equal (X.690 section 8.3.2).
+fn integer_contents(contents: &[u8]) -> Result<&[u8], Asn1Error> {
+    match contents {
+        [] => Err(Asn1Error::NonMinimalInteger),
+        [0x00, b, ..] if b & 0x80 == 0 => Err(Asn1Error::NonMinimalInteger),
+        [0xff, b, ..] if b & 0x80 != 0 => Err(Asn1Error::NonMinimalInteger),
+        _ => Ok(contents),
+    }
+}
+
+/// Checks that the TLVs in `contents` are in ascending order of encoding.
+pub(crate) fn check_sorted(contents: &[u8]) -> Result<(), Asn1Error> {
+    let mut rest = contents;
+    let mut previous: Option<&[u8]> = None;
+    while !rest.is_empty() {
+        let (tlv, next) = split_tlv(rest)?;
+        if previous.is_some_and(|p| p > tlv.encoded) {
+            return Err(Asn1Error::UnsortedSet);
+        }
+        previous = Some(tlv.encoded);
+        rest = next;
+    }
+    Ok(())
+}
+
+/// Checks that the TLVs in `contents` have strictly ascending tags, by
+/// class and then number, as the components of a SET must (X.690 section
+/// 10.3).
+pub(crate) fn check_tag_order(contents: &[u8]) -> Result<(), Asn1Error> {
+    let mut rest = contents;
+    let mut previous = None;
+    while !rest.is_empty() {
+        let (tlv, next) = split_tlv(rest)?;
+        let key = (tlv.tag.class, tlv.tag.number);
+        if previous.is_some_and(|p| p >= key) {
+            return Err(Asn1Error::UnsortedSet);
+        }
+        previous = Some(key);
+        rest = next;
+    }
+    Ok(())
+}
+
+/// Base-128 with continuation bits, as in high tag numbers.
+/// Returns the value and the number of bytes used, or `None` if the
+/// encoding is non-minimal (a leading 0x80), truncated or over 64 bits.
+pub(crate) fn read_base128(data: &[u8]) -> Option<(u64, usize)> {
+    if data.first() == Some(&0x80) {
+        return None;
+    }
+    let mut value = 0u64;
+    for (i, &b) in data.iter().enumerate() {
+        if value >> 57 != 0 {
+            return None;
+        }
+        value = (value << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Decodes the identifier octets, returning the tag and their length.
+pub(crate) fn read_tag(data: &[u8]) -> Result<(Tag, usize), Asn1Error> {
+    let &first = data.first().ok_or(Asn1Error::Truncated)?;
+    let class = match first >> 6 {
+        0 => Class::Universal,
+        1 => Class::Application,
+        2 => Class::ContextSpecific,
+        _ => Class::Private,
+    };
+    let constructed = first & 0x20 != 0;
+    if first & 0x1f != 0x1f {
+        let number = (first & 0x1f) as u32;
+        return Ok((
+            Tag {
+                class,
+                constructed,
+                number,
+            },
+            1,
+        ));
+    }
+    let (number, used) = read_base128(&data[1..]).ok_or(if data.len() < 3 {
+        Asn1Error::Truncated
+    } else {
+        Asn1Error::NonMinimalTag
+    })?;
+    // The high-tag-number form is only for numbers that do not fit in five
+    // bits.
+    if number < 31 || number > u32::MAX as u64 {
+        return Err(Asn1Error::NonMinimalTag);
+    }
+    let tag = Tag {
+        class,
+        constructed,
+        number: number as u32,
+    };
+    Ok((tag, 1 + used))
+}
+
+/// Decodes a definite length in its shortest form, returning it and the
+/// number of length octets.
+pub(crate) fn read_length(data: &[u8]) -> Result<(usize, usize), Asn1Error> {
+    let &first = data.first().ok_or(Asn1Error::Truncated)?;
+    if first < 0x80 {
+        return Ok((first as usize, 1));
+    }
+    if first == 0x80 {
+        return Err(Asn1Error::IndefiniteLength);
+    }
+    let n = (first & 0x7f) as usize;
+    let bytes = data.get(1..1 + n).ok_or(Asn1Error::Truncated)?;
+    if bytes[0] == 0 {
+        return Err(Asn1Error::NonMinimalLength);
+    }
+    if n > std::mem::size_of::<usize>() {
+        return Err(Asn1Error::Truncated);
+    }
+    let len = bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+    if len < 0x80 {
+        return Err(Asn1Error::NonMinimalLength);
+    }
+    Ok((len, 1 + n))
+}
+
+/// Splits the first TLV off `data`.
+pub(crate) fn split_tlv(data: &[u8]) -> Result<(Tlv<'_>, &[u8]), Asn1Error> {
+    let (tag, tag_len) = read_tag(data)?;
+    let (len, len_len) = read_length(&data[tag_len..])?;
+    let header = tag_len + len_len;
+    if data.len() - header < len {
+        return Err(Asn1Error::Truncated);
+    }
+    let tlv = Tlv {
+        tag,
+        contents: &data[header..header + len],
+        encoded: &data[..header + len],
+    };
+    Ok((tlv, &data[header + len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1::{oid, DerWriter};
+    use crate::hex;
+
+    fn reader(s: &str) -> Vec<u8> {
+        hex::decode(s).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let time = DateTime::new(2025, 2, 2, 12, 0, 0).unwrap();
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.explicit(0, |w| w.integer_i64(2));
+            w.integer(&BigInt::from_hex("0123456789abcdef0123456789abcdef"));
+            w.integer_i64(-129);
+            w.boolean(true);
+            w.bit_string(&[0xa0], 5);
+            w.octet_string(b"octets");
+            w.null();
+            w.oid(&oid::COMMON_NAME);
+            w.utf8_string("caf\u{e9}");
+            w.printable_string("Example CA").unwrap();
+            w.ia5_string("ca@example.com").unwrap();
+            w.utc_time(&time).unwrap();
+            w.generalized_time(&time);
+            w.set_of(|w| {
+                w.integer_i64(300);
+                w.integer_i64(1);
+            });
+            w.implicit(2, |w| w.octet_string(b"dns"));
+        });
+        let der = w.finish();
+
+        DerReader::parse(&der, |r| {
+            r.sequence(|r| {
+                assert_eq!(r.explicit(0, |r| r.integer_i64())?, 2);
+                assert_eq!(
+                    r.integer()?,
+                    BigInt::from_hex("0123456789abcdef0123456789abcdef")
+                );
+                assert_eq!(r.integer_i64()?, -129);
+                assert!(r.boolean()?);
+                assert_eq!(r.bit_string()?, (&[0xa0][..], 5));
+                assert_eq!(r.octet_string()?, b"octets");
+                r.null()?;
+                assert_eq!(r.oid()?, oid::COMMON_NAME);
+                assert_eq!(r.utf8_string()?, "caf\u{e9}");
+                assert_eq!(r.printable_string()?, "Example CA");
+                assert_eq!(r.ia5_string()?, "ca@example.com");
+                assert_eq!(r.utc_time()?, time);
+                assert_eq!(r.generalized_time()?, time);
+                r.set_of(|r| {
+                    assert_eq!((r.integer_i64()?, r.integer_i64()?), (1, 300));
+                    Ok(())
+                })?;
+                assert_eq!(r.read_optional(Tag::context(0, false))?, None);
+                assert_eq!(r.read_optional(Tag::context(2, false))?, Some(&b"dns"[..]));
+                Ok(())
+            })
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_length_errors() {
+        for (der, err) in [
+            ("", Asn1Error::Truncated),
+            ("04", Asn1Error::Truncated),
+            ("0403aabb", Asn1Error::Truncated),
+            ("0481", Asn1Error::Truncated),
+            ("0480aa0000", Asn1Error::IndefiniteLength),
+            ("048101aa", Asn1Error::NonMinimalLength),
+            ("04820001aa", Asn1Error::NonMinimalLength),
+            ("048900000000000000000100", Asn1Error::NonMinimalLength),
+            ("0489010000000000000000", Asn1Error::Truncated),
+            ("0401aa00", Asn1Error::TrailingData),
+        ] {
+            let der = reader(der);
+            assert_eq!(
+                DerReader::parse(&der, |r| r.octet_string().map(|_| ())),
+                Err(err),
+                "{}",
+                hex::encode(&der)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tag_errors() {
+        // [5] in high-tag-number form, with a padded tag number, and cut
+        // short.
+        for (der, err) in [
+            ("9f0500", Asn1Error::NonMinimalTag),
+            ("9f801f00", Asn1Error::NonMinimalTag),
+            ("9f", Asn1Error::Truncated),
+        ] {
+            assert_eq!(DerReader::new(&reader(der)).read_any(), Err(err), "{}", der);
+        }
+        let der = reader("9f1f00");
+        let tlv = DerReader::new(&der).read_any().unwrap();
+        assert_eq!(tlv.tag, Tag::context(31, false));
+        let der = reader("020101");
+        assert_eq!(
+            DerReader::new(&der).octet_string(),
+            Err(Asn1Error::UnexpectedTag {
+                expected: Tag::OCTET_STRING,
+                found: Tag::INTEGER
+            })
+        );
+    }
+
+    #[test]
+    fn test_value_errors() {
+        let check = |der: &str, f: fn(&mut DerReader) -> Result<(), Asn1Error>, err| {
+            let der = reader(der);
+            assert_eq!(DerReader::parse(&der, f), Err(err), "{}", hex::encode(&der));
+        };
+        let integer = |r: &mut DerReader| r.integer().map(|_| ());
+        check("0200", integer, Asn1Error::NonMinimalInteger);
+        check("0202007f", integer, Asn1Error::NonMinimalInteger);
+        check("0202ff80", integer, Asn1Error::NonMinimalInteger);
+        check("0201ff", integer, Asn1Error::NegativeInteger);
+        check(
+            "0209010000000000000000",
+            |r| r.integer_i64().map(|_| ()),
+            Asn1Error::IntegerOverflow,
+        );
+        check(
+            "010101",
+            |r| r.boolean().map(|_| ()),
+            Asn1Error::InvalidBoolean,
+        );
+        check("050100", |r| r.null(), Asn1Error::InvalidNull);
+        let bits = |r: &mut DerReader| r.bit_string().map(|_| ());
+        check("0300", bits, Asn1Error::InvalidBitString);
+        check("030101", bits, Asn1Error::InvalidBitString);
+        check("030208ff", bits, Asn1Error::InvalidBitString);
+        check("030203ff", bits, Asn1Error::InvalidBitString);
+        let oid = |r: &mut DerReader| r.oid().map(|_| ());
+        check("0600", oid, Asn1Error::InvalidOid);
+        check("0603550480", oid, Asn1Error::InvalidOid);
+        check("060355800403", oid, Asn1Error::InvalidOid);
+        check(
+            "0c01ff",
+            |r| r.utf8_string().map(|_| ()),
+            Asn1Error::InvalidString,
+        );
+        check(
+            "130140",
+            |r| r.printable_string().map(|_| ()),
+            Asn1Error::InvalidString,
+        );
+        check(
+            "1601ff",
+            |r| r.ia5_string().map(|_| ()),
+            Asn1Error::InvalidString,
+        );
+        // UTCTime without seconds or with an offset, and February 30.
+        check(
+            "170b323530323032313230305a",
+            |r| r.utc_time().map(|_| ()),
+            Asn1Error::InvalidTime,
+        );
+        check(
+            "17113235303230323132303030302b30313030",
+            |r| r.utc_time().map(|_| ()),
+            Asn1Error::InvalidTime,
+        );
+        check(
+            "170d3235303233303132303030305a",
+            |r| r.utc_time().map(|_| ()),
+            Asn1Error::InvalidTime,
+        );
+        // GeneralizedTime with fractional seconds.
+        check(
+            "181132303235303230323132303030302e355a",
+            |r| r.generalized_time().map(|_| ()),
+            Asn1Error::InvalidTime,
+        );
+    }
+
+    #[test]
+    fn test_structure_errors() {
+        // Unsorted SET OF, and an element left unread inside a SEQUENCE.
+        let der = reader("3106020102020101");
+        assert_eq!(
+            DerReader::parse(&der, |r| r.set_of(|r| {
+                r.integer_i64()?;
+                r.integer_i64()?;
+                Ok(())
+            })),
+            Err(Asn1Error::UnsortedSet)
+        );
+        let der = reader("3006020101020102");
+        assert_eq!(
+            DerReader::parse(&der, |r| r.sequence(|r| r.integer_i64())),
+            Err(Asn1Error::TrailingData)
+        );
+    }
+
+    #[test]
+    fn test_set_tag_order() {
+        let read = |r: &mut DerReader| {
+            r.read_any()?;
+            r.read_any()?;
+            Ok(())
+        };
+        // Constructed [0] then primitive [1] is in tag order but not
+        // encoding order; the reverse is the other way round.
+        let tag_order = reader("3107a0020500810105");
+        let byte_order = reader("3107810105a0020500");
+        assert_eq!(DerReader::parse(&tag_order, |r| r.set(read)), Ok(()));
+        assert_eq!(
+            DerReader::parse(&byte_order, |r| r.set(read)),
+            Err(Asn1Error::UnsortedSet)
+        );
+        assert_eq!(DerReader::parse(&byte_order, |r| r.set_of(read)), Ok(()));
+        assert_eq!(
+            DerReader::parse(&tag_order, |r| r.set_of(read)),
+            Err(Asn1Error::UnsortedSet)
+        );
+
+        // [31] before [200] in the high-tag-number form, and no repeats.
+        let high = reader("31079f1f009f814800");
+        assert_eq!(DerReader::parse(&high, |r| r.set(read)), Ok(()));
+        let high = reader("31079f8148009f1f00");
+        assert_eq!(
+            DerReader::parse(&high, |r| r.set(read)),
+            Err(Asn1Error::UnsortedSet)
+        );
+        let repeated = reader("3106020101020102");
+        assert_eq!(
+            DerReader::parse(&repeated, |r| r.set(read)),
+            Err(Asn1Error::UnsortedSet)
+        );
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let nest = |depth: usize| {
+            let mut der = reader("0500");
+            for _ in 0..depth {
+                let mut w = DerWriter::new();
+                w.sequence(|w| w.raw(&der));
+                der = w.finish();
+            }
+            der
+        };
+        fn descend(r: &mut DerReader) -> Result<(), Asn1Error> {
+            if r.peek_tag()? == Some(Tag::NULL) {
+                return r.null();
+            }
+            r.sequence(descend)
+        }
+        assert_eq!(DerReader::parse(&nest(MAX_DEPTH), descend), Ok(()));
+        assert_eq!(
+            DerReader::parse(&nest(MAX_DEPTH + 1), descend),
+            Err(Asn1Error::TooDeep)
+        );
+    }
+
+    /// Decodes every value in `r` with the decoder for its tag.
+    fn walk(r: &mut DerReader) -> Result<(), Asn1Error> {
+        while let Some(tag) = r.peek_tag()? {
+            match tag {
+                Tag::BOOLEAN => r.boolean().map(|_| ())?,
+                Tag::INTEGER => r.integer().map(|_| ())?,
+                Tag::BIT_STRING => r.bit_string().map(|_| ())?,
+                Tag::NULL => r.null()?,
+                Tag::OBJECT_IDENTIFIER => r.oid().map(|_| ())?,
+                Tag::UTF8_STRING => r.utf8_string().map(|_| ())?,
+                Tag::PRINTABLE_STRING => r.printable_string().map(|_| ())?,
+                Tag::UTC_TIME => r.utc_time().map(|_| ())?,
+                Tag::SEQUENCE => r.sequence(walk)?,
+                Tag::SET => r.set_of(walk)?,
+                tag if tag.constructed => r.constructed(tag, walk)?,
+                _ => r.read_any().map(|_| ())?,
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_input_does_not_panic() {
+        let time = DateTime::new(2025, 2, 2, 12, 0, 0).unwrap();
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.explicit(0, |w| w.integer_i64(2));
+            w.integer(&BigInt::from_hex("00c0ffee"));
+            w.sequence(|w| {
+                w.oid(&oid::SHA256_WITH_RSA);
+                w.null();
+            });
+            w.set_of(|w| {
+                w.sequence(|w| {
+                    w.oid(&oid::COMMON_NAME);
+                    w.printable_string("Test").unwrap();
+                });
+            });
+            w.utc_time(&time).unwrap();
+            w.boolean(false);
+            w.bit_string(&[0x04, 0xab, 0xcd], 0);
+            w.utf8_string("x");
+        });
+        let der = w.finish();
+        assert_eq!(DerReader::parse(&der, walk), Ok(()));
+
+        for len in 1..der.len() {
+            assert!(DerReader::parse(&der[..len], walk).is_err());
+        }
+        for i in 0..der.len() {
+            for b in [0x00, 0x01, 0x1f, 0x7f, 0x80, 0x81, 0x84, 0x89, 0xff] {
+                let mut bad = der.clone();
+                bad[i] = b;
+                let _ = DerReader::parse(&bad, walk);
+                bad[i] ^= der[i].wrapping_add(1);
+                let _ = DerReader::parse(&bad, walk);
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use super::Asn1Error;
+
+/// A UTC calendar time to the second, as carried by UTCTime and
+/// GeneralizedTime. Years run from 0 to 9999; fractional seconds and
+/// time zones other than Z are not represented because DER forbids them
+/// in certificates (RFC 5280 section 4.1.2.5).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl DateTime {
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, Asn1Error> {
+        if year > 9999
+            || !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return Err(Asn1Error::InvalidTime);
+        }
+        Ok(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// The time `secs` seconds after 1970-01-01T00:00:00Z.
+    pub fn from_unix(secs: i64) -> Result<Self, Asn1Error> {
+        let days = secs.div_euclid(86400);
+        let rem = secs.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=9999).contains(&year) {
+            return Err(Asn1Error::InvalidTime);
+        }
+        Self::new(
+            year as u16,
+            month,
+            day,
+            (rem / 3600) as u8,
+            (rem / 60 % 60) as u8,
+            (rem % 60) as u8,
+        )
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z (negative before it).
+    pub fn unix(&self) -> i64 {
+        let days = days_from_civil(self.year as i64, self.month, self.day);
+        days * 86400 + self.hour as i64 * 3600 + self.minute as i64 * 60 + self.second as i64
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// YYMMDDHHMMSSZ. UTCTime only covers 1950 to 2049 (RFC 5280 section
+    /// 4.1.2.5.1).
+    pub(crate) fn to_utc_time(self) -> Result<String, Asn1Error> {
+        if !(1950..2050).contains(&self.year) {
+            return Err(Asn1Error::InvalidTime);
+        }
+        Ok(format!(
+            "{:02}{:02}{:02}{:02}{:02}{:02}Z",
+            self.year % 100,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second
+        ))
+    }
+
+    /// YYYYMMDDHHMMSSZ.
+    pub(crate) fn to_generalized_time(self) -> String {
+        format!(
+            "{:04}{:02}{:02}{:02}{:02}{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    /// Parses YYMMDDHHMMSSZ, mapping YY below 50 to 20YY and the rest to
+    /// 19YY.
+    pub(crate) fn from_utc_time(s: &[u8]) -> Result<Self, Asn1Error> {
+        let [year @ .., b'Z'] = s else {
+            return Err(Asn1Error::InvalidTime);
+        };
+        if year.len() != 12 {
+            return Err(Asn1Error::InvalidTime);
+        }
+        let yy = digits(&year[..2])?;
+        let year = if yy < 50 { 2000 + yy } else { 1900 + yy };
+        Self::from_digits(year, &s[2..12])
+    }
+
+    /// Parses YYYYMMDDHHMMSSZ. Fractional seconds are rejected, as RFC
+    /// 5280 section 4.1.2.5.2 requires.
+    pub(crate) fn from_generalized_time(s: &[u8]) -> Result<Self, Asn1Error> {
+        if s.len() != 15 || s[14] != b'Z' {
+            return Err(Asn1Error::InvalidTime);
+        }
+        Self::from_digits(digits(&s[..4])?, &s[4..14])
+    }
+
+    /// MMDDHHMMSS after the year.
+    fn from_digits(year: u16, s: &[u8]) -> Result<Self, Asn1Error> {
+        let field = |i: usize| digits(&s[2 * i..2 * i + 2]).map(|v| v as u8);
+        Self::new(year, field(0)?, field(1)?, field(2)?, field(3)?, field(4)?)
+    }
+}
+
+fn digits(s: &[u8]) -> Result<u16, Asn1Error> {
+    s.iter().try_fold(0u16, |acc, &b| {
+        if b.is_ascii_digit() {
+            Ok(acc * 10 + (b - b'0') as u16)
+        } else {
+            Err(Asn1Error::InvalidTime)
+        }
+    })
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar, counting
+/// in 400-year eras that start on March 1 so the leap day comes last.
+fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Inverse of `days_from_civil`.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u8;
+    let month = ((month_from_march + 2) % 12 + 1) as u8;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_conversion() {
+        // From `date -u -d @<secs>`.
+        for (secs, expected) in [
+            (0i64, (1970, 1, 1, 0, 0, 0)),
+            (951782400, (2000, 2, 29, 0, 0, 0)),
+            (2147483647, (2038, 1, 19, 3, 14, 7)),
+            (-1, (1969, 12, 31, 23, 59, 59)),
+            (253402300799, (9999, 12, 31, 23, 59, 59)),
+            (-62167219200, (0, 1, 1, 0, 0, 0)),
+        ] {
+            let (y, mo, d, h, mi, s) = expected;
+            let t = DateTime::new(y, mo, d, h, mi, s).unwrap();
+            assert_eq!(DateTime::from_unix(secs), Ok(t), "{}", secs);
+            assert_eq!(t.unix(), secs);
+        }
+        assert_eq!(
+            DateTime::from_unix(253402300800),
+            Err(Asn1Error::InvalidTime)
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert!(DateTime::new(2000, 2, 29, 0, 0, 0).is_ok());
+        for (y, mo, d, h, mi, s) in [
+            (2023, 2, 29, 0, 0, 0),
+            (1900, 2, 29, 0, 0, 0),
+            (2024, 4, 31, 0, 0, 0),
+            (2024, 13, 1, 0, 0, 0),
+            (2024, 1, 0, 0, 0, 0),
+            (2024, 1, 1, 24, 0, 0),
+            (2024, 1, 1, 0, 60, 0),
+            (2024, 1, 1, 0, 0, 60),
+            (10000, 1, 1, 0, 0, 0),
+        ] {
+            assert_eq!(
+                DateTime::new(y, mo, d, h, mi, s),
+                Err(Asn1Error::InvalidTime)
+            );
+        }
+    }
+
+    #[test]
+    fn test_formatting() {
+        let t = DateTime::new(2049, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(t.to_utc_time().unwrap(), "491231235959Z");
+        assert_eq!(t.to_generalized_time(), "20491231235959Z");
+        let t = DateTime::new(2050, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(t.to_utc_time(), Err(Asn1Error::InvalidTime));
+        assert_eq!(t.to_generalized_time(), "20500101000000Z");
+    }
+
+    #[test]
+    fn test_parsing() {
+        let t = DateTime::new(2049, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(DateTime::from_utc_time(b"491231235959Z"), Ok(t));
+        assert_eq!(DateTime::from_generalized_time(b"20491231235959Z"), Ok(t));
+        let t = DateTime::new(1950, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(DateTime::from_utc_time(b"500101000000Z"), Ok(t));
+        for s in [
+            &b"4912312359Z"[..],
+            b"491231235959",
+            b"491231235959+0000",
+            b"49123123595 Z",
+            b"4912312359-9Z",
+            b"491331235959Z",
+        ] {
+            assert_eq!(DateTime::from_utc_time(s), Err(Asn1Error::InvalidTime));
+        }
+        for s in [
+            &b"20491231235959.5Z"[..],
+            b"20491231235959",
+            b"204912312359Z",
+            b"+0491231235959Z",
+        ] {
+            assert_eq!(
+                DateTime::from_generalized_time(s),
+                Err(Asn1Error::InvalidTime)
+            );
+        }
+    }
+}
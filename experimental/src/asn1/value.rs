@@ -0,0 +1,411 @@
+use super::reader::{check_sorted, check_tag_order, read_length, read_tag, MAX_DEPTH};
+use super::{push_length, Asn1Error, Class, Tag};
+
+/// A decoded value tree, owned so that BER input can be normalized into
+/// it. Every tree is in DER form: strings are primitive, lengths are
+/// implied, BOOLEAN true is 0xff and SET elements are in DER order (see
+/// `sort_set`), so `to_der` reproduces the canonical encoding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Primitive { tag: Tag, contents: Vec<u8> },
+    Constructed { tag: Tag, children: Vec<Value> },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Der,
+    Ber,
+}
+
+impl Value {
+    pub fn tag(&self) -> Tag {
+        match self {
+            Value::Primitive { tag, .. } | Value::Constructed { tag, .. } => *tag,
+        }
+    }
+
+    /// Parses exactly one value, rejecting anything DER does not allow.
+    pub fn from_der(der: &[u8]) -> Result<Self, Asn1Error> {
+        parse_all(der, Mode::Der)
+    }
+
+    /// Parses exactly one BER value and normalizes it: indefinite and
+    /// non-minimal lengths are resolved, constructed strings of universal
+    /// type are concatenated, BOOLEAN true becomes 0xff, BIT STRING
+    /// padding is cleared and SET elements are sorted.
+    ///
+    /// Strings under IMPLICIT tags cannot be recognized without the
+    /// schema, so a constructed [n] stays constructed.
+    pub fn from_ber(ber: &[u8]) -> Result<Self, Asn1Error> {
+        parse_all(ber, Mode::Ber)
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode(&mut out);
+        out
+    }
+
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            Value::Primitive { tag, contents } => {
+                tag.encode(out);
+                push_length(out, contents.len());
+                out.extend_from_slice(contents);
+            }
+            Value::Constructed { tag, children } => {
+                let mut encoded: Vec<Vec<u8>> = children.iter().map(Value::to_der).collect();
+                if *tag == Tag::SET {
+                    sort_set(&mut encoded, |der| der);
+                }
+                tag.encode(out);
+                push_length(out, encoded.iter().map(Vec::len).sum());
+                for child in encoded {
+                    out.extend_from_slice(&child);
+                }
+            }
+        }
+    }
+}
+
+/// Re-encodes BER as DER, for checking signatures computed over the DER
+/// form of content that arrived as BER.
+pub fn ber_to_der(ber: &[u8]) -> Result<Vec<u8>, Asn1Error> {
+    Ok(Value::from_ber(ber)?.to_der())
+}
+
+fn parse_all(data: &[u8], mode: Mode) -> Result<Value, Asn1Error> {
+    let (value, rest) = parse(data, mode, 0)?;
+    if !rest.is_empty() {
+        return Err(Asn1Error::TrailingData);
+    }
+    Ok(value)
+}
+
+/// Universal string types, which BER may send in constructed form
+/// (X.690 section 8.23.6).
+fn is_string(tag: Tag) -> bool {
+    tag.class == Class::Universal && matches!(tag.number, 3 | 4 | 12 | 18..=30)
+}
+
+fn parse(data: &[u8], mode: Mode, depth: usize) -> Result<(Value, &[u8]), Asn1Error> {
+    let (tag, tag_len) = read_tag(data)?;
+    let data = &data[tag_len..];
+    let (len, len_len) = match mode {
+        Mode::Der => read_length(data).map(|(len, n)| (Some(len), n))?,
+        Mode::Ber => read_ber_length(data)?,
+    };
+    let data = &data[len_len..];
+    if tag.class == Class::Universal && tag.number == 0 {
+        return Err(Asn1Error::InvalidForm);
+    }
+
+    if !tag.constructed {
+        let len = len.ok_or(Asn1Error::IndefiniteLength)?;
+        if data.len() < len {
+            return Err(Asn1Error::Truncated);
+        }
+        let contents = primitive_contents(tag, &data[..len], mode)?;
+        return Ok((Value::Primitive { tag, contents }, &data[len..]));
+    }
+
+    if tag.class == Class::Universal && !is_string(tag) && !matches!(tag.number, 16 | 17) {
+        return Err(Asn1Error::InvalidForm);
+    }
+    if depth >= MAX_DEPTH {
+        return Err(Asn1Error::TooDeep);
+    }
+    let mut children = Vec::new();
+    let rest = match len {
+        Some(len) => {
+            if data.len() < len {
+                return Err(Asn1Error::Truncated);
+            }
+            if mode == Mode::Der && tag == Tag::SET {
+                check_tag_order(&data[..len]).or_else(|_| check_sorted(&data[..len]))?;
+            }
+            let mut contents = &data[..len];
+            while !contents.is_empty() {
+                let (child, next) = parse(contents, mode, depth + 1)?;
+                children.push(child);
+                contents = next;
+            }
+            &data[len..]
+        }
+        None => {
+            let mut contents = data;
+            loop {
+                if let [0x00, 0x00, rest @ ..] = contents {
+                    break rest;
+                }
+                let (child, next) = parse(contents, mode, depth + 1)?;
+                children.push(child);
+                contents = next;
+            }
+        }
+    };
+
+    if is_string(tag) {
+        if mode == Mode::Der {
+            return Err(Asn1Error::InvalidForm);
+        }
+        let tag = Tag {
+            constructed: false,
+            ..tag
+        };
+        let contents = join_segments(tag, children)?;
+        return Ok((Value::Primitive { tag, contents }, rest));
+    }
+    if tag == Tag::SET {
+        let mut encoded: Vec<(Vec<u8>, Value)> =
+            children.into_iter().map(|c| (c.to_der(), c)).collect();
+        sort_set(&mut encoded, |(der, _)| der);
+        children = encoded.into_iter().map(|(_, c)| c).collect();
+    }
+    Ok((Value::Constructed { tag, children }, rest))
+}
+
+/// Puts SET elements in DER order, given each one's encoding. A SET's
+/// components go in tag order (X.690 section 10.3) and a SET OF's elements
+/// in encoding order (section 11.6). Without the schema the two look
+/// alike, so elements already in either order are left alone and any
+/// others are sorted by tag and then by encoding. The orders only differ
+/// when primitive and constructed elements are mixed.
+fn sort_set<T>(elements: &mut [T], der: impl Fn(&T) -> &[u8]) {
+    let tag_order = |der: &[u8]| {
+        let (tag, _) = read_tag(der).unwrap();
+        (tag.class, tag.number)
+    };
+    let pairs = || elements.windows(2).map(|w| (der(&w[0]), der(&w[1])));
+    if pairs().all(|(a, b)| tag_order(a) < tag_order(b)) || pairs().all(|(a, b)| a <= b) {
+        return;
+    }
+    elements.sort_by(|a, b| {
+        let (a, b) = (der(a), der(b));
+        tag_order(a).cmp(&tag_order(b)).then(a.cmp(b))
+    });
+}
+
+/// Checks the contents of a primitive universal value, normalizing what
+/// BER leaves open.
+fn primitive_contents(tag: Tag, contents: &[u8], mode: Mode) -> Result<Vec<u8>, Asn1Error> {
+    if tag.class != Class::Universal {
+        return Ok(contents.to_vec());
+    }
+    match tag {
+        Tag {
+            number: 16 | 17, ..
+        } => Err(Asn1Error::InvalidForm),
+        Tag::BOOLEAN => match (contents, mode) {
+            ([0x00], _) | ([0xff], _) => Ok(contents.to_vec()),
+            ([_], Mode::Ber) => Ok(vec![0xff]),
+            _ => Err(Asn1Error::InvalidBoolean),
+        },
+        Tag::INTEGER => match contents {
+            [] => Err(Asn1Error::NonMinimalInteger),
+            [0x00, b, ..] if b & 0x80 == 0 => Err(Asn1Error::NonMinimalInteger),
+            [0xff, b, ..] if b & 0x80 != 0 => Err(Asn1Error::NonMinimalInteger),
+            _ => Ok(contents.to_vec()),
+        },
+        Tag::NULL if !contents.is_empty() => Err(Asn1Error::InvalidNull),
+        Tag::BIT_STRING => {
+            let mut contents = contents.to_vec();
+            let (unused, bytes) = contents
+                .split_first_mut()
+                .ok_or(Asn1Error::InvalidBitString)?;
+            if *unused > 7 || (bytes.is_empty() && *unused != 0) {
+                return Err(Asn1Error::InvalidBitString);
+            }
+            if let Some(last) = bytes.last_mut() {
+                let mask = (1u8 << *unused) - 1;
+                if *last & mask != 0 && mode == Mode::Der {
+                    return Err(Asn1Error::InvalidBitString);
+                }
+                *last &= !mask;
+            }
+            Ok(contents)
+        }
+        _ => Ok(contents.to_vec()),
+    }
+}
+
+/// Concatenates the segments of a constructed string, which must all be
+/// (already flattened) strings of the same type. For BIT STRING only the
+/// last segment may have unused bits.
+fn join_segments(tag: Tag, segments: Vec<Value>) -> Result<Vec<u8>, Asn1Error> {
+    let bit_string = tag == Tag::BIT_STRING;
+    let mut out = if bit_string { vec![0] } else { Vec::new() };
+    let count = segments.len();
+    for (i, segment) in segments.into_iter().enumerate() {
+        let Value::Primitive {
+            tag: segment_tag,
+            contents,
+        } = segment
+        else {
+            return Err(Asn1Error::InvalidForm);
+        };
+        if segment_tag != tag {
+            return Err(Asn1Error::UnexpectedTag {
+                expected: tag,
+                found: segment_tag,
+            });
+        }
+        if bit_string {
+            if contents[0] != 0 && i + 1 != count {
+                return Err(Asn1Error::InvalidBitString);
+            }
+            out[0] = contents[0];
+            out.extend_from_slice(&contents[1..]);
+        } else {
+            out.extend_from_slice(&contents);
+        }
+    }
+    Ok(out)
+}
+
+/// A BER length: `None` for the indefinite form, and long forms of any
+/// size accepted as long as the value fits.
+fn read_ber_length(data: &[u8]) -> Result<(Option<usize>, usize), Asn1Error> {
+    let &first = data.first().ok_or(Asn1Error::Truncated)?;
+    match first {
+        0x00..=0x7f => Ok((Some(first as usize), 1)),
+        0x80 => Ok((None, 1)),
+        // Reserved (X.690 section 8.1.3.5).
+        0xff => Err(Asn1Error::InvalidForm),
+        _ => {
+            let n = (first & 0x7f) as usize;
+            let bytes = data.get(1..1 + n).ok_or(Asn1Error::Truncated)?;
+            let len = bytes.iter().try_fold(0usize, |acc, &b| {
+                acc.checked_mul(256)
+                    .map(|acc| acc | b as usize)
+                    .ok_or(Asn1Error::Truncated)
+            })?;
+            Ok((Some(len), 1 + n))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1::{oid, DerReader};
+    use crate::hex;
+
+    const CMS_BER: &[u8] = include_bytes!("../../testdata/cms_signed_streamed.ber");
+    const CMS_DER: &[u8] = include_bytes!("../../testdata/cms_signed.der");
+
+    fn convert(ber: &str) -> Result<String, Asn1Error> {
+        ber_to_der(&hex::decode(ber).unwrap()).map(|der| hex::encode(&der))
+    }
+
+    #[test]
+    fn test_ber_normalization() {
+        for (ber, der) in [
+            // Indefinite lengths, nested.
+            ("3080020101308000000000", "30050201013000"),
+            // Non-minimal lengths.
+            ("048101aa", "0401aa"),
+            ("04840000000100", "040100"),
+            // Constructed OCTET STRING, with a constructed segment.
+            ("24800402aabb24030401cc0000", "0403aabbcc"),
+            // Constructed BIT STRING; only the last segment has unused bits.
+            ("2380030200aa030204b10000", "030304aab0"),
+            ("0302040f", "03020400"),
+            // BOOLEAN true in any non-zero form.
+            ("010101", "0101ff"),
+            // SET elements sorted.
+            ("3106020102020101", "3106020101020102"),
+            // Mixed forms in neither order go in tag order.
+            ("3109810105a00205000500", "31090500a0020500810105"),
+            // Context-specific constructed stays constructed.
+            ("a080040100040101a08000000000", "a008040100040101a000"),
+        ] {
+            assert_eq!(convert(ber), Ok(der.to_string()), "{}", ber);
+            let der_value = Value::from_der(&hex::decode(der).unwrap()).unwrap();
+            assert_eq!(Value::from_ber(&hex::decode(ber).unwrap()), Ok(der_value));
+        }
+    }
+
+    #[test]
+    fn test_ber_errors() {
+        for (ber, err) in [
+            ("3080020101", Asn1Error::Truncated),
+            ("0480aa0000", Asn1Error::IndefiniteLength),
+            (
+                "2403020100",
+                Asn1Error::UnexpectedTag {
+                    expected: Tag::OCTET_STRING,
+                    found: Tag::INTEGER,
+                },
+            ),
+            ("2308030204b0030200aa", Asn1Error::InvalidBitString),
+            ("300000", Asn1Error::TrailingData),
+            ("3001ff", Asn1Error::Truncated),
+            ("1000", Asn1Error::InvalidForm),
+            ("2203020101", Asn1Error::InvalidForm),
+            ("02020001", Asn1Error::NonMinimalInteger),
+            ("04ff", Asn1Error::InvalidForm),
+        ] {
+            assert_eq!(convert(ber), Err(err), "{}", ber);
+        }
+    }
+
+    #[test]
+    fn test_der_mode_is_strict() {
+        for (der, err) in [
+            ("3080020101000000", Asn1Error::IndefiniteLength),
+            ("048101aa", Asn1Error::NonMinimalLength),
+            ("2403040100", Asn1Error::InvalidForm),
+            ("010101", Asn1Error::InvalidBoolean),
+            ("0302040f", Asn1Error::InvalidBitString),
+            ("3106020102020101", Asn1Error::UnsortedSet),
+            ("3109810105a00205000500", Asn1Error::UnsortedSet),
+        ] {
+            assert_eq!(
+                Value::from_der(&hex::decode(der).unwrap()),
+                Err(err),
+                "{}",
+                der
+            );
+        }
+        // A SET in tag order and a SET OF in encoding order both survive
+        // the round trip.
+        for der in [
+            "3107a0020500810105",
+            "3107810105a0020500",
+            "31079f1f009f814800",
+        ] {
+            let der = hex::decode(der).unwrap();
+            assert_eq!(Value::from_der(&der).unwrap().to_der(), der);
+        }
+        assert_eq!(Value::from_der(CMS_DER).unwrap().to_der(), CMS_DER);
+    }
+
+    #[test]
+    fn test_streamed_cms() {
+        // `openssl cms -sign -stream -outform DER`, and the same structure
+        // re-encoded by `openssl cms -cmsout -outform DER`.
+        assert_eq!(Value::from_der(CMS_BER), Err(Asn1Error::IndefiniteLength));
+        let der = ber_to_der(CMS_BER).unwrap();
+        assert_eq!(der, CMS_DER);
+        DerReader::parse(&der, |r| {
+            r.sequence(|r| {
+                assert_eq!(r.oid()?, oid::SIGNED_DATA);
+                r.explicit(0, |r| r.read_any().map(|_| ()))
+            })
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let ber = [
+            [0x30, 0x80].repeat(MAX_DEPTH + 1),
+            [0x00; 2].repeat(MAX_DEPTH + 1),
+        ]
+        .concat();
+        assert_eq!(Value::from_ber(&ber), Err(Asn1Error::TooDeep));
+        let ber = [[0x30, 0x80].repeat(MAX_DEPTH), [0x00; 2].repeat(MAX_DEPTH)].concat();
+        assert!(Value::from_ber(&ber).is_ok());
+    }
+}
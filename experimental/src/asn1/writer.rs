@@ -0,0 +1,417 @@
+use super::reader::read_tag;
+use super::{push_length, Asn1Error, DateTime, ObjectIdentifier, Tag};
+use crate::bigint::BigInt;
+
+/// Builds a DER encoding front to back.
+///
+/// Primitive values are appended directly. Constructed values take a
+/// closure that writes their contents into a fresh writer; the contents
+/// are then wrapped with the tag and their (now known) definite length.
+/// Closures may return a value, typically a `Result`, which is passed
+/// through:
+///
+/// ```
+/// # use experimental::asn1::{oid, Asn1Error, DerWriter};
+/// let mut w = DerWriter::new();
+/// w.sequence(|w| {
+///     w.oid(&oid::COMMON_NAME);
+///     w.printable_string("Example CA")
+/// })?;
+/// assert_eq!(w.finish()[..2], [0x30, 0x11]);
+/// # Ok::<_, Asn1Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct DerWriter {
+    buf: Vec<u8>,
+}
+
+impl DerWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+
+    /// Appends pre-encoded DER, which must be a sequence of complete TLVs.
+    pub fn raw(&mut self, der: &[u8]) {
+        self.buf.extend_from_slice(der);
+    }
+
+    /// Appends one TLV with the given tag and contents.
+    pub fn tlv(&mut self, tag: Tag, contents: &[u8]) {
+        tag.encode(&mut self.buf);
+        push_length(&mut self.buf, contents.len());
+        self.buf.extend_from_slice(contents);
+    }
+
+    pub fn boolean(&mut self, value: bool) {
+        self.tlv(Tag::BOOLEAN, &[if value { 0xff } else { 0x00 }]);
+    }
+
+    /// A non-negative INTEGER in the fewest octets, with a leading zero
+    /// octet when the top bit would otherwise read as a sign.
+    pub fn integer(&mut self, value: &BigInt) {
+        let mut contents = value.to_bytes_be();
+        if contents[0] & 0x80 != 0 {
+            contents.insert(0, 0);
+        }
+        self.tlv(Tag::INTEGER, &contents);
+    }
+
+    /// A possibly negative INTEGER in minimal two's complement.
+    pub fn integer_i64(&mut self, value: i64) {
+        let bytes = value.to_be_bytes();
+        // Drop leading octets that only repeat the sign bit of the next one.
+        let mut start = 0;
+        while start < 7 {
+            let (b, next) = (bytes[start], bytes[start + 1]);
+            if (b == 0x00 && next & 0x80 == 0) || (b == 0xff && next & 0x80 != 0) {
+                start += 1;
+            } else {
+                break;
+            }
+        }
+        self.tlv(Tag::INTEGER, &bytes[start..]);
+    }
+
+    /// A BIT STRING of `bytes` whose last `unused_bits` bits are padding.
+    /// DER requires the padding bits to be zero.
+    pub fn bit_string(&mut self, bytes: &[u8], unused_bits: u8) {
+        assert!(unused_bits < 8 && (unused_bits == 0 || !bytes.is_empty()));
+        assert_eq!(
+            bytes.last().map_or(0, |b| b & ((1u8 << unused_bits) - 1)),
+            0,
+            "padding bits must be zero"
+        );
+        let mut contents = vec![unused_bits];
+        contents.extend_from_slice(bytes);
+        self.tlv(Tag::BIT_STRING, &contents);
+    }
+
+    pub fn octet_string(&mut self, bytes: &[u8]) {
+        self.tlv(Tag::OCTET_STRING, bytes);
+    }
+
+    pub fn null(&mut self) {
+        self.tlv(Tag::NULL, &[]);
+    }
+
+    pub fn oid(&mut self, oid: &ObjectIdentifier) {
+        self.tlv(Tag::OBJECT_IDENTIFIER, oid.as_bytes());
+    }
+
+    pub fn utf8_string(&mut self, s: &str) {
+        self.tlv(Tag::UTF8_STRING, s.as_bytes());
+    }
+
+    /// A PrintableString: letters, digits, space and ' ( ) + , - . / : = ?
+    pub fn printable_string(&mut self, s: &str) -> Result<(), Asn1Error> {
+        if !s.bytes().all(is_printable) {
+            return Err(Asn1Error::InvalidString);
+        }
+        self.tlv(Tag::PRINTABLE_STRING, s.as_bytes());
+        Ok(())
+    }
+
+    /// An IA5String: 7-bit ASCII.
+    pub fn ia5_string(&mut self, s: &str) -> Result<(), Asn1Error> {
+        if !s.is_ascii() {
+            return Err(Asn1Error::InvalidString);
+        }
+        self.tlv(Tag::IA5_STRING, s.as_bytes());
+        Ok(())
+    }
+
+    /// A UTCTime, which only covers 1950 to 2049.
+    pub fn utc_time(&mut self, time: &DateTime) -> Result<(), Asn1Error> {
+        self.tlv(Tag::UTC_TIME, time.to_utc_time()?.as_bytes());
+        Ok(())
+    }
+
+    pub fn generalized_time(&mut self, time: &DateTime) {
+        self.tlv(Tag::GENERALIZED_TIME, time.to_generalized_time().as_bytes());
+    }
+
+    /// A constructed value with an arbitrary tag.
+    pub fn constructed<R>(&mut self, tag: Tag, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        let mut inner = DerWriter::new();
+        let result = f(&mut inner);
+        self.tlv(tag, &inner.buf);
+        result
+    }
+
+    pub fn sequence<R>(&mut self, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        self.constructed(Tag::SEQUENCE, f)
+    }
+
+    /// A SET, with the components in tag order: by class, then by tag
+    /// number (X.690 section 10.3). This is not the encoding order of
+    /// `set_of`: a primitive [1] (0x81) follows a constructed [0] (0xa0).
+    pub fn set<R>(&mut self, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        let mut inner = DerWriter::new();
+        let result = f(&mut inner);
+        let mut elements = split_elements(&inner.buf);
+        elements.sort_by_key(|der| {
+            let (tag, _) = read_tag(der).unwrap();
+            (tag.class, tag.number)
+        });
+        self.tlv(Tag::SET, &elements.concat());
+        result
+    }
+
+    /// A SET OF, with the elements sorted by their encodings as DER
+    /// requires (X.690 section 11.6).
+    pub fn set_of<R>(&mut self, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        let mut inner = DerWriter::new();
+        let result = f(&mut inner);
+        let mut elements = split_elements(&inner.buf);
+        elements.sort();
+        self.tlv(Tag::SET, &elements.concat());
+        result
+    }
+
+    /// An EXPLICIT [number] wrapper around whatever `f` writes.
+    pub fn explicit<R>(&mut self, number: u32, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        self.constructed(Tag::context(number, true), f)
+    }
+
+    /// An IMPLICIT [number]: `f` writes a single value whose tag is replaced
+    /// by the context-specific one, keeping its constructed bit.
+    pub fn implicit<R>(&mut self, number: u32, f: impl FnOnce(&mut DerWriter) -> R) -> R {
+        let mut inner = DerWriter::new();
+        let result = f(&mut inner);
+        let der = &inner.buf;
+        let header = tag_len(der);
+        assert_eq!(tlv_len(der), der.len(), "IMPLICIT wraps exactly one value");
+        let tag = Tag::context(number, der[0] & 0x20 != 0);
+        tag.encode(&mut self.buf);
+        self.buf.extend_from_slice(&der[header..]);
+        result
+    }
+}
+
+pub(crate) fn is_printable(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b)
+}
+
+/// Length of the identifier octets at the start of well-formed DER.
+fn tag_len(der: &[u8]) -> usize {
+    if der[0] & 0x1f != 0x1f {
+        return 1;
+    }
+    1 + der[1..].iter().position(|b| b & 0x80 == 0).unwrap() + 1
+}
+
+/// The TLVs that make up well-formed DER contents.
+fn split_elements(mut der: &[u8]) -> Vec<&[u8]> {
+    let mut elements = Vec::new();
+    while !der.is_empty() {
+        let len = tlv_len(der);
+        elements.push(&der[..len]);
+        der = &der[len..];
+    }
+    elements
+}
+
+/// Total length of the TLV at the start of well-formed DER.
+fn tlv_len(der: &[u8]) -> usize {
+    let header = tag_len(der);
+    let first = der[header] as usize;
+    if first < 0x80 {
+        return header + 1 + first;
+    }
+    let n = first & 0x7f;
+    let len = der[header + 1..header + 1 + n]
+        .iter()
+        .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+    header + 1 + n + len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asn1::oid;
+    use crate::digest::Digest;
+    use crate::hex;
+    use crate::sha2::Sha256;
+
+    fn encode(f: impl FnOnce(&mut DerWriter)) -> String {
+        let mut w = DerWriter::new();
+        f(&mut w);
+        hex::encode(&w.finish())
+    }
+
+    // Expected encodings in this module are from `openssl asn1parse
+    // -genstr` unless noted.
+
+    #[test]
+    fn test_integers() {
+        for (value, expected) in [
+            ("0", "020100"),
+            ("7f", "02017f"),
+            ("80", "02020080"),
+            ("100", "02020100"),
+        ] {
+            assert_eq!(encode(|w| w.integer(&BigInt::from_hex(value))), expected);
+        }
+        for (value, expected) in [
+            (0i64, "020100"),
+            (127, "02017f"),
+            (128, "02020080"),
+            (-1, "0201ff"),
+            (-128, "020180"),
+            (-129, "0202ff7f"),
+            (i64::MIN, "02088000000000000000"),
+        ] {
+            assert_eq!(encode(|w| w.integer_i64(value)), expected, "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(encode(|w| w.boolean(true)), "0101ff");
+        assert_eq!(encode(|w| w.null()), "0500");
+        assert_eq!(encode(|w| w.bit_string(&[0x0a, 0x30], 4)), "0303040a30");
+        assert_eq!(encode(|w| w.bit_string(&[], 0)), "030100");
+        assert_eq!(encode(|w| w.octet_string(&[1, 2])), "04020102");
+        // UTF-8 bytes pass through unchanged.
+        assert_eq!(encode(|w| w.utf8_string("h\u{e9}llo")), "0c0668c3a96c6c6f");
+        assert_eq!(
+            encode(|w| w.printable_string("Test CA").unwrap()),
+            "130754657374204341"
+        );
+        assert_eq!(encode(|w| w.ia5_string("a@b.c").unwrap()), "16056140622e63");
+        let mut w = DerWriter::new();
+        assert_eq!(w.printable_string("a@b"), Err(Asn1Error::InvalidString));
+        assert_eq!(w.ia5_string("\u{e9}"), Err(Asn1Error::InvalidString));
+    }
+
+    #[test]
+    fn test_oids() {
+        assert_eq!(
+            encode(|w| w.oid(&oid::SHA256_WITH_RSA)),
+            "06092a864886f70d01010b"
+        );
+        let example = ObjectIdentifier::from_arcs(&[2, 999, 3]);
+        assert_eq!(encode(|w| w.oid(&example)), "0603883703");
+    }
+
+    #[test]
+    fn test_times() {
+        let t = DateTime::new(2049, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(
+            encode(|w| w.utc_time(&t).unwrap()),
+            "170d3439313233313233353935395a"
+        );
+        let t = DateTime::new(2050, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            encode(|w| w.generalized_time(&t)),
+            "180f32303530303130313030303030305a"
+        );
+        assert_eq!(DerWriter::new().utc_time(&t), Err(Asn1Error::InvalidTime));
+    }
+
+    #[test]
+    fn test_set_of_is_sorted() {
+        let der = encode(|w| {
+            w.set_of(|w| {
+                w.octet_string(&[0xaa, 0xbb]);
+                w.octet_string(&[0xff]);
+                w.octet_string(&[0x01]);
+            })
+        });
+        assert_eq!(der, "310a0401010401ff0402aabb");
+        // SET components come out in tag order.
+        let der = encode(|w| {
+            w.set(|w| {
+                w.explicit(1, |w| w.null());
+                w.integer_i64(5);
+                w.explicit(0, |w| w.null());
+            })
+        });
+        assert_eq!(der, "310b020105a0020500a1020500");
+    }
+
+    #[test]
+    fn test_set_tag_order() {
+        // A constructed [0] sorts before a primitive [1] in a SET but after
+        // it in a SET OF, whose order is bytewise.
+        let elements = |w: &mut DerWriter| {
+            w.implicit(1, |w| w.integer_i64(5));
+            w.explicit(0, |w| w.null());
+        };
+        assert_eq!(encode(|w| w.set(elements)), "3107a0020500810105");
+        assert_eq!(encode(|w| w.set_of(elements)), "3107810105a0020500");
+
+        // High tag numbers compare as numbers, after low ones of any form.
+        let der = encode(|w| {
+            w.set(|w| {
+                w.implicit(200, |w| w.null());
+                w.implicit(31, |w| w.null());
+                w.explicit(2, |w| w.null());
+                w.integer_i64(1);
+            })
+        });
+        assert_eq!(der, "310e020101a20205009f1f009f814800");
+    }
+
+    #[test]
+    fn test_context_tags() {
+        // [0] EXPLICIT INTEGER 2 is the X.509 v3 version field.
+        assert_eq!(
+            encode(|w| w.explicit(0, |w| w.integer_i64(2))),
+            "a003020102"
+        );
+        // [2] IMPLICIT OCTET STRING and [1] IMPLICIT SEQUENCE keep the
+        // constructed bit of what they replace.
+        assert_eq!(
+            encode(|w| w.implicit(2, |w| w.octet_string(b"ab"))),
+            "82026162"
+        );
+        assert_eq!(
+            encode(|w| w.implicit(1, |w| w.sequence(|w| w.null()))),
+            "a1020500"
+        );
+    }
+
+    #[test]
+    fn test_long_lengths() {
+        let der = encode(|w| w.octet_string(&[0u8; 300]));
+        assert_eq!(&der[..8], "0482012c");
+        assert_eq!(der.len(), 2 * 304);
+        // Nested lengths grow as their contents do.
+        let der = encode(|w| w.sequence(|w| w.octet_string(&[0u8; 200])));
+        assert_eq!(&der[..12], "3081cb0481c8");
+    }
+
+    #[test]
+    fn test_rsa_spki_matches_openssl() {
+        // SubjectPublicKeyInfo for the shared RSA test key, compared with
+        // `openssl pkey -pubout -outform DER`.
+        let key = crate::rsa::tests::test_key();
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.sequence(|w| {
+                w.oid(&oid::RSA_ENCRYPTION);
+                w.null();
+            });
+            let mut inner = DerWriter::new();
+            inner.sequence(|w| {
+                w.integer(key.public_key().n());
+                w.integer(key.public_key().e());
+            });
+            w.bit_string(&inner.finish(), 0);
+        });
+        let der = w.finish();
+        assert_eq!(
+            hex::encode(&der[..24]),
+            "30820122300d06092a864886f70d01010105000382010f00"
+        );
+        assert_eq!(
+            hex::encode(&Sha256::digest(&der)),
+            "e66013dbfbe24925d1cfd77fe6af2e8d65a80178c95bb92891ad1c99fbc1bbdf"
+        );
+    }
+}
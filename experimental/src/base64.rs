@@ -0,0 +1,225 @@
+//! RFC 4648 base64, in the standard and URL-safe alphabets, with and
+//! without padding.
+//!
+//! PEM bodies carry private keys, so characters are mapped arithmetically
+//! rather than through a lookup table indexed by secret data.
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Base64Error {
+    /// A character outside the alphabet, or a padding character where a
+    /// data character belongs.
+    InvalidCharacter,
+    /// A length that no encoding produces: one character past a multiple
+    /// of four, or a multiple of four missing with padding required.
+    InvalidLength,
+    /// Missing, misplaced or unexpected `=`, or non-zero bits left over in
+    /// the last character (RFC 4648 section 3.5).
+    InvalidPadding,
+}
+
+/// An alphabet and padding rule. Use one of the four constants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Base64 {
+    url_safe: bool,
+    padded: bool,
+}
+
+/// The section 4 alphabet with `=` padding, as in PEM and MIME.
+pub const STANDARD: Base64 = Base64 {
+    url_safe: false,
+    padded: true,
+};
+pub const STANDARD_NO_PAD: Base64 = Base64 {
+    url_safe: false,
+    padded: false,
+};
+/// The section 5 alphabet (`-` and `_` for `+` and `/`).
+pub const URL_SAFE: Base64 = Base64 {
+    url_safe: true,
+    padded: true,
+};
+/// As used by JOSE (RFC 7515 appendix C).
+pub const URL_SAFE_NO_PAD: Base64 = Base64 {
+    url_safe: true,
+    padded: false,
+};
+
+impl Base64 {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let mut block = [0u8; 3];
+            block[..chunk.len()].copy_from_slice(chunk);
+            let n = u32::from_be_bytes([0, block[0], block[1], block[2]]);
+            for i in 0..chunk.len() + 1 {
+                let sextet = (n >> (18 - 6 * i)) as u8 & 0x3f;
+                out.push(self.encode_sextet(sextet) as char);
+            }
+            if self.padded {
+                for _ in chunk.len()..3 {
+                    out.push('=');
+                }
+            }
+        }
+        out
+    }
+
+    /// Strict decoding: no whitespace, padding exactly as configured and
+    /// zero trailing bits, so each byte string has one accepted encoding.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, Base64Error> {
+        let s = s.as_bytes();
+        let data = if self.padded {
+            if !s.len().is_multiple_of(4) {
+                return Err(Base64Error::InvalidLength);
+            }
+            let pad = s.iter().rev().take(2).take_while(|&&c| c == b'=').count();
+            &s[..s.len() - pad]
+        } else {
+            s
+        };
+        if data.len() % 4 == 1 {
+            return Err(Base64Error::InvalidLength);
+        }
+        if data.contains(&b'=') {
+            return Err(Base64Error::InvalidPadding);
+        }
+
+        let mut out = Vec::with_capacity(data.len() / 4 * 3 + 2);
+        let mut invalid = 0u8;
+        let mut trailing_bits = false;
+        for chunk in data.chunks(4) {
+            let mut n = 0u32;
+            for (i, &c) in chunk.iter().enumerate() {
+                let sextet = self.decode_char(c);
+                invalid |= sextet >> 7;
+                n |= ((sextet & 0x3f) as u32) << (18 - 6 * i);
+            }
+            let bytes = n.to_be_bytes();
+            let len = chunk.len() - 1;
+            out.extend_from_slice(&bytes[1..1 + len]);
+            trailing_bits |= len < 3 && bytes[1 + len] != 0;
+        }
+        if invalid != 0 {
+            return Err(Base64Error::InvalidCharacter);
+        }
+        if trailing_bits {
+            return Err(Base64Error::InvalidPadding);
+        }
+        Ok(out)
+    }
+
+    /// Maps 0..64 to a character using comparisons and masks only.
+    fn encode_sextet(&self, v: u8) -> u8 {
+        let (c62, c63) = if self.url_safe {
+            (b'-', b'_')
+        } else {
+            (b'+', b'/')
+        };
+        let mut c = v.wrapping_add(b'A');
+        c = select(ge(v, 26), v.wrapping_add(b'a' - 26), c);
+        c = select(ge(v, 52), v.wrapping_sub(52).wrapping_add(b'0'), c);
+        c = select(eq(v, 62), c62, c);
+        select(eq(v, 63), c63, c)
+    }
+
+    /// The sextet for `c`, or 0x80 set if `c` is not in the alphabet.
+    fn decode_char(&self, c: u8) -> u8 {
+        let (c62, c63) = if self.url_safe {
+            (b'-', b'_')
+        } else {
+            (b'+', b'/')
+        };
+        let mut v = 0xff;
+        v = select(in_range(c, b'A', b'Z'), c.wrapping_sub(b'A'), v);
+        v = select(in_range(c, b'a', b'z'), c.wrapping_sub(b'a' - 26), v);
+        v = select(
+            in_range(c, b'0', b'9'),
+            c.wrapping_sub(b'0').wrapping_add(52),
+            v,
+        );
+        v = select(eq(c, c62), 62, v);
+        select(eq(c, c63), 63, v)
+    }
+}
+
+/// 0xff if a >= b, else 0.
+fn ge(a: u8, b: u8) -> u8 {
+    (((a as u16).wrapping_sub(b as u16) >> 8) as u8 & 1).wrapping_sub(1)
+}
+
+fn eq(a: u8, b: u8) -> u8 {
+    ge(a, b) & ge(b, a)
+}
+
+fn in_range(c: u8, lo: u8, hi: u8) -> u8 {
+    ge(c, lo) & ge(hi, c)
+}
+
+fn select(mask: u8, a: u8, b: u8) -> u8 {
+    (mask & a) | (!mask & b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc4648_vectors() {
+        // Section 10.
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(STANDARD.encode(plain.as_bytes()), encoded);
+            assert_eq!(STANDARD.decode(encoded).unwrap(), plain.as_bytes());
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(STANDARD_NO_PAD.encode(plain.as_bytes()), unpadded);
+            assert_eq!(STANDARD_NO_PAD.decode(unpadded).unwrap(), plain.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_alphabets() {
+        let all: Vec<u8> = (0..=255).collect();
+        let standard = STANDARD.encode(&all);
+        let url = URL_SAFE.encode(&all);
+        assert_eq!(url, standard.replace('+', "-").replace('/', "_"));
+        assert_eq!(&standard[..8], "AAECAwQF");
+        assert_eq!(&standard[standard.len() - 8..], "/P3+/w==");
+        assert_eq!(URL_SAFE_NO_PAD.encode(&all), url.trim_end_matches('='));
+        for codec in [STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD] {
+            for len in 0..all.len() {
+                let encoded = codec.encode(&all[len..]);
+                assert_eq!(codec.decode(&encoded).unwrap(), &all[len..]);
+            }
+        }
+        assert_eq!(STANDARD.decode("-_8="), Err(Base64Error::InvalidCharacter));
+        assert_eq!(URL_SAFE.decode("+/8="), Err(Base64Error::InvalidCharacter));
+    }
+
+    #[test]
+    fn test_strictness() {
+        for (codec, s, err) in [
+            (STANDARD, "Zg=", Base64Error::InvalidLength),
+            (STANDARD, "Zg", Base64Error::InvalidLength),
+            (STANDARD, "Z===", Base64Error::InvalidPadding),
+            (STANDARD, "Zh==", Base64Error::InvalidPadding),
+            (STANDARD, "Zm9=", Base64Error::InvalidPadding),
+            (STANDARD, "Zg==Zg==", Base64Error::InvalidPadding),
+            (STANDARD, "Z=g=", Base64Error::InvalidPadding),
+            (STANDARD, "Zm9v Yg==", Base64Error::InvalidLength),
+            (STANDARD, "Zm9\nYg==", Base64Error::InvalidCharacter),
+            (STANDARD, "Zm9vYg\0=", Base64Error::InvalidCharacter),
+            (STANDARD_NO_PAD, "Zg==", Base64Error::InvalidPadding),
+            (STANDARD_NO_PAD, "Zm9vY", Base64Error::InvalidLength),
+            (STANDARD_NO_PAD, "Zm9v\u{e9}", Base64Error::InvalidCharacter),
+        ] {
+            assert_eq!(codec.decode(s), Err(err), "{:?}", s);
+        }
+    }
+}
@@ -1,5 +1,4 @@
-mod bigint;
-use bigint::BigInt;
+use experimental::bigint::BigInt;
 
 fn main() {
     for bits in 61..68 {
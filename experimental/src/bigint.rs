@@ -1,6 +1,6 @@
 extern crate rand;
-use rand::Rng;
-use std::ops::{Add, BitAnd, BitOrAssign, Mul, Rem, Shl, ShrAssign, Sub};
+use rand::RngCore;
+use std::ops::{Add, BitAnd, BitOrAssign, Div, Mul, Rem, Shl, ShrAssign, Sub};
 use std::sync::{LazyLock, Mutex};
 
 #[derive(Clone)]
@@ -14,19 +14,25 @@ static R: LazyLock<Mutex<StdRng>> = LazyLock::new(|| Mutex::new(StdRng::seed_fro
 
 // Construction and instantiation and validation
 impl BigInt {
-    pub fn seed(seed: u64) -> () {
+    pub fn seed(seed: u64) {
         *R.lock().unwrap() = StdRng::seed_from_u64(seed);
     }
 
     pub fn random(bits: usize) -> Self {
-        let num_limbs = (bits + 63) / 64;
-        let mut limbs = Vec::with_capacity(num_limbs);
         // TODO: we can make it so that it uses rand::thread_rng() if not explicitly seeded.
+        Self::random_with(bits, &mut *R.lock().unwrap())
+    }
+
+    /// Like `random`, but draws from the given RNG (e.g. an `HmacDrbg`)
+    /// instead of the global seeded one.
+    pub fn random_with<G: RngCore + ?Sized>(bits: usize, rng: &mut G) -> Self {
+        let num_limbs = bits.div_ceil(64);
+        let mut limbs = Vec::with_capacity(num_limbs);
         for _ in 0..bits / 64 {
-            limbs.push(R.lock().unwrap().gen());
+            limbs.push(rng.next_u64());
         }
-        if bits % 64 != 0 {
-            limbs.push(R.lock().unwrap().gen::<u64>() & ((1 << (bits % 64)) - 1));
+        if !bits.is_multiple_of(64) {
+            limbs.push(rng.next_u64() & ((1 << (bits % 64)) - 1));
         }
         let mut result = Self { limbs };
         result.compact();
@@ -59,6 +65,39 @@ impl BigInt {
         Self { limbs: vec![n] }
     }
 
+    /// Builds a value from little-endian 64-bit limbs.
+    pub(crate) fn from_limbs(limbs: Vec<u64>) -> Self {
+        let mut result = Self { limbs };
+        if result.limbs.is_empty() {
+            result.limbs.push(0);
+        }
+        result.compact();
+        result
+    }
+
+    /// Little-endian 64-bit limbs (at least one).
+    pub(crate) fn limbs(&self) -> &[u64] {
+        &self.limbs
+    }
+
+    /// OS2IP: interprets big-endian bytes as an unsigned integer.
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        let mut limbs: Vec<u64> = bytes
+            .rchunks(8)
+            .map(|chunk| {
+                let mut buf = [0u8; 8];
+                buf[8 - chunk.len()..].copy_from_slice(chunk);
+                u64::from_be_bytes(buf)
+            })
+            .collect();
+        if limbs.is_empty() {
+            limbs.push(0);
+        }
+        let mut result = Self { limbs };
+        result.compact();
+        result
+    }
+
     fn compact(&mut self) {
         // TODO: do in in a way that i can "a.compact()" in fn random so it returns the compacted BigInt,
         // but also i can "a.compact()" as a function to just compact it. figure out how ownership works.
@@ -149,7 +188,7 @@ impl Mul for &BigInt {
     }
 }
 
-impl<'a, 'b> Sub<&'b BigInt> for &'a BigInt {
+impl Sub<&BigInt> for &BigInt {
     type Output = BigInt;
     fn sub(self, other: &BigInt) -> BigInt {
         assert!(
@@ -160,9 +199,10 @@ impl<'a, 'b> Sub<&'b BigInt> for &'a BigInt {
         let mut result = Vec::with_capacity(self.limbs.len());
         for (i, &limb) in self.limbs.iter().enumerate() {
             let other_limb = other.limbs.get(i).copied().unwrap_or(0);
-            let (diff, did_borrow) = limb.overflowing_sub(other_limb + borrow);
+            let (diff, borrow1) = limb.overflowing_sub(other_limb);
+            let (diff, borrow2) = diff.overflowing_sub(borrow);
             result.push(diff);
-            borrow = if did_borrow { 1 } else { 0 };
+            borrow = (borrow1 || borrow2) as u64;
         }
         // TODO: use .compact()
         // Normalize: remove any trailing zero limbs while ensuring at least one limb remains.
@@ -199,23 +239,14 @@ impl Shl<usize> for &BigInt {
 impl Rem for &BigInt {
     type Output = BigInt;
     fn rem(self, rhs: &BigInt) -> BigInt {
-        // Panic if trying to mod by 0.
-        if rhs.limbs.len() == 1 && rhs.limbs[0] == 0 {
-            panic!("Division by zero in modulus operation");
-        }
-        // If self is smaller than rhs, the remainder is self.
-        let mut dividend = self.clone();
-        if dividend.cmp_bigint(rhs) == std::cmp::Ordering::Less {
-            return dividend.clone();
-        }
-        let shift = dividend.bit_length() - rhs.bit_length();
-        for i in (0..=shift).rev() {
-            let candidate = rhs << i;
-            if dividend.cmp_bigint(&candidate) != std::cmp::Ordering::Less {
-                dividend = &dividend - &candidate;
-            }
-        }
-        dividend.clone()
+        self.divrem(rhs).1
+    }
+}
+
+impl Div for &BigInt {
+    type Output = BigInt;
+    fn div(self, rhs: &BigInt) -> BigInt {
+        self.divrem(rhs).0
     }
 }
 
@@ -263,27 +294,25 @@ impl PartialOrd<u64> for BigInt {
 
 impl PartialOrd for BigInt {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         // Compare based on the number of limbs first.
         if self.limbs.len() != other.limbs.len() {
-            return Some(self.limbs.len().cmp(&other.limbs.len()));
+            return self.limbs.len().cmp(&other.limbs.len());
         }
         // If both have the same length, compare from the most significant limb.
         for (a, b) in self.limbs.iter().rev().zip(other.limbs.iter().rev()) {
             if a < b {
-                return Some(std::cmp::Ordering::Less);
+                return std::cmp::Ordering::Less;
             } else if a > b {
-                return Some(std::cmp::Ordering::Greater);
+                return std::cmp::Ordering::Greater;
             }
         }
-        Some(std::cmp::Ordering::Equal)
-    }
-}
-
-impl Ord for BigInt {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        // Since our BigInt is always non-negative and normalized,
-        // we can safely unwrap the result of partial_cmp.
-        self.partial_cmp(other).unwrap()
+        std::cmp::Ordering::Equal
     }
 }
 
@@ -347,21 +376,6 @@ impl BigInt {
         (self.limbs.len() - 1) * 64 + bits
     }
 
-    fn cmp_bigint(&mut self, other: &BigInt) -> std::cmp::Ordering {
-        while self.limbs.len() > 1 && self.limbs[self.limbs.len() - 1] == 0 {
-            self.limbs.pop();
-        }
-        use std::cmp::Ordering;
-        if self.limbs.len() != other.limbs.len() {
-            return self.limbs.len().cmp(&other.limbs.len());
-        }
-        for (&a, &b) in self.limbs.iter().rev().zip(other.limbs.iter().rev()) {
-            if a != b {
-                return a.cmp(&b);
-            }
-        }
-        Ordering::Equal
-    }
     pub fn minus_one(&mut self) {
         // technically i--; but rust do not have decrement operator, thus a new function.
         let mut i = 0;
@@ -377,7 +391,7 @@ impl BigInt {
     }
 
     pub fn is_even(&self) -> bool {
-        self.limbs[0] % 2 == 0
+        self.limbs[0].is_multiple_of(2)
     }
 
     pub fn trailing_zeros(&self) -> u32 {
@@ -409,12 +423,219 @@ impl BigInt {
         result
     }
 
+    /// `self^exp mod modulus` by a Montgomery ladder over exactly
+    /// `exp_bits` bits, for secret exponents. Each step does one multiply,
+    /// one square and two masked swaps whatever the bit, so the sequence of
+    /// operations does not depend on `exp` (the limb arithmetic underneath
+    /// is still variable-time). `exp` must be below 2^exp_bits.
+    pub fn modpow_ct(&self, exp: &BigInt, modulus: &BigInt, exp_bits: usize) -> BigInt {
+        assert!(
+            exp.bit_length() <= exp_bits,
+            "exponent longer than exp_bits"
+        );
+        let len = modulus.limbs.len();
+        let mut r0 = BigInt::from_u64(1);
+        let mut r1 = self % modulus;
+        for i in (0..exp_bits).rev() {
+            let bit = exp.bit(i);
+            BigInt::conditional_swap(&mut r0, &mut r1, bit, len);
+            r1 = &(&r0 * &r1) % modulus;
+            r0 = &(&r0 * &r0) % modulus;
+            BigInt::conditional_swap(&mut r0, &mut r1, bit, len);
+        }
+        r0
+    }
+
+    /// Swaps `a` and `b` when `choice` is set, masking every one of `len`
+    /// limbs the same way either way. Both values must fit in `len` limbs.
+    pub(crate) fn conditional_swap(a: &mut BigInt, b: &mut BigInt, choice: bool, len: usize) {
+        let mask = 0u64.wrapping_sub(choice as u64);
+        a.limbs.resize(len, 0);
+        b.limbs.resize(len, 0);
+        for (x, y) in a.limbs.iter_mut().zip(b.limbs.iter_mut()) {
+            let t = (*x ^ *y) & mask;
+            *x ^= t;
+            *y ^= t;
+        }
+        a.compact();
+        b.compact();
+    }
+
     pub fn modpow_u32(&self, exp: u32, modulus: &BigInt) -> BigInt {
         self.modpow(&BigInt::from_u64(exp as u64), modulus)
     }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().all(|&l| l == 0)
+    }
+
+    /// Returns bit `i` (bit 0 is the least significant).
+    pub fn bit(&self, i: usize) -> bool {
+        self.limbs
+            .get(i / 64)
+            .is_some_and(|limb| (limb >> (i % 64)) & 1 == 1)
+    }
+
+    pub fn set_bit(&mut self, i: usize) {
+        if self.limbs.len() <= i / 64 {
+            self.limbs.resize(i / 64 + 1, 0);
+        }
+        self.limbs[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Quotient and remainder (Knuth, TAOCP vol. 2, 4.3.1 Algorithm D).
+    pub fn divrem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        if divisor.is_zero() {
+            panic!("Division by zero in modulus operation");
+        }
+        if self < divisor {
+            return (BigInt::from_u64(0), self.clone());
+        }
+
+        // Single-limb divisor: schoolbook short division.
+        if divisor.limbs.len() == 1 {
+            let d = divisor.limbs[0] as u128;
+            let mut quotient = vec![0u64; self.limbs.len()];
+            let mut rem: u128 = 0;
+            for i in (0..self.limbs.len()).rev() {
+                let cur = (rem << 64) | self.limbs[i] as u128;
+                quotient[i] = (cur / d) as u64;
+                rem = cur % d;
+            }
+            let mut q = BigInt { limbs: quotient };
+            q.compact();
+            return (q, BigInt::from_u64(rem as u64));
+        }
+
+        // D1: normalize so the divisor's top limb has its high bit set.
+        let shift = divisor.limbs.last().unwrap().leading_zeros() as usize;
+        let v = (divisor << shift).limbs;
+        let mut u = (self << shift).limbs;
+        u.resize(self.limbs.len() + 1, 0);
+        let n = v.len();
+        let m = u.len() - n - 1;
+        let mut quotient = vec![0u64; m + 1];
+        let base: u128 = 1 << 64;
+
+        for j in (0..=m).rev() {
+            // D3: estimate the quotient digit from the top two limbs.
+            let num = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+            let mut qhat = num / v[n - 1] as u128;
+            let mut rhat = num % v[n - 1] as u128;
+            while qhat >= base || qhat * v[n - 2] as u128 > ((rhat << 64) | u[j + n - 2] as u128) {
+                qhat -= 1;
+                rhat += v[n - 1] as u128;
+                if rhat >= base {
+                    break;
+                }
+            }
+
+            // D4: multiply and subtract.
+            let mut borrow: i128 = 0;
+            let mut carry: u128 = 0;
+            for i in 0..n {
+                let p = qhat * v[i] as u128 + carry;
+                carry = p >> 64;
+                let t = u[i + j] as i128 - borrow - (p as u64) as i128;
+                u[i + j] = t as u64;
+                borrow = (t < 0) as i128;
+            }
+            let t = u[j + n] as i128 - borrow - carry as i128;
+            u[j + n] = t as u64;
+
+            // D5/D6: the estimate was one too large; add the divisor back.
+            if t < 0 {
+                qhat -= 1;
+                let mut carry: u128 = 0;
+                for i in 0..n {
+                    let sum = u[i + j] as u128 + v[i] as u128 + carry;
+                    u[i + j] = sum as u64;
+                    carry = sum >> 64;
+                }
+                u[j + n] = u[j + n].wrapping_add(carry as u64);
+            }
+            quotient[j] = qhat as u64;
+        }
+
+        // D8: unnormalize the remainder.
+        u.truncate(n);
+        let mut rem = BigInt { limbs: u };
+        rem.compact();
+        rem >>= shift as u32;
+        let mut q = BigInt { limbs: quotient };
+        q.compact();
+        (q, rem)
+    }
+
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Inverse of `self` modulo `modulus`, or `None` if they are not coprime.
+    ///
+    /// Extended Euclid, keeping the Bezout coefficient reduced mod `modulus`
+    /// so everything stays non-negative.
+    pub fn modinv(&self, modulus: &BigInt) -> Option<BigInt> {
+        let mut r0 = modulus.clone();
+        let mut r1 = self % modulus;
+        let mut t0 = BigInt::from_u64(0);
+        let mut t1 = BigInt::from_u64(1);
+        while !r1.is_zero() {
+            let (q, r) = r0.divrem(&r1);
+            let qt = &(&q * &t1) % modulus;
+            let t = &(&t0 + modulus) - &qt;
+            let t = &t % modulus;
+            r0 = r1;
+            r1 = r;
+            t0 = t1;
+            t1 = t;
+        }
+        if r0 == 1 {
+            Some(t0)
+        } else {
+            None
+        }
+    }
 }
 // Representation
 impl BigInt {
+    /// Minimal big-endian bytes (a single zero byte for zero).
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let bytes: Vec<u8> = self
+            .limbs
+            .iter()
+            .rev()
+            .flat_map(|l| l.to_be_bytes())
+            .collect();
+        let first = bytes
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(bytes.len() - 1);
+        bytes[first..].to_vec()
+    }
+
+    /// I2OSP: big-endian bytes left-padded to exactly `len` bytes, or `None`
+    /// if the value does not fit.
+    pub fn to_bytes_be_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let bytes = self.to_bytes_be();
+        if self.is_zero() {
+            return Some(vec![0u8; len]);
+        }
+        if bytes.len() > len {
+            return None;
+        }
+        let mut out = vec![0u8; len - bytes.len()];
+        out.extend_from_slice(&bytes);
+        Some(out)
+    }
+
     pub fn binary(&self) -> String {
         self.limbs
             .iter()
@@ -529,9 +750,9 @@ mod tests_ops {
 
     #[test]
     fn test_bitor() {
-        let mut a = BigInt::from_binary(&"10101010");
+        let mut a = BigInt::from_binary("10101010");
         a |= 1;
-        let b = BigInt::from_binary(&"10101011");
+        let b = BigInt::from_binary("10101011");
         assert!(a == b);
 
         a = BigInt::from_binary(&"101".repeat(1000));
@@ -542,9 +763,9 @@ mod tests_ops {
 
     #[test]
     fn test_bitand_u64() {
-        let a = BigInt::from_binary(&"1111");
+        let a = BigInt::from_binary("1111");
         let a_u64 = a & 10u64; // 1010 in binary
-        let b = BigInt::from_binary(&"1010");
+        let b = BigInt::from_binary("1010");
         assert!(b == a_u64);
 
         let a = BigInt::from_hex("FFFFFFFFFFFFFFFF");
@@ -622,6 +843,7 @@ mod tests_ops {
         assert_eq!(c, BigInt::from_u64(11));
     }
     #[test]
+    #[allow(clippy::nonminimal_bool)]
     fn test_partial_ord_u64() {
         let a = BigInt::from_u64(42);
         let b = 42u64;
@@ -644,7 +866,7 @@ mod tests_ops {
         assert!(a <= b);
         assert!(!(a >= b));
 
-        let a = BigInt::from_hex(&"ABCDEF1234567890");
+        let a = BigInt::from_hex("ABCDEF1234567890");
         let b = 42u64;
         assert!(!(a < b));
         assert!(a > b);
@@ -801,4 +1023,111 @@ mod tests_arithmetic {
         a = BigInt::from_binary(&"0".repeat(256));
         assert_eq!(a.trailing_zeros(), 256);
     }
+
+    #[test]
+    fn test_divrem() {
+        let a = BigInt::from_hex("DEADBEEF0DEADBEEF1DEADBEEF2DEADBEEF3DEADBEEF4DEADBEEF5DEADBEEF6DEADBEEF7DEADBEEF8DEADBEEF9DEADBEEF0");
+        let b = BigInt::from_hex("123456789ABCDEF0FEDCBA9876543210FFFF");
+        let (q, r) = a.divrem(&b);
+        assert_eq!(
+            q,
+            BigInt::from_hex("C3B6B4D01B3B6BFA8D970B3CF5E81A8B560E0A7F761F518E6380A2F44A8B53EF")
+        );
+        assert_eq!(r, BigInt::from_hex("D278031839A88FBB705E27B480F48842DF"));
+
+        // q * b + r == a and r < b for random operands of assorted sizes.
+        for (bits_a, bits_b) in [(64, 64), (200, 65), (1000, 130), (2048, 1024), (4096, 2047)] {
+            let a = BigInt::random(bits_a);
+            let mut b = BigInt::random(bits_b);
+            b |= 1;
+            let (q, r) = a.divrem(&b);
+            assert!(r < b);
+            assert_eq!(&(&q * &b) + &r, a);
+        }
+
+        // Dividend smaller than divisor, and single-limb divisor.
+        let (q, r) = BigInt::from_u64(5).divrem(&b);
+        assert_eq!((q, r), (BigInt::from_u64(0), BigInt::from_u64(5)));
+        assert_eq!(&a / &BigInt::from_u64(16), {
+            let mut x = a.clone();
+            x >>= 4;
+            x
+        });
+    }
+
+    #[test]
+    fn test_modinv_and_gcd() {
+        let m =
+            BigInt::from_hex("FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF");
+        let b = BigInt::from_hex("123456789ABCDEF0FEDCBA9876543210FFFF");
+        let inv = b.modinv(&m).unwrap();
+        assert_eq!(
+            inv,
+            BigInt::from_hex("84E1511DB07DE8879BA6DF47D960BDAF8D6DFF3EB65BD58719A6E77B9569688D")
+        );
+        assert_eq!(&(&inv * &b) % &m, BigInt::from_u64(1));
+
+        assert_eq!(BigInt::from_u64(6).modinv(&BigInt::from_u64(9)), None);
+        assert_eq!(
+            BigInt::from_u64(84).gcd(&BigInt::from_u64(36)),
+            BigInt::from_u64(12)
+        );
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let a = BigInt::from_bytes_be(&[
+            0x00, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+        ]);
+        assert_eq!(a, BigInt::from_hex("10203040506070809"));
+        assert_eq!(a.to_bytes_be(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(
+            a.to_bytes_be_padded(12).unwrap(),
+            vec![0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(a.to_bytes_be_padded(8), None);
+
+        let zero = BigInt::from_bytes_be(&[]);
+        assert!(zero.is_zero());
+        assert_eq!(zero.to_bytes_be(), vec![0]);
+        assert_eq!(zero.to_bytes_be_padded(3).unwrap(), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn test_sub_borrow_chain() {
+        // A borrow into a u64::MAX limb must propagate rather than overflow.
+        let a = BigInt {
+            limbs: vec![0, 0, 1],
+        };
+        let b = BigInt {
+            limbs: vec![1, u64::MAX],
+        };
+        assert_eq!(&a - &b, BigInt::from_u64(u64::MAX));
+    }
+
+    #[test]
+    fn test_modpow_ct() {
+        let m = BigInt::from_hex("f1e2d3c4b5a6978812345678");
+        let base = BigInt::from_hex("123456789abcdef0fedcba98");
+        for exp in [
+            "0",
+            "1",
+            "2",
+            "ffff",
+            "8000000000000001",
+            "deadbeefcafebabe0123",
+        ] {
+            let e = BigInt::from_hex(exp);
+            assert_eq!(base.modpow_ct(&e, &m, 96), base.modpow(&e, &m));
+        }
+    }
+
+    #[test]
+    fn test_bits() {
+        let mut a = BigInt::from_u64(0);
+        a.set_bit(130);
+        a.set_bit(0);
+        assert!(a.bit(130) && a.bit(0) && !a.bit(1) && !a.bit(500));
+        assert_eq!(a.bit_length(), 131);
+    }
 }
@@ -0,0 +1,9 @@
+/// Compares two byte strings without an early exit on the first mismatch, so
+/// the running time depends only on the lengths, not on where they differ.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y));
+    diff == 0
+}
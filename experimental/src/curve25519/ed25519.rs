@@ -0,0 +1,248 @@
+use super::edwards::EdwardsPoint;
+use super::Curve25519Error;
+use crate::bigint::BigInt;
+use crate::digest::Digest;
+use crate::sha2::Sha512;
+use rand::RngCore;
+
+/// An Ed25519 public key: the compressed point A, kept with its decoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ed25519PublicKey {
+    bytes: [u8; 32],
+    point: EdwardsPoint,
+}
+
+/// An Ed25519 private key, stored as the 32-byte seed together with the
+/// values RFC 8032 section 5.1.5 derives from it.
+#[derive(Clone)]
+pub struct Ed25519PrivateKey {
+    seed: [u8; 32],
+    /// The clamped secret scalar s.
+    scalar: [u8; 32],
+    /// The second half of SHA-512(seed), used to derive nonces.
+    prefix: [u8; 32],
+    public: Ed25519PublicKey,
+}
+
+impl std::fmt::Debug for Ed25519PrivateKey {
+    // Never print the seed or scalar.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Ed25519PrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Ed25519PrivateKey {
+    /// Key generation from a 32-byte seed (RFC 8032 section 5.1.5).
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        let h = Sha512::digest(seed);
+        let mut scalar: [u8; 32] = h[..32].try_into().unwrap();
+        scalar[0] &= 0xf8;
+        scalar[31] &= 0x7f;
+        scalar[31] |= 0x40;
+        let prefix = h[32..].try_into().unwrap();
+        let point = EdwardsPoint::basepoint().mul(&scalar);
+        Self {
+            seed: *seed,
+            scalar,
+            prefix,
+            public: Ed25519PublicKey {
+                bytes: point.compress(),
+                point,
+            },
+        }
+    }
+
+    pub fn generate<G: RngCore + ?Sized>(rng: &mut G) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    pub fn seed(&self) -> &[u8; 32] {
+        &self.seed
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public
+    }
+
+    /// Pure Ed25519 signing (RFC 8032 section 5.1.6).
+    pub fn sign(&self, msg: &[u8]) -> [u8; 64] {
+        let mut h = Sha512::new();
+        h.update(&self.prefix);
+        h.update(msg);
+        let r = reduce_scalar(&h.finalize());
+        let big_r = EdwardsPoint::basepoint().mul(&r).compress();
+        let k = challenge(&big_r, &self.public.bytes, msg);
+        let s = mul_add_scalar(&k, &self.scalar, &r);
+
+        let mut sig = [0u8; 64];
+        sig[..32].copy_from_slice(&big_r);
+        sig[32..].copy_from_slice(&s);
+        sig
+    }
+}
+
+impl Ed25519PublicKey {
+    /// Decodes A, rejecting non-canonical encodings.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Result<Self, Curve25519Error> {
+        let point = EdwardsPoint::decompress(bytes).ok_or(Curve25519Error::InvalidEncoding)?;
+        Ok(Self {
+            bytes: *bytes,
+            point,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+
+    /// Strict verification (RFC 8032 section 5.1.7, cofactorless): S must
+    /// be below L, R must be a canonical point encoding, and
+    /// [S]B - [k]A must encode to exactly R.
+    pub fn verify(&self, msg: &[u8], sig: &[u8; 64]) -> Result<(), Curve25519Error> {
+        let big_r: [u8; 32] = sig[..32].try_into().unwrap();
+        let s: [u8; 32] = sig[32..].try_into().unwrap();
+        if !is_canonical_scalar(&s) || EdwardsPoint::decompress(&big_r).is_none() {
+            return Err(Curve25519Error::Verification);
+        }
+        let k = challenge(&big_r, &self.bytes, msg);
+        let check = EdwardsPoint::basepoint()
+            .mul_vartime(&s)
+            .add(&self.point.mul_vartime(&k).neg());
+        if check.compress() == big_r {
+            Ok(())
+        } else {
+            Err(Curve25519Error::Verification)
+        }
+    }
+}
+
+/// The group order L = 2^252 + 27742317777372353535851937790883648493.
+fn group_order() -> BigInt {
+    BigInt::from_hex("1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed")
+}
+
+fn le_to_bigint(bytes: &[u8]) -> BigInt {
+    let mut be = bytes.to_vec();
+    be.reverse();
+    BigInt::from_bytes_be(&be)
+}
+
+fn bigint_to_le(value: &BigInt) -> [u8; 32] {
+    let mut bytes = value.to_bytes_be_padded(32).expect("reduced mod L");
+    bytes.reverse();
+    bytes.try_into().unwrap()
+}
+
+/// A little-endian byte string reduced mod L.
+fn reduce_scalar(bytes: &[u8]) -> [u8; 32] {
+    bigint_to_le(&(&le_to_bigint(bytes) % &group_order()))
+}
+
+/// (a * b + c) mod L.
+fn mul_add_scalar(a: &[u8; 32], b: &[u8; 32], c: &[u8; 32]) -> [u8; 32] {
+    let sum = &(&le_to_bigint(a) * &le_to_bigint(b)) + &le_to_bigint(c);
+    bigint_to_le(&(&sum % &group_order()))
+}
+
+fn is_canonical_scalar(s: &[u8; 32]) -> bool {
+    le_to_bigint(s) < group_order()
+}
+
+/// k = SHA-512(R || A || M) mod L.
+fn challenge(big_r: &[u8; 32], a: &[u8; 32], msg: &[u8]) -> [u8; 32] {
+    let mut h = Sha512::new();
+    h.update(big_r);
+    h.update(a);
+    h.update(msg);
+    reduce_scalar(&h.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    fn array<const N: usize>(s: &str) -> [u8; N] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    // RFC 8032 section 7.1, TESTs 1, 2, 3 and SHA(abc).
+    #[rustfmt::skip]
+    const RFC8032_VECTORS: &[(&str, &str, &str, &str)] = &[
+        ("9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+         "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+         "",
+         "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b"),
+        ("4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+         "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+         "72",
+         "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00"),
+        ("c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+         "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+         "af82",
+         "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a"),
+        ("833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+         "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+         "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+         "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704"),
+    ];
+
+    #[test]
+    fn test_rfc8032_vectors() {
+        for (seed, public, msg, sig) in RFC8032_VECTORS {
+            let key = Ed25519PrivateKey::from_seed(&array(seed));
+            assert_eq!(hex::encode(key.public_key().as_bytes()), *public);
+            let msg = hex::decode(msg).unwrap();
+            let signature = key.sign(&msg);
+            assert_eq!(hex::encode(&signature), *sig);
+
+            let public = Ed25519PublicKey::from_bytes(&array(public)).unwrap();
+            public.verify(&msg, &signature).unwrap();
+            let mut tampered = msg.clone();
+            tampered.push(0);
+            assert_eq!(
+                public.verify(&tampered, &signature),
+                Err(Curve25519Error::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_malleable_s() {
+        let key = Ed25519PrivateKey::from_seed(&[7u8; 32]);
+        let sig = key.sign(b"msg");
+        // S + L verifies under a lax check but is not canonical.
+        let s = &le_to_bigint(&sig[32..]) + &group_order();
+        let mut malleated = sig;
+        malleated[32..].copy_from_slice(&bigint_to_le(&s));
+        assert_eq!(
+            key.public_key().verify(b"msg", &malleated),
+            Err(Curve25519Error::Verification)
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_encodings() {
+        let key = Ed25519PrivateKey::from_seed(&[9u8; 32]);
+        let sig = key.sign(b"msg");
+        // R with y = p is rejected rather than reduced.
+        let mut bad_r = sig;
+        bad_r[..32].copy_from_slice(&array::<32>(
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ));
+        assert_eq!(
+            key.public_key().verify(b"msg", &bad_r),
+            Err(Curve25519Error::Verification)
+        );
+        assert_eq!(
+            Ed25519PublicKey::from_bytes(&array(
+                "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f"
+            )),
+            Err(Curve25519Error::InvalidEncoding)
+        );
+    }
+}
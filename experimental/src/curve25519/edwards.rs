@@ -0,0 +1,270 @@
+use super::field::FieldElement;
+
+/// d = -121665/121666, the edwards25519 curve constant.
+const D: FieldElement = FieldElement::from_limbs([
+    929955233495203,
+    466365720129213,
+    1662059464998953,
+    2033849074728123,
+    1442794654840575,
+]);
+
+/// 2d, used by the addition formula.
+const D2: FieldElement = FieldElement::from_limbs([
+    1859910466990425,
+    932731440258426,
+    1072319116312658,
+    1815898335770999,
+    633789495995903,
+]);
+
+/// sqrt(-1) = 2^((p-1)/4).
+pub(crate) const SQRT_M1: FieldElement = FieldElement::from_limbs([
+    1718705420411056,
+    234908883556509,
+    2233514472574048,
+    2117202627021982,
+    765476049583133,
+]);
+
+/// Compressed base point B: y = 4/5 with x even.
+const BASEPOINT_COMPRESSED: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+/// A point on the twisted Edwards curve -x^2 + y^2 = 1 + d x^2 y^2 in
+/// extended coordinates (X : Y : Z : T), x = X/Z, y = Y/Z, xy = T/Z
+/// (RFC 8032 section 5.1.4).
+#[derive(Clone, Copy, Debug)]
+pub struct EdwardsPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+}
+
+impl EdwardsPoint {
+    pub fn identity() -> Self {
+        Self {
+            x: FieldElement::ZERO,
+            y: FieldElement::ONE,
+            z: FieldElement::ONE,
+            t: FieldElement::ZERO,
+        }
+    }
+
+    pub fn basepoint() -> Self {
+        Self::decompress(&BASEPOINT_COMPRESSED).expect("base point decodes")
+    }
+
+    /// Decodes a point (RFC 8032 section 5.1.3), rejecting non-canonical
+    /// y (>= p) and the non-canonical "negative zero" x.
+    pub fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        if !FieldElement::is_canonical(bytes) {
+            return None;
+        }
+        let sign = bytes[31] >> 7 == 1;
+        let y = FieldElement::from_bytes(bytes);
+
+        // x^2 = u/v with u = y^2 - 1, v = d y^2 + 1.
+        let yy = y.square();
+        let u = &yy - &FieldElement::ONE;
+        let v = &(&D * &yy) + &FieldElement::ONE;
+        // Candidate root x = u v^3 (u v^7)^((p-5)/8).
+        let v3 = &v.square() * &v;
+        let v7 = &v3.square() * &v;
+        let mut x = &(&u * &v3) * &(&u * &v7).pow_p58();
+        let vxx = &v * &x.square();
+        if vxx.ct_eq(&u) {
+            // x is a root.
+        } else if vxx.ct_eq(&-&u) {
+            x = &x * &SQRT_M1;
+        } else {
+            return None;
+        }
+        if x.is_zero() && sign {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = -&x;
+        }
+        Some(Self {
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: &x * &y,
+        })
+    }
+
+    /// Encodes the point as y with the sign of x in the top bit.
+    pub fn compress(&self) -> [u8; 32] {
+        let zinv = self.z.invert();
+        let x = &self.x * &zinv;
+        let y = &self.y * &zinv;
+        let mut out = y.to_bytes();
+        out[31] |= (x.is_negative() as u8) << 7;
+        out
+    }
+
+    /// Point addition (RFC 8032 section 5.1.4); complete, so it also
+    /// doubles and handles the identity.
+    pub fn add(&self, other: &Self) -> Self {
+        let a = &(&self.y - &self.x) * &(&other.y - &other.x);
+        let b = &(&self.y + &self.x) * &(&other.y + &other.x);
+        let c = &(&self.t * &D2) * &other.t;
+        let d = &(&self.z + &self.z) * &other.z;
+        let e = &b - &a;
+        let f = &d - &c;
+        let g = &d + &c;
+        let h = &b + &a;
+        Self {
+            x: &e * &f,
+            y: &g * &h,
+            z: &f * &g,
+            t: &e * &h,
+        }
+    }
+
+    /// Dedicated doubling (RFC 8032 section 5.1.4).
+    pub fn double(&self) -> Self {
+        let a = self.x.square();
+        let b = self.y.square();
+        let zz = self.z.square();
+        let c = &zz + &zz;
+        let h = &a + &b;
+        let e = &h - &(&self.x + &self.y).square();
+        let g = &a - &b;
+        let f = &c + &g;
+        Self {
+            x: &e * &f,
+            y: &g * &h,
+            z: &f * &g,
+            t: &e * &h,
+        }
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            x: -&self.x,
+            y: self.y,
+            z: self.z,
+            t: -&self.t,
+        }
+    }
+
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        FieldElement::conditional_swap(&mut a.x, &mut b.x, choice);
+        FieldElement::conditional_swap(&mut a.y, &mut b.y, choice);
+        FieldElement::conditional_swap(&mut a.z, &mut b.z, choice);
+        FieldElement::conditional_swap(&mut a.t, &mut b.t, choice);
+    }
+
+    /// `k * self` for a 256-bit little-endian scalar, by a Montgomery
+    /// ladder over all 256 bits with masked swaps: the same operations run
+    /// whatever the scalar.
+    pub fn mul(&self, k: &[u8; 32]) -> Self {
+        let mut r0 = Self::identity();
+        let mut r1 = *self;
+        for i in (0..256).rev() {
+            let bit = (k[i / 8] >> (i % 8)) & 1 == 1;
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+            r1 = r0.add(&r1);
+            r0 = r0.double();
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+        }
+        r0
+    }
+
+    /// `k * self` by double-and-add; only for public scalars.
+    pub fn mul_vartime(&self, k: &[u8; 32]) -> Self {
+        let mut result = Self::identity();
+        for i in (0..256).rev() {
+            result = result.double();
+            if (k[i / 8] >> (i % 8)) & 1 == 1 {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.x.is_zero() && self.y.ct_eq(&self.z)
+    }
+
+    /// Whether the point is in the small subgroup of order dividing 8.
+    pub fn is_small_order(&self) -> bool {
+        self.double().double().double().is_identity()
+    }
+}
+
+impl PartialEq for EdwardsPoint {
+    fn eq(&self, other: &Self) -> bool {
+        (&self.x * &other.z).ct_eq(&(&other.x * &self.z))
+            && (&self.y * &other.z).ct_eq(&(&other.y * &self.z))
+    }
+}
+
+impl Eq for EdwardsPoint {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    /// The group order L in little-endian bytes.
+    const L: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x10,
+    ];
+
+    fn scalar(n: u8) -> [u8; 32] {
+        let mut k = [0u8; 32];
+        k[0] = n;
+        k
+    }
+
+    #[test]
+    fn test_basepoint() {
+        let b = EdwardsPoint::basepoint();
+        assert_eq!(b.compress(), BASEPOINT_COMPRESSED);
+        assert!(b.mul(&L).is_identity());
+        assert!(b.mul_vartime(&L).is_identity());
+        assert!(!b.is_small_order());
+        assert_eq!(b.double(), b.add(&b));
+        assert_eq!(b.mul(&scalar(3)), b.double().add(&b));
+        assert!(b.add(&b.neg()).is_identity());
+        // 2B, computed independently with affine arithmetic in Python.
+        assert_eq!(
+            hex::encode(&b.double().compress()),
+            "c9a3f86aae465f0e56513864510f3997561fa2c9e85ea21dc2292309f3cd6022"
+        );
+    }
+
+    #[test]
+    fn test_decompress_rejects() {
+        // y = p is non-canonical even though it reduces to a valid y = 0.
+        let mut y_p = [0xff; 32];
+        y_p[0] = 0xed;
+        y_p[31] = 0x7f;
+        assert!(EdwardsPoint::decompress(&y_p).is_none());
+        // y = 1 gives x = 0; with the sign bit set it is "negative zero".
+        let mut neg_zero = scalar(1);
+        assert!(EdwardsPoint::decompress(&neg_zero).unwrap().is_identity());
+        neg_zero[31] = 0x80;
+        assert!(EdwardsPoint::decompress(&neg_zero).is_none());
+        // y = 2 is not on the curve.
+        assert!(EdwardsPoint::decompress(&scalar(2)).is_none());
+    }
+
+    #[test]
+    fn test_small_order() {
+        // (0, -1) has order 2.
+        let mut minus_one = [0xff; 32];
+        minus_one[0] = 0xec;
+        minus_one[31] = 0x7f;
+        let p = EdwardsPoint::decompress(&minus_one).unwrap();
+        assert!(p.is_small_order());
+        assert!(p.double().is_identity());
+    }
+}
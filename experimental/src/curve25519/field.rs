@@ -0,0 +1,286 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+const MASK: u64 = (1 << 51) - 1;
+
+/// An element of GF(2^255 - 19) in five 51-bit limbs, least significant
+/// first.
+///
+/// Unlike `ec::field::Fp` this is fixed-width and branch-free: every
+/// operation runs the same instructions whatever the values, which is what
+/// Ed25519 and X25519 need for secret scalars. Limbs are kept below about
+/// 2^52 between operations; only `to_bytes` fully reduces.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    pub const ZERO: Self = Self([0, 0, 0, 0, 0]);
+    pub const ONE: Self = Self([1, 0, 0, 0, 0]);
+
+    pub(crate) const fn from_limbs(limbs: [u64; 5]) -> Self {
+        Self(limbs)
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self([value & MASK, value >> 51, 0, 0, 0])
+    }
+
+    /// Decodes 32 little-endian bytes. The top bit is ignored, and values in
+    /// [p, 2^255) are accepted and reduced; callers that need canonical
+    /// input check with `is_canonical` first.
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        Self([
+            load(0) & MASK,
+            (load(6) >> 3) & MASK,
+            (load(12) >> 6) & MASK,
+            (load(19) >> 1) & MASK,
+            (load(24) >> 12) & MASK,
+        ])
+    }
+
+    /// Whether the 255-bit value in `bytes` (top bit ignored) is below p.
+    pub fn is_canonical(bytes: &[u8; 32]) -> bool {
+        let mut masked = *bytes;
+        masked[31] &= 0x7f;
+        Self::from_bytes(&masked).to_bytes() == masked
+    }
+
+    /// The canonical 32-byte little-endian encoding.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut l = self.carry().0;
+        // l < 2^255 + small now. Adding 19 overflows 2^255 iff l >= p, so
+        // the final carry out of the top limb is the quotient q.
+        let mut q = (l[0] + 19) >> 51;
+        for limb in &l[1..] {
+            q = (limb + q) >> 51;
+        }
+        l[0] += 19 * q;
+        for i in 0..4 {
+            l[i + 1] += l[i] >> 51;
+            l[i] &= MASK;
+        }
+        l[4] &= MASK;
+
+        let mut out = [0u8; 32];
+        let packed = [
+            l[0] | (l[1] << 51),
+            (l[1] >> 13) | (l[2] << 38),
+            (l[2] >> 26) | (l[3] << 25),
+            (l[3] >> 39) | (l[4] << 12),
+        ];
+        for (chunk, word) in out.chunks_mut(8).zip(packed) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        out
+    }
+
+    /// Propagates carries so every limb is below 2^51 + 2^18.
+    fn carry(self) -> Self {
+        let mut l = self.0;
+        for i in 0..4 {
+            l[i + 1] += l[i] >> 51;
+            l[i] &= MASK;
+        }
+        l[0] += 19 * (l[4] >> 51);
+        l[4] &= MASK;
+        Self(l)
+    }
+
+    pub fn square(&self) -> Self {
+        self * self
+    }
+
+    /// `self^exp` for a public exponent given as 32 little-endian bytes.
+    fn pow(&self, exp: &[u8; 32]) -> Self {
+        let mut result = Self::ONE;
+        for i in (0..256).rev() {
+            result = result.square();
+            if (exp[i / 8] >> (i % 8)) & 1 == 1 {
+                result = &result * self;
+            }
+        }
+        result
+    }
+
+    /// Inverse by Fermat, self^(p-2). Zero maps to zero.
+    pub fn invert(&self) -> Self {
+        let mut exp = [0xff; 32];
+        exp[0] = 0xeb;
+        exp[31] = 0x7f;
+        self.pow(&exp)
+    }
+
+    /// self^((p-5)/8), the core of the square-root computation in RFC 8032
+    /// section 5.1.3.
+    pub fn pow_p58(&self) -> Self {
+        let mut exp = [0xff; 32];
+        exp[0] = 0xfd;
+        exp[31] = 0x0f;
+        self.pow(&exp)
+    }
+
+    /// The "sign" of x in RFC 8032: the low bit of its canonical encoding.
+    pub fn is_negative(&self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.ct_eq(&Self::ZERO)
+    }
+
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        crate::ct::ct_eq(&self.to_bytes(), &other.to_bytes())
+    }
+
+    /// Swaps `a` and `b` if `choice` is set, without branching on it.
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        let mask = 0u64.wrapping_sub(choice as u64);
+        for i in 0..5 {
+            let t = (a.0[i] ^ b.0[i]) & mask;
+            a.0[i] ^= t;
+            b.0[i] ^= t;
+        }
+    }
+
+    /// `b` if `choice` is set, otherwise `a`, without branching on it.
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        let mask = 0u64.wrapping_sub(choice as u64);
+        let mut out = [0u64; 5];
+        for (i, limb) in out.iter_mut().enumerate() {
+            *limb = a.0[i] ^ ((a.0[i] ^ b.0[i]) & mask);
+        }
+        Self(out)
+    }
+}
+
+impl Add for &FieldElement {
+    type Output = FieldElement;
+    fn add(self, rhs: &FieldElement) -> FieldElement {
+        let mut l = self.0;
+        for (x, y) in l.iter_mut().zip(rhs.0) {
+            *x += y;
+        }
+        FieldElement(l).carry()
+    }
+}
+
+impl Sub for &FieldElement {
+    type Output = FieldElement;
+    fn sub(self, rhs: &FieldElement) -> FieldElement {
+        // Add 4p first so no limb goes negative.
+        const FOUR_P: [u64; 5] = [4 * ((1 << 51) - 19), 4 * MASK, 4 * MASK, 4 * MASK, 4 * MASK];
+        let mut l = [0u64; 5];
+        for i in 0..5 {
+            l[i] = self.0[i] + FOUR_P[i] - rhs.0[i];
+        }
+        FieldElement(l).carry()
+    }
+}
+
+impl Mul for &FieldElement {
+    type Output = FieldElement;
+    fn mul(self, rhs: &FieldElement) -> FieldElement {
+        let m = |x: u64, y: u64| x as u128 * y as u128;
+        let [a0, a1, a2, a3, a4] = self.0;
+        let [b0, b1, b2, b3, b4] = rhs.0;
+        // 2^255 = 19 mod p, so limb products that land at 2^(51k) for
+        // k >= 5 fold back down multiplied by 19.
+        let (b1_19, b2_19, b3_19, b4_19) = (b1 * 19, b2 * 19, b3 * 19, b4 * 19);
+
+        let c0 = m(a0, b0) + m(a4, b1_19) + m(a3, b2_19) + m(a2, b3_19) + m(a1, b4_19);
+        let mut c1 = m(a1, b0) + m(a0, b1) + m(a4, b2_19) + m(a3, b3_19) + m(a2, b4_19);
+        let mut c2 = m(a2, b0) + m(a1, b1) + m(a0, b2) + m(a4, b3_19) + m(a3, b4_19);
+        let mut c3 = m(a3, b0) + m(a2, b1) + m(a1, b2) + m(a0, b3) + m(a4, b4_19);
+        let mut c4 = m(a4, b0) + m(a3, b1) + m(a2, b2) + m(a1, b3) + m(a0, b4);
+
+        let mut l = [0u64; 5];
+        c1 += c0 >> 51;
+        l[0] = (c0 as u64) & MASK;
+        c2 += c1 >> 51;
+        l[1] = (c1 as u64) & MASK;
+        c3 += c2 >> 51;
+        l[2] = (c2 as u64) & MASK;
+        c4 += c3 >> 51;
+        l[3] = (c3 as u64) & MASK;
+        let carry = (c4 >> 51) as u64;
+        l[4] = (c4 as u64) & MASK;
+        l[0] += carry * 19;
+        l[1] += l[0] >> 51;
+        l[0] &= MASK;
+        FieldElement(l)
+    }
+}
+
+impl Neg for &FieldElement {
+    type Output = FieldElement;
+    fn neg(self) -> FieldElement {
+        &FieldElement::ZERO - self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint::BigInt;
+
+    fn to_bigint(fe: &FieldElement) -> BigInt {
+        let mut bytes = fe.to_bytes();
+        bytes.reverse();
+        BigInt::from_bytes_be(&bytes)
+    }
+
+    fn p() -> BigInt {
+        BigInt::from_hex("7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed")
+    }
+
+    fn random_element() -> (FieldElement, BigInt) {
+        let v = &BigInt::random(255) % &p();
+        let mut bytes = v.to_bytes_be_padded(32).unwrap();
+        bytes.reverse();
+        (FieldElement::from_bytes(&bytes.try_into().unwrap()), v)
+    }
+
+    #[test]
+    fn test_against_bigint() {
+        let p = p();
+        for _ in 0..100 {
+            let (a, x) = random_element();
+            let (b, y) = random_element();
+            assert_eq!(to_bigint(&(&a + &b)), &(&x + &y) % &p);
+            assert_eq!(to_bigint(&(&a - &b)), &(&(&x + &p) - &y) % &p);
+            assert_eq!(to_bigint(&(&a * &b)), &(&x * &y) % &p);
+            assert_eq!(to_bigint(&(&a * &a.invert())), BigInt::from_u64(1));
+        }
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        // p itself decodes to zero but is not canonical; p - 1 is.
+        let mut p_bytes = [0xff; 32];
+        p_bytes[0] = 0xed;
+        p_bytes[31] = 0x7f;
+        assert!(!FieldElement::is_canonical(&p_bytes));
+        assert!(FieldElement::from_bytes(&p_bytes).is_zero());
+        p_bytes[0] = 0xec;
+        assert!(FieldElement::is_canonical(&p_bytes));
+        assert_eq!(FieldElement::from_bytes(&p_bytes).to_bytes(), p_bytes);
+        // 2^255 - 1 = p + 18.
+        assert_eq!(
+            FieldElement::from_bytes(&[0xff; 32]).to_bytes(),
+            FieldElement::from_u64(18).to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_negation_and_select() {
+        let (a, _) = random_element();
+        assert!((&a + &(-&a)).is_zero());
+        assert!((-&FieldElement::ZERO).is_zero());
+        let one = FieldElement::ONE;
+        assert!(FieldElement::conditional_select(&a, &one, true).ct_eq(&one));
+        assert!(FieldElement::conditional_select(&a, &one, false).ct_eq(&a));
+        let (mut x, mut y) = (a, one);
+        FieldElement::conditional_swap(&mut x, &mut y, true);
+        assert!(x.ct_eq(&one) && y.ct_eq(&a));
+    }
+}
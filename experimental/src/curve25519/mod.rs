@@ -0,0 +1,15 @@
+pub mod ed25519;
+pub mod edwards;
+pub mod field;
+pub mod x25519;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Curve25519Error {
+    /// A key, point or signature encoding is malformed or non-canonical.
+    InvalidEncoding,
+    /// Signature verification failed.
+    Verification,
+    /// X25519 produced the all-zero shared secret, meaning the peer's public
+    /// key was a small-order point (RFC 7748 section 6.1).
+    LowOrderPoint,
+}
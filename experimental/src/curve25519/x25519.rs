@@ -0,0 +1,225 @@
+use super::field::FieldElement;
+use super::Curve25519Error;
+use rand::RngCore;
+
+/// The u-coordinate of the Curve25519 base point.
+const BASEPOINT_U: [u8; 32] = {
+    let mut u = [0u8; 32];
+    u[0] = 9;
+    u
+};
+
+/// (A - 2) / 4 for Curve25519's A = 486662.
+const A24: u64 = 121665;
+
+/// The X25519 function (RFC 7748 section 5): clamps `scalar`, decodes `u`
+/// (masking the top bit and accepting non-canonical values, as the RFC
+/// requires) and returns the u-coordinate of scalar * u.
+///
+/// The Montgomery ladder runs all 255 steps with masked swaps, and the
+/// field arithmetic is fixed-width, so timing does not depend on the
+/// scalar.
+pub fn x25519(scalar: &[u8; 32], u: &[u8; 32]) -> [u8; 32] {
+    let mut k = *scalar;
+    k[0] &= 0xf8;
+    k[31] &= 0x7f;
+    k[31] |= 0x40;
+
+    let x1 = FieldElement::from_bytes(u);
+    let mut x2 = FieldElement::ONE;
+    let mut z2 = FieldElement::ZERO;
+    let mut x3 = x1;
+    let mut z3 = FieldElement::ONE;
+    let a24 = FieldElement::from_u64(A24);
+
+    let mut swap = false;
+    for t in (0..255).rev() {
+        let bit = (k[t / 8] >> (t % 8)) & 1 == 1;
+        swap ^= bit;
+        FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+        FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+        swap = bit;
+
+        let a = &x2 + &z2;
+        let aa = a.square();
+        let b = &x2 - &z2;
+        let bb = b.square();
+        let e = &aa - &bb;
+        let c = &x3 + &z3;
+        let d = &x3 - &z3;
+        let da = &d * &a;
+        let cb = &c * &b;
+        x3 = (&da + &cb).square();
+        z3 = &x1 * &(&da - &cb).square();
+        x2 = &aa * &bb;
+        z2 = &e * &(&aa + &(&a24 * &e));
+    }
+    FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+    FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+
+    (&x2 * &z2.invert()).to_bytes()
+}
+
+/// An X25519 private key: 32 random bytes, clamped on use.
+#[derive(Clone)]
+pub struct X25519PrivateKey {
+    scalar: [u8; 32],
+}
+
+impl std::fmt::Debug for X25519PrivateKey {
+    // Never print the scalar.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("X25519PrivateKey")
+            .field("public", &self.public_key())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An X25519 public key: a u-coordinate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct X25519PublicKey {
+    bytes: [u8; 32],
+}
+
+impl X25519PrivateKey {
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self { scalar: *bytes }
+    }
+
+    pub fn generate<G: RngCore + ?Sized>(rng: &mut G) -> Self {
+        let mut scalar = [0u8; 32];
+        rng.fill_bytes(&mut scalar);
+        Self { scalar }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.scalar
+    }
+
+    pub fn public_key(&self) -> X25519PublicKey {
+        X25519PublicKey {
+            bytes: x25519(&self.scalar, &BASEPOINT_U),
+        }
+    }
+
+    /// The shared secret with `peer`, refusing the all-zero output that a
+    /// small-order peer key forces (RFC 7748 section 6.1).
+    pub fn diffie_hellman(&self, peer: &X25519PublicKey) -> Result<[u8; 32], Curve25519Error> {
+        let shared = x25519(&self.scalar, &peer.bytes);
+        if crate::ct::ct_eq(&shared, &[0u8; 32]) {
+            return Err(Curve25519Error::LowOrderPoint);
+        }
+        Ok(shared)
+    }
+}
+
+impl X25519PublicKey {
+    pub fn from_bytes(bytes: &[u8; 32]) -> Self {
+        Self { bytes: *bytes }
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    fn array(s: &str) -> [u8; 32] {
+        hex::decode(s).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc7748_vectors() {
+        // Section 5.2. The second u has its top bit set, which must be
+        // masked off.
+        assert_eq!(
+            hex::encode(&x25519(
+                &array("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &array("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            )),
+            "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552"
+        );
+        assert_eq!(
+            hex::encode(&x25519(
+                &array("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+                &array("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"),
+            )),
+            "95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957"
+        );
+    }
+
+    fn iterate(n: usize) -> [u8; 32] {
+        let mut k = BASEPOINT_U;
+        let mut u = BASEPOINT_U;
+        for _ in 0..n {
+            let next = x25519(&k, &u);
+            u = k;
+            k = next;
+        }
+        k
+    }
+
+    #[test]
+    fn test_rfc7748_iterated() {
+        assert_eq!(
+            hex::encode(&iterate(1)),
+            "422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079"
+        );
+        assert_eq!(
+            hex::encode(&iterate(1000)),
+            "684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51"
+        );
+    }
+
+    #[test]
+    #[ignore = "one million ladder runs; run with --ignored --release"]
+    fn test_rfc7748_iterated_million() {
+        assert_eq!(
+            hex::encode(&iterate(1_000_000)),
+            "7c3911e0ab2586fd864497297e575e6f3bc601c0883c30df5f4dd2d24f665424"
+        );
+    }
+
+    #[test]
+    fn test_rfc7748_diffie_hellman() {
+        // Section 6.1.
+        let alice = X25519PrivateKey::from_bytes(&array(
+            "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+        ));
+        let bob = X25519PrivateKey::from_bytes(&array(
+            "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb",
+        ));
+        assert_eq!(
+            hex::encode(alice.public_key().as_bytes()),
+            "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+        );
+        assert_eq!(
+            hex::encode(bob.public_key().as_bytes()),
+            "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"
+        );
+        let shared = alice.diffie_hellman(&bob.public_key()).unwrap();
+        assert_eq!(shared, bob.diffie_hellman(&alice.public_key()).unwrap());
+        assert_eq!(
+            hex::encode(&shared),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+    }
+
+    #[test]
+    fn test_low_order_peer() {
+        let key = X25519PrivateKey::from_bytes(&[0x42; 32]);
+        // u = 0 has order 2 and u = 1 has order 4.
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        for u in [[0u8; 32], one] {
+            assert_eq!(
+                key.diffie_hellman(&X25519PublicKey::from_bytes(&u)),
+                Err(Curve25519Error::LowOrderPoint)
+            );
+        }
+    }
+}
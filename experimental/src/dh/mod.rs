@@ -0,0 +1,453 @@
+use crate::bigint::BigInt;
+use crate::prime::{miller_rabin, small_primes};
+use rand::RngCore;
+use std::sync::{Arc, LazyLock};
+
+mod ffdhe;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DhError {
+    /// The group parameters are inconsistent.
+    InvalidGroup,
+    /// A public value is out of range or outside the prime-order subgroup.
+    InvalidPublicValue,
+    /// A private exponent is out of range.
+    InvalidPrivateValue,
+    /// The two keys belong to different groups.
+    GroupMismatch,
+}
+
+/// How much checking a peer's public value gets before use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PublicCheck {
+    /// Only 1 < y < p-1, which rules out the values that force a trivial
+    /// shared secret (SP 800-56A rev 3 section 5.6.2.3.1).
+    Range,
+    /// The range check plus y^q = 1 mod p, so y lies in the subgroup of
+    /// order q (section 5.6.2.3.2). Needs a group with a known q.
+    Subgroup,
+}
+
+/// A finite-field Diffie-Hellman group: a prime p, a generator g and,
+/// when known, the prime order q of the subgroup g generates.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DhGroup {
+    name: Option<&'static str>,
+    p: BigInt,
+    g: BigInt,
+    q: Option<BigInt>,
+}
+
+fn ffdhe(name: &'static str, hex: &str) -> Arc<DhGroup> {
+    // Each modulus is a safe prime and g = 2 generates the subgroup of
+    // order q = (p-1)/2 (RFC 7919 appendix A).
+    let p = BigInt::from_hex(hex);
+    let mut q = p.clone();
+    q >>= 1;
+    Arc::new(DhGroup {
+        name: Some(name),
+        p,
+        g: BigInt::from_u64(2),
+        q: Some(q),
+    })
+}
+
+static FFDHE2048: LazyLock<Arc<DhGroup>> = LazyLock::new(|| ffdhe("ffdhe2048", ffdhe::FFDHE2048));
+static FFDHE3072: LazyLock<Arc<DhGroup>> = LazyLock::new(|| ffdhe("ffdhe3072", ffdhe::FFDHE3072));
+static FFDHE4096: LazyLock<Arc<DhGroup>> = LazyLock::new(|| ffdhe("ffdhe4096", ffdhe::FFDHE4096));
+static FFDHE6144: LazyLock<Arc<DhGroup>> = LazyLock::new(|| ffdhe("ffdhe6144", ffdhe::FFDHE6144));
+static FFDHE8192: LazyLock<Arc<DhGroup>> = LazyLock::new(|| ffdhe("ffdhe8192", ffdhe::FFDHE8192));
+
+impl DhGroup {
+    /// Defines a group from its parameters. Checks that p is odd,
+    /// 1 < g < p-1 and, if q is given, that q divides p-1 and g^q = 1 mod
+    /// p. Primality of p and q is not tested.
+    pub fn new(p: BigInt, g: BigInt, q: Option<BigInt>) -> Result<Arc<Self>, DhError> {
+        if p < 5u64 || p.is_even() {
+            return Err(DhError::InvalidGroup);
+        }
+        let mut p1 = p.clone();
+        p1.minus_one();
+        if g <= 1u64 || g >= p1 {
+            return Err(DhError::InvalidGroup);
+        }
+        if let Some(q) = &q {
+            if q <= &1u64 || !(&p1 % q).is_zero() || g.modpow(q, &p) != 1u64 {
+                return Err(DhError::InvalidGroup);
+            }
+        }
+        Ok(Arc::new(Self {
+            name: None,
+            p,
+            g,
+            q,
+        }))
+    }
+
+    /// Generates a group over a fresh `bits`-bit safe prime p = 2q + 1
+    /// with g = 4, which generates the subgroup of order q.
+    pub fn generate<G: RngCore + ?Sized>(bits: usize, rng: &mut G) -> Arc<Self> {
+        assert!(bits >= 16, "unsupported DH group size");
+        let sieve = small_primes(2048);
+        loop {
+            let mut q = BigInt::random_with(bits - 1, rng);
+            q.set_bit(bits - 2);
+            q |= 1;
+            // Sieve both q and p = 2q + 1: r divides p iff q = (r-1)/2 mod r.
+            if sieve.iter().any(|&r| {
+                let m = &q % r;
+                (m == 0 || (r > 2 && m == (r - 1) / 2)) && q != r
+            }) {
+                continue;
+            }
+            if !miller_rabin(&q, 40) {
+                continue;
+            }
+            let p = &(&q << 1) + &BigInt::from_u64(1);
+            if miller_rabin(&p, 40) {
+                return Self::new(p, BigInt::from_u64(4), Some(q))
+                    .expect("freshly generated safe-prime group is consistent");
+            }
+        }
+    }
+
+    /// The 2048-bit group from RFC 7919 appendix A.1.
+    pub fn ffdhe2048() -> Arc<Self> {
+        FFDHE2048.clone()
+    }
+
+    /// The 3072-bit group from RFC 7919 appendix A.2.
+    pub fn ffdhe3072() -> Arc<Self> {
+        FFDHE3072.clone()
+    }
+
+    /// The 4096-bit group from RFC 7919 appendix A.3.
+    pub fn ffdhe4096() -> Arc<Self> {
+        FFDHE4096.clone()
+    }
+
+    /// The 6144-bit group from RFC 7919 appendix A.4.
+    pub fn ffdhe6144() -> Arc<Self> {
+        FFDHE6144.clone()
+    }
+
+    /// The 8192-bit group from RFC 7919 appendix A.5.
+    pub fn ffdhe8192() -> Arc<Self> {
+        FFDHE8192.clone()
+    }
+
+    /// The RFC 7919 name, for the named groups.
+    pub fn name(&self) -> Option<&'static str> {
+        self.name
+    }
+
+    pub fn p(&self) -> &BigInt {
+        &self.p
+    }
+
+    pub fn g(&self) -> &BigInt {
+        &self.g
+    }
+
+    pub fn q(&self) -> Option<&BigInt> {
+        self.q.as_ref()
+    }
+
+    /// Length of p in bytes, which is also the length of a shared secret.
+    pub fn byte_len(&self) -> usize {
+        self.p.bit_length().div_ceil(8)
+    }
+
+    /// Validates a peer's public value y.
+    pub fn check_public(&self, y: &BigInt, check: PublicCheck) -> Result<(), DhError> {
+        let mut p1 = self.p.clone();
+        p1.minus_one();
+        if y <= &1u64 || y >= &p1 {
+            return Err(DhError::InvalidPublicValue);
+        }
+        if check == PublicCheck::Subgroup {
+            let q = self.q.as_ref().ok_or(DhError::InvalidGroup)?;
+            if y.modpow(q, &self.p) != 1u64 {
+                return Err(DhError::InvalidPublicValue);
+            }
+        }
+        Ok(())
+    }
+
+    /// Exponents are drawn from [1, q-1], or [1, p-2] when q is unknown.
+    fn exponent_bound(&self) -> BigInt {
+        match &self.q {
+            Some(q) => q.clone(),
+            None => {
+                let mut p1 = self.p.clone();
+                p1.minus_one();
+                p1
+            }
+        }
+    }
+}
+
+/// A Diffie-Hellman public value y = g^x mod p.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DhPublicKey {
+    group: Arc<DhGroup>,
+    y: BigInt,
+}
+
+/// A Diffie-Hellman private exponent x with its public value.
+#[derive(Clone)]
+pub struct DhPrivateKey {
+    x: BigInt,
+    public: DhPublicKey,
+}
+
+impl std::fmt::Debug for DhPrivateKey {
+    // Never print the exponent.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DhPrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DhPublicKey {
+    /// Wraps a peer's public value after checking it with `check`.
+    pub fn new(group: &Arc<DhGroup>, y: BigInt, check: PublicCheck) -> Result<Self, DhError> {
+        group.check_public(&y, check)?;
+        Ok(Self {
+            group: group.clone(),
+            y,
+        })
+    }
+
+    /// Decodes a big-endian public value, as carried in a TLS key share.
+    pub fn from_bytes(
+        group: &Arc<DhGroup>,
+        bytes: &[u8],
+        check: PublicCheck,
+    ) -> Result<Self, DhError> {
+        Self::new(group, BigInt::from_bytes_be(bytes), check)
+    }
+
+    pub fn group(&self) -> &Arc<DhGroup> {
+        &self.group
+    }
+
+    pub fn y(&self) -> &BigInt {
+        &self.y
+    }
+
+    /// y as big-endian bytes left-padded to the length of p (RFC 7919
+    /// section 5.1).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.y
+            .to_bytes_be_padded(self.group.byte_len())
+            .expect("y is below p")
+    }
+}
+
+impl DhPrivateKey {
+    /// Wraps an exponent x, which must lie in [1, q-1] (or [1, p-2] for a
+    /// group without a known q).
+    pub fn from_exponent(group: &Arc<DhGroup>, x: BigInt) -> Result<Self, DhError> {
+        let bound = group.exponent_bound();
+        if x.is_zero() || x >= bound {
+            return Err(DhError::InvalidPrivateValue);
+        }
+        let y = group.g.modpow_ct(&x, &group.p, bound.bit_length());
+        Ok(Self {
+            x,
+            public: DhPublicKey {
+                group: group.clone(),
+                y,
+            },
+        })
+    }
+
+    /// Draws x uniformly from the exponent range by rejection sampling.
+    pub fn generate<G: RngCore + ?Sized>(group: &Arc<DhGroup>, rng: &mut G) -> Self {
+        let bits = group.exponent_bound().bit_length();
+        loop {
+            let x = BigInt::random_with(bits, rng);
+            if let Ok(key) = Self::from_exponent(group, x) {
+                return key;
+            }
+        }
+    }
+
+    pub fn x(&self) -> &BigInt {
+        &self.x
+    }
+
+    pub fn public_key(&self) -> &DhPublicKey {
+        &self.public
+    }
+
+    pub fn group(&self) -> &Arc<DhGroup> {
+        &self.public.group
+    }
+
+    /// The shared secret Z = y^x mod p as big-endian bytes left-padded to
+    /// the length of p (RFC 7919 section 5.1; SP 800-56A rev 3 section
+    /// 5.7.1.1). The exponentiation is a fixed-length ladder over the bit
+    /// length of the exponent bound, so its step count does not depend on
+    /// x. Z = 1 is refused.
+    pub fn diffie_hellman(&self, peer: &DhPublicKey) -> Result<Vec<u8>, DhError> {
+        let group = self.group();
+        if peer.group != *group {
+            return Err(DhError::GroupMismatch);
+        }
+        let bits = group.exponent_bound().bit_length();
+        let z = peer.y.modpow_ct(&self.x, &group.p, bits);
+        if z == 1u64 {
+            return Err(DhError::InvalidPublicValue);
+        }
+        Ok(z.to_bytes_be_padded(group.byte_len())
+            .expect("z is below p"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+    use crate::sha2::Sha256;
+
+    /// floor(2^bits * e), from the series e = sum 1/k! with 64 guard bits.
+    fn e_fixed(bits: usize) -> BigInt {
+        let one = &BigInt::from_u64(1) << (bits + 64);
+        let mut term = one.clone();
+        let mut sum = BigInt::from_u64(0);
+        let mut k = 1u64;
+        while !term.is_zero() {
+            sum = &sum + &term;
+            term = &term / &BigInt::from_u64(k);
+            k += 1;
+        }
+        sum >>= 64;
+        sum
+    }
+
+    #[test]
+    fn test_ffdhe_moduli_match_rfc7919_formula() {
+        // p = 2^b - 2^(b-64) + {[2^(b-130) e] + X} * 2^64 - 1.
+        let groups = [
+            (DhGroup::ffdhe2048(), 2048, 560316u64),
+            (DhGroup::ffdhe3072(), 3072, 2625351),
+            (DhGroup::ffdhe4096(), 4096, 5736041),
+            (DhGroup::ffdhe6144(), 6144, 15705020),
+            (DhGroup::ffdhe8192(), 8192, 10965728),
+        ];
+        let one = BigInt::from_u64(1);
+        for (group, b, x) in groups {
+            let middle = &(&e_fixed(b - 130) + &BigInt::from_u64(x)) << 64;
+            let p = &(&(&(&one << b) - &(&one << (b - 64))) + &middle) - &one;
+            assert_eq!(group.p(), &p, "{}", group.name().unwrap());
+            assert_eq!(group.byte_len(), b / 8);
+            assert_eq!(group.g(), &BigInt::from_u64(2));
+            assert_eq!(&(group.q().unwrap() << 1) + &one, p);
+        }
+    }
+
+    #[test]
+    fn test_ffdhe2048_is_safe_prime() {
+        let group = DhGroup::ffdhe2048();
+        assert!(miller_rabin(group.p(), 2));
+        assert!(miller_rabin(group.q().unwrap(), 2));
+        // g = 2 lies in the order-q subgroup since p = 7 mod 8.
+        assert_eq!(group.g().modpow(group.q().unwrap(), group.p()), 1u64);
+    }
+
+    #[test]
+    fn test_public_value_checks() {
+        let group = DhGroup::ffdhe2048();
+        let p = group.p().clone();
+        let mut p1 = p.clone();
+        p1.minus_one();
+        for y in [BigInt::from_u64(0), BigInt::from_u64(1), p1.clone(), p] {
+            for check in [PublicCheck::Range, PublicCheck::Subgroup] {
+                assert_eq!(
+                    DhPublicKey::new(&group, y.clone(), check),
+                    Err(DhError::InvalidPublicValue)
+                );
+            }
+        }
+        // 4 is a square, so it lies in the subgroup. p = 7 mod 8 makes 2 a
+        // square and -1 a non-square, so -2 = p-2 lies outside it.
+        assert!(DhPublicKey::new(&group, BigInt::from_u64(4), PublicCheck::Subgroup).is_ok());
+        let minus_two = &p1 - &BigInt::from_u64(1);
+        assert_eq!(
+            DhPublicKey::new(&group, minus_two.clone(), PublicCheck::Subgroup),
+            Err(DhError::InvalidPublicValue)
+        );
+        assert!(DhPublicKey::new(&group, minus_two, PublicCheck::Range).is_ok());
+    }
+
+    #[test]
+    fn test_agreement_ffdhe2048() {
+        let mut rng = HmacDrbg::<Sha256>::new(b"dh agreement entropy", b"nonce", b"");
+        let group = DhGroup::ffdhe2048();
+        let alice = DhPrivateKey::generate(&group, &mut rng);
+        let bob = DhPrivateKey::generate(&group, &mut rng);
+        let bob_public =
+            DhPublicKey::from_bytes(&group, &bob.public_key().to_bytes(), PublicCheck::Subgroup)
+                .unwrap();
+        let shared = alice.diffie_hellman(&bob_public).unwrap();
+        assert_eq!(shared.len(), 256);
+        assert_eq!(shared, bob.diffie_hellman(alice.public_key()).unwrap());
+        let expected = bob_public.y().modpow(alice.x(), group.p());
+        assert_eq!(shared, expected.to_bytes_be_padded(256).unwrap());
+    }
+
+    #[test]
+    fn test_generated_group() {
+        let mut rng = HmacDrbg::<Sha256>::new(b"dh group entropy", b"nonce", b"");
+        let group = DhGroup::generate(128, &mut rng);
+        let q = group.q().unwrap();
+        assert_eq!(group.p().bit_length(), 128);
+        assert!(miller_rabin(group.p(), 20) && miller_rabin(q, 20));
+        assert_eq!(&(q << 1) + &BigInt::from_u64(1), *group.p());
+
+        let alice = DhPrivateKey::generate(&group, &mut rng);
+        let bob = DhPrivateKey::generate(&group, &mut rng);
+        assert_eq!(
+            alice.diffie_hellman(bob.public_key()),
+            bob.diffie_hellman(alice.public_key())
+        );
+        // Keys from different groups do not mix.
+        let other = DhPrivateKey::generate(&DhGroup::ffdhe2048(), &mut rng);
+        assert_eq!(
+            alice.diffie_hellman(other.public_key()),
+            Err(DhError::GroupMismatch)
+        );
+    }
+
+    #[test]
+    fn test_custom_group_validation() {
+        // p = 23 = 2 * 11 + 1; 2 has order 11, 5 is a generator of order 22.
+        let p = BigInt::from_u64(23);
+        let q = Some(BigInt::from_u64(11));
+        assert!(DhGroup::new(p.clone(), BigInt::from_u64(2), q.clone()).is_ok());
+        assert_eq!(
+            DhGroup::new(p.clone(), BigInt::from_u64(5), q.clone()),
+            Err(DhError::InvalidGroup)
+        );
+        assert_eq!(
+            DhGroup::new(p.clone(), BigInt::from_u64(22), None),
+            Err(DhError::InvalidGroup)
+        );
+        assert_eq!(
+            DhGroup::new(BigInt::from_u64(24), BigInt::from_u64(2), None),
+            Err(DhError::InvalidGroup)
+        );
+        let group = DhGroup::new(p, BigInt::from_u64(5), None).unwrap();
+        assert_eq!(
+            group.check_public(&BigInt::from_u64(3), PublicCheck::Subgroup),
+            Err(DhError::InvalidGroup)
+        );
+        assert_eq!(
+            DhPrivateKey::from_exponent(&group, BigInt::from_u64(22)).unwrap_err(),
+            DhError::InvalidPrivateValue
+        );
+    }
+}
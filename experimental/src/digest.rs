@@ -0,0 +1,128 @@
+use crate::sha1::Sha1;
+use crate::sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::sha3::{Sha3_256, Sha3_384, Sha3_512};
+
+/// Streaming hash function interface shared by every digest in the crate.
+///
+/// `update` may be called any number of times; `finalize` consumes the state
+/// and returns the `OUTPUT_LEN`-byte digest.
+pub trait Digest: Clone {
+    /// Digest size in bytes.
+    const OUTPUT_LEN: usize;
+    /// Internal block size in bytes (needed by HMAC).
+    const BLOCK_LEN: usize;
+
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Vec<u8>;
+
+    /// One-shot convenience: hash `data` and return the digest.
+    fn digest(data: &[u8]) -> Vec<u8> {
+        let mut h = Self::new();
+        h.update(data);
+        h.finalize()
+    }
+}
+
+/// Merkle-Damgard block buffering used by SHA-1 and SHA-2.
+///
+/// Collects input into `B`-byte blocks and hands each full block to `compress`.
+#[derive(Clone)]
+pub(crate) struct BlockBuffer<const B: usize> {
+    buf: [u8; B],
+    len: usize,
+    /// Total number of bytes absorbed so far.
+    pub total: u128,
+}
+
+impl<const B: usize> BlockBuffer<B> {
+    pub fn new() -> Self {
+        Self {
+            buf: [0u8; B],
+            len: 0,
+            total: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; B])) {
+        self.total += data.len() as u128;
+        if self.len > 0 {
+            let take = (B - self.len).min(data.len());
+            self.buf[self.len..self.len + take].copy_from_slice(&data[..take]);
+            self.len += take;
+            data = &data[take..];
+            if self.len < B {
+                return;
+            }
+            compress(&self.buf);
+            self.len = 0;
+        }
+        let mut chunks = data.chunks_exact(B);
+        for block in &mut chunks {
+            compress(block.try_into().unwrap());
+        }
+        let rest = chunks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.len = rest.len();
+    }
+
+    /// Appends the 0x80 terminator, zero padding and the big-endian bit length
+    /// (`len_bytes` wide: 8 for 64-byte blocks, 16 for 128-byte blocks).
+    pub fn pad(&mut self, len_bytes: usize, mut compress: impl FnMut(&[u8; B])) {
+        let bit_len = self.total.wrapping_mul(8);
+        self.buf[self.len] = 0x80;
+        self.len += 1;
+        if self.len > B - len_bytes {
+            self.buf[self.len..].fill(0);
+            compress(&self.buf);
+            self.len = 0;
+        }
+        self.buf[self.len..B - len_bytes].fill(0);
+        self.buf[B - len_bytes..].copy_from_slice(&bit_len.to_be_bytes()[16 - len_bytes..]);
+        compress(&self.buf);
+        self.len = 0;
+    }
+}
+
+/// Runtime selection of a hash, for APIs (signatures, DigestInfo, MGF1)
+/// where the algorithm is a parameter rather than a type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// Legacy only; see `Sha1`.
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+}
+
+impl HashAlgorithm {
+    pub fn output_len(self) -> usize {
+        match self {
+            HashAlgorithm::Sha1 => Sha1::OUTPUT_LEN,
+            HashAlgorithm::Sha224 => Sha224::OUTPUT_LEN,
+            HashAlgorithm::Sha256 => Sha256::OUTPUT_LEN,
+            HashAlgorithm::Sha384 => Sha384::OUTPUT_LEN,
+            HashAlgorithm::Sha512 => Sha512::OUTPUT_LEN,
+            HashAlgorithm::Sha3_256 => Sha3_256::OUTPUT_LEN,
+            HashAlgorithm::Sha3_384 => Sha3_384::OUTPUT_LEN,
+            HashAlgorithm::Sha3_512 => Sha3_512::OUTPUT_LEN,
+        }
+    }
+
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha1 => Sha1::digest(data),
+            HashAlgorithm::Sha224 => Sha224::digest(data),
+            HashAlgorithm::Sha256 => Sha256::digest(data),
+            HashAlgorithm::Sha384 => Sha384::digest(data),
+            HashAlgorithm::Sha512 => Sha512::digest(data),
+            HashAlgorithm::Sha3_256 => Sha3_256::digest(data),
+            HashAlgorithm::Sha3_384 => Sha3_384::digest(data),
+            HashAlgorithm::Sha3_512 => Sha3_512::digest(data),
+        }
+    }
+}
@@ -0,0 +1,731 @@
+use crate::bigint::BigInt;
+use crate::digest::{Digest, HashAlgorithm};
+use crate::drbg::HmacDrbg;
+use crate::ec::ecdsa::bits2int;
+use crate::prime::{is_probable_prime, small_primes};
+use crate::sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::sha3::{Sha3_256, Sha3_384, Sha3_512};
+use rand::RngCore;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DsaError {
+    /// The domain parameters are malformed, of an unsupported size, or do
+    /// not re-derive from their validation seed.
+    InvalidParameters,
+    /// A key is out of range or inconsistent with the parameters.
+    InvalidKey,
+    /// The hash is too short for the parameters, or may be used for
+    /// verification but not for new signatures.
+    UnsupportedHash,
+    /// Signature verification failed.
+    Verification,
+}
+
+/// The (L, N) bit lengths of p and q that FIPS 186-4 section 4.2 allows.
+const SIZES: [(usize, usize); 4] = [(1024, 160), (2048, 224), (2048, 256), (3072, 256)];
+
+/// Miller-Rabin rounds for (p, q) from FIPS 186-4 table C.1.
+fn rounds(l: usize) -> (u64, u64) {
+    match l {
+        1024 => (40, 40),
+        2048 => (56, 64),
+        _ => (64, 64),
+    }
+}
+
+/// What a third party needs to re-derive p and q (FIPS 186-4 A.1.1.2)
+/// and g (A.2.3) and so check the parameters were not chosen with a
+/// hidden structure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationParams {
+    hash: HashAlgorithm,
+    seed: Vec<u8>,
+    counter: u32,
+    index: u8,
+}
+
+/// DSA domain parameters (p, q, g), with the validation seed when they
+/// were generated verifiably.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DsaParams {
+    p: BigInt,
+    q: BigInt,
+    g: BigInt,
+    validation: Option<ValidationParams>,
+}
+
+/// A DSA public key y = g^x mod p.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaPublicKey {
+    params: Arc<DsaParams>,
+    y: BigInt,
+}
+
+/// A DSA private key x in [1, q-1] with its public key.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DsaPrivateKey {
+    x: BigInt,
+    public: DsaPublicKey,
+}
+
+impl std::fmt::Debug for DsaPrivateKey {
+    // Never print the private key.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DsaPrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A DSA signature (r, s).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DsaSignature {
+    r: BigInt,
+    s: BigInt,
+}
+
+impl ValidationParams {
+    /// `hash` is the function used for generation, `seed` the
+    /// domain_parameter_seed, `counter` the value at which p was found and
+    /// `index` the g generation index.
+    pub fn new(hash: HashAlgorithm, seed: Vec<u8>, counter: u32, index: u8) -> Self {
+        Self {
+            hash,
+            seed,
+            counter,
+            index,
+        }
+    }
+
+    pub fn hash(&self) -> HashAlgorithm {
+        self.hash
+    }
+
+    pub fn seed(&self) -> &[u8] {
+        &self.seed
+    }
+
+    pub fn counter(&self) -> u32 {
+        self.counter
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+}
+
+impl DsaParams {
+    /// Wraps parameters after the cheap checks: p odd, q dividing p-1,
+    /// 1 < g < p and g^q = 1 mod p (FIPS 186-4 A.2.2). Primality and the
+    /// validation seed are only checked by `validate`.
+    pub fn new(
+        p: BigInt,
+        q: BigInt,
+        g: BigInt,
+        validation: Option<ValidationParams>,
+    ) -> Result<Arc<Self>, DsaError> {
+        let err = DsaError::InvalidParameters;
+        if p < 5u64 || p.is_even() || q < 2u64 {
+            return Err(err);
+        }
+        let mut p1 = p.clone();
+        p1.minus_one();
+        if !(&p1 % &q).is_zero() || g <= 1u64 || g >= p || g.modpow(&q, &p) != 1u64 {
+            return Err(err);
+        }
+        Ok(Arc::new(Self {
+            p,
+            q,
+            g,
+            validation,
+        }))
+    }
+
+    /// Generates parameters of an approved (L, N) size: p and q by the
+    /// hash-based procedure of FIPS 186-4 A.1.1.2 from an N-bit seed drawn
+    /// from `rng`, and g by the verifiable procedure of A.2.3 with index 1.
+    /// The hash output must be at least N bits.
+    pub fn generate<G: RngCore + ?Sized>(
+        l: usize,
+        n: usize,
+        hash: HashAlgorithm,
+        rng: &mut G,
+    ) -> Result<Arc<Self>, DsaError> {
+        if !SIZES.contains(&(l, n)) {
+            return Err(DsaError::InvalidParameters);
+        }
+        if 8 * hash.output_len() < n {
+            return Err(DsaError::UnsupportedHash);
+        }
+        let (p_rounds, q_rounds) = rounds(l);
+        let sieve = small_primes(2048);
+        let mut seed = vec![0u8; n / 8];
+        loop {
+            rng.fill_bytes(&mut seed);
+            let q = derive_q(hash, &seed, n);
+            if !is_probable_prime(&q, &sieve, q_rounds) {
+                continue;
+            }
+            let Some((p, counter)) =
+                derive_p(hash, &seed, &q, l, 4 * l as u32 - 1, &sieve, p_rounds)
+            else {
+                continue;
+            };
+            let index = 1;
+            let g = derive_g(hash, &seed, index, &p, &q).ok_or(DsaError::InvalidParameters)?;
+            let validation = ValidationParams::new(hash, seed, counter, index);
+            return Self::new(p, q, g, Some(validation));
+        }
+    }
+
+    pub fn p(&self) -> &BigInt {
+        &self.p
+    }
+
+    pub fn q(&self) -> &BigInt {
+        &self.q
+    }
+
+    pub fn g(&self) -> &BigInt {
+        &self.g
+    }
+
+    /// The seed, counter and index from generation, if known.
+    pub fn validation(&self) -> Option<&ValidationParams> {
+        self.validation.as_ref()
+    }
+
+    /// Full validation. With a validation seed this re-derives p and q
+    /// (FIPS 186-4 A.1.1.3) and g (A.2.4) and compares them; without one it
+    /// can only test p and q for primality and check the order of g.
+    pub fn validate(&self) -> Result<(), DsaError> {
+        let err = DsaError::InvalidParameters;
+        let (l, n) = (self.p.bit_length(), self.q.bit_length());
+        if !SIZES.contains(&(l, n)) {
+            return Err(err);
+        }
+        let (p_rounds, q_rounds) = rounds(l);
+        let sieve = small_primes(2048);
+        let Some(v) = &self.validation else {
+            if is_probable_prime(&self.q, &sieve, q_rounds)
+                && is_probable_prime(&self.p, &sieve, p_rounds)
+            {
+                return Ok(());
+            }
+            return Err(err);
+        };
+        if 8 * v.seed.len() < n || 8 * v.hash.output_len() < n || v.counter > 4 * l as u32 - 1 {
+            return Err(err);
+        }
+        let q = derive_q(v.hash, &v.seed, n);
+        if q != self.q || !is_probable_prime(&q, &sieve, q_rounds) {
+            return Err(err);
+        }
+        match derive_p(v.hash, &v.seed, &q, l, v.counter, &sieve, p_rounds) {
+            Some((p, counter)) if p == self.p && counter == v.counter => {}
+            _ => return Err(err),
+        }
+        if derive_g(v.hash, &v.seed, v.index, &self.p, &self.q).as_ref() != Some(&self.g) {
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+/// A.1.1.2 steps 6-7: q = 2^(N-1) + U + 1 - (U mod 2) with
+/// U = Hash(seed) mod 2^(N-1).
+fn derive_q(hash: HashAlgorithm, seed: &[u8], n: usize) -> BigInt {
+    let one = BigInt::from_u64(1);
+    let top = &one << (n - 1);
+    let u = &BigInt::from_bytes_be(&hash.digest(seed)) % &top;
+    let mut q = &top + &u;
+    q |= 1;
+    q
+}
+
+/// A.1.1.2 steps 9-10: walks the candidates for p derived from the seed
+/// and returns the first probable prime with its counter, giving up after
+/// `max_counter`.
+fn derive_p(
+    hash: HashAlgorithm,
+    seed: &[u8],
+    q: &BigInt,
+    l: usize,
+    max_counter: u32,
+    sieve: &[u64],
+    rounds: u64,
+) -> Option<(BigInt, u32)> {
+    let outlen = 8 * hash.output_len();
+    let seedlen = 8 * seed.len();
+    let n = l.div_ceil(outlen) - 1;
+    let b = l - 1 - n * outlen;
+    let one = BigInt::from_u64(1);
+    let seed_modulus = &one << seedlen;
+    let top = &one << (l - 1);
+    let two_q = q << 1;
+    let seed_value = BigInt::from_bytes_be(seed);
+
+    let mut offset = 1u64;
+    for counter in 0..=max_counter {
+        // W = V_0 + V_1 2^outlen + ... + (V_n mod 2^b) 2^(n outlen).
+        let mut w = BigInt::from_u64(0);
+        for j in (0..=n).rev() {
+            let input = &(&seed_value + &BigInt::from_u64(offset + j as u64)) % &seed_modulus;
+            let input = input.to_bytes_be_padded(seed.len()).expect("reduced");
+            let mut v = BigInt::from_bytes_be(&hash.digest(&input));
+            if j == n {
+                v = &v % &(&one << b);
+            }
+            w = &(&w << outlen) + &v;
+        }
+        let x = &w + &top;
+        let c = &x % &two_q;
+        let p = &(&x + &one) - &c;
+        if p.bit_length() == l && is_probable_prime(&p, sieve, rounds) {
+            return Some((p, counter));
+        }
+        offset += n as u64 + 1;
+    }
+    None
+}
+
+/// A.2.3: g = Hash(seed || "ggen" || index || count)^((p-1)/q) mod p for
+/// the first count giving g >= 2.
+fn derive_g(hash: HashAlgorithm, seed: &[u8], index: u8, p: &BigInt, q: &BigInt) -> Option<BigInt> {
+    let mut p1 = p.clone();
+    p1.minus_one();
+    let e = &p1 / q;
+    for count in 1..=u16::MAX {
+        let mut u = seed.to_vec();
+        u.extend_from_slice(b"ggen");
+        u.push(index);
+        u.extend_from_slice(&count.to_be_bytes());
+        let w = BigInt::from_bytes_be(&hash.digest(&u));
+        let g = w.modpow(&e, p);
+        if g >= 2u64 {
+            return Some(g);
+        }
+    }
+    None
+}
+
+impl DsaPublicKey {
+    /// Wraps y after checking 1 < y < p and y^q = 1 mod p (SP 800-89
+    /// section 5.3.2).
+    pub fn new(params: &Arc<DsaParams>, y: BigInt) -> Result<Self, DsaError> {
+        if y <= 1u64 || y >= params.p || y.modpow(&params.q, &params.p) != 1u64 {
+            return Err(DsaError::InvalidKey);
+        }
+        Ok(Self {
+            params: params.clone(),
+            y,
+        })
+    }
+
+    pub fn params(&self) -> &Arc<DsaParams> {
+        &self.params
+    }
+
+    pub fn y(&self) -> &BigInt {
+        &self.y
+    }
+
+    /// DSA verification (FIPS 186-4 section 4.7) of `msg` hashed with
+    /// `hash`. SHA-1 is accepted here for legacy signatures.
+    pub fn verify(
+        &self,
+        hash: HashAlgorithm,
+        msg: &[u8],
+        sig: &DsaSignature,
+    ) -> Result<(), DsaError> {
+        self.verify_digest(&hash.digest(msg), sig)
+    }
+
+    /// Verifies a signature over an already computed message digest.
+    pub fn verify_digest(&self, digest: &[u8], sig: &DsaSignature) -> Result<(), DsaError> {
+        let DsaParams { p, q, g, .. } = &*self.params;
+        if sig.r.is_zero() || sig.s.is_zero() || &sig.r >= q || &sig.s >= q {
+            return Err(DsaError::Verification);
+        }
+        let z = bits2int(digest, q.bit_length());
+        let w = sig.s.modinv(q).ok_or(DsaError::Verification)?;
+        let u1 = &(&z * &w) % q;
+        let u2 = &(&sig.r * &w) % q;
+        let v = &(&(&g.modpow(&u1, p) * &self.y.modpow(&u2, p)) % p) % q;
+        if v == sig.r {
+            Ok(())
+        } else {
+            Err(DsaError::Verification)
+        }
+    }
+}
+
+impl DsaPrivateKey {
+    pub fn new(params: &Arc<DsaParams>, x: BigInt) -> Result<Self, DsaError> {
+        if x.is_zero() || x >= params.q {
+            return Err(DsaError::InvalidKey);
+        }
+        let y = params.g.modpow_ct(&x, &params.p, params.q.bit_length());
+        Ok(Self {
+            x,
+            public: DsaPublicKey {
+                params: params.clone(),
+                y,
+            },
+        })
+    }
+
+    /// Draws x uniformly from [1, q-1] by rejection sampling (FIPS 186-4
+    /// B.1.2).
+    pub fn generate<G: RngCore + ?Sized>(params: &Arc<DsaParams>, rng: &mut G) -> Self {
+        let bits = params.q.bit_length();
+        loop {
+            let x = BigInt::random_with(bits, rng);
+            if let Ok(key) = Self::new(params, x) {
+                return key;
+            }
+        }
+    }
+
+    pub fn x(&self) -> &BigInt {
+        &self.x
+    }
+
+    pub fn public_key(&self) -> &DsaPublicKey {
+        &self.public
+    }
+
+    pub fn params(&self) -> &Arc<DsaParams> {
+        &self.public.params
+    }
+
+    /// Deterministic DSA (RFC 6979) over `msg` hashed with `hash`. SHA-1
+    /// is refused for new signatures. Fails with `InvalidParameters` if a
+    /// nonce has no inverse mod q, which only happens when q, unchecked by
+    /// `DsaParams::new`, is not prime.
+    pub fn sign(&self, hash: HashAlgorithm, msg: &[u8]) -> Result<DsaSignature, DsaError> {
+        self.sign_digest(hash, &hash.digest(msg))
+    }
+
+    /// Deterministic DSA over a precomputed digest. `hash` must be the
+    /// algorithm that produced `digest`; it also keys the RFC 6979 HMAC.
+    pub fn sign_digest(
+        &self,
+        hash: HashAlgorithm,
+        digest: &[u8],
+    ) -> Result<DsaSignature, DsaError> {
+        match hash {
+            HashAlgorithm::Sha1 => Err(DsaError::UnsupportedHash),
+            HashAlgorithm::Sha224 => self.sign_rfc6979::<Sha224>(digest),
+            HashAlgorithm::Sha256 => self.sign_rfc6979::<Sha256>(digest),
+            HashAlgorithm::Sha384 => self.sign_rfc6979::<Sha384>(digest),
+            HashAlgorithm::Sha512 => self.sign_rfc6979::<Sha512>(digest),
+            HashAlgorithm::Sha3_256 => self.sign_rfc6979::<Sha3_256>(digest),
+            HashAlgorithm::Sha3_384 => self.sign_rfc6979::<Sha3_384>(digest),
+            HashAlgorithm::Sha3_512 => self.sign_rfc6979::<Sha3_512>(digest),
+        }
+    }
+
+    /// RFC 6979 section 3.2 with HMAC_DRBG as the nonce generator, as in
+    /// `EcdsaPrivateKey`. r = (g^k mod p) mod q is computed with a
+    /// fixed-length ladder over the bits of q.
+    fn sign_rfc6979<D: Digest>(&self, digest: &[u8]) -> Result<DsaSignature, DsaError> {
+        let DsaParams { p, q, g, .. } = &**self.params();
+        let qlen = q.bit_length();
+        let rlen = qlen.div_ceil(8);
+
+        let z = bits2int(digest, qlen);
+        let x_octets = self.x.to_bytes_be_padded(rlen).expect("x < q");
+        let h_octets = (&z % q).to_bytes_be_padded(rlen).expect("reduced mod q");
+        let mut drbg = HmacDrbg::<D>::new(&x_octets, &h_octets, &[]);
+
+        let mut t = vec![0u8; rlen];
+        loop {
+            drbg.generate(&mut t, &[]).expect("well within DRBG limits");
+            let k = bits2int(&t, qlen);
+            if k.is_zero() || &k >= q {
+                continue;
+            }
+            let r = &g.modpow_ct(&k, p, qlen) % q;
+            if r.is_zero() {
+                continue;
+            }
+            let kinv = k.modinv(q).ok_or(DsaError::InvalidParameters)?;
+            let s = &(&kinv * &(&z + &(&r * &self.x))) % q;
+            if s.is_zero() {
+                continue;
+            }
+            return Ok(DsaSignature { r, s });
+        }
+    }
+}
+
+impl DsaSignature {
+    pub fn new(r: BigInt, s: BigInt) -> Self {
+        Self { r, s }
+    }
+
+    pub fn r(&self) -> &BigInt {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigInt {
+        &self.s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    /// The RFC 6979 appendix A.2.1 (1024-bit) and A.2.2 (2048-bit) keys.
+    fn rfc6979_key(bits: usize) -> DsaPrivateKey {
+        let (p, q, g, x) = if bits == 1024 {
+            (
+                "86f5ca03dcfeb225063ff830a0c769b9dd9d6153ad91d7ce27f787c43278b447e6533b86b18bed6e8a48b784a14c252c5be0dbf60b86d6385bd2f12fb763ed8873abfd3f5ba2e0a8c0a59082eac056935e529daf7c610467899c77adedfc846c881870b7b19b2b58f9be0521a17002e3bdd6b86685ee90b3d9a1b02b782b1779",
+                "996f967f6c8e388d9e28d01e205fba957a5698b1",
+                "07b0f92546150b62514bb771e2a0c0ce387f03bda6c56b505209ff25fd3c133d89bbcd97e904e09114d9a7defdeadfc9078ea544d2e401aeecc40bb9fbbf78fd87995a10a1c27cb7789b594ba7efb5c4326a9fe59a070e136db77175464adca417be5dce2f40d10a46a3a3943f26ab7fd9c0398ff8c76ee0a56826a8a88f1dbd",
+                "411602cb19a6ccc34494d79d98ef1e7ed5af25f7",
+            )
+        } else {
+            (
+                "9db6fb5951b66bb6fe1e140f1d2ce5502374161fd6538df1648218642f0b5c48c8f7a41aadfa187324b87674fa1822b00f1ecf8136943d7c55757264e5a1a44ffe012e9936e00c1d3e9310b01c7d179805d3058b2a9f4bb6f9716bfe6117c6b5b3cc4d9be341104ad4a80ad6c94e005f4b993e14f091eb51743bf33050c38de235567e1b34c3d6a5c0ceaa1a0f368213c3d19843d0b4b09dcb9fc72d39c8de41f1bf14d4bb4563ca28371621cad3324b6a2d392145bebfac748805236f5ca2fe92b871cd8f9c36d3292b5509ca8caa77a2adfc7bfd77dda6f71125a7456fea153e433256a2261c6a06ed3693797e7995fad5aabbcfbe3eda2741e375404ae25b",
+                "f2c3119374ce76c9356990b465374a17f23f9ed35089bd969f61c6dde9998c1f",
+                "5c7ff6b06f8f143fe8288433493e4769c4d988ace5be25a0e24809670716c613d7b0cee6932f8faa7c44d2cb24523da53fbe4f6ec3595892d1aa58c4328a06c46a15662e7eaa703a1decf8bbb2d05dbe2eb956c142a338661d10461c0d135472085057f3494309ffa73c611f78b32adbb5740c361c9f35be90997db2014e2ef5aa61782f52abeb8bd6432c4dd097bc5423b285dafb60dc364e8161f4a2a35aca3a10b1c4d203cc76a470a33afdcbdd92959859abd8b56e1725252d78eac66e71ba9ae3f1dd2487199874393cd4d832186800654760e1e34c09e4d155179f9ec0dc4473f996bdce6eed1cabed8b6f116f7ad9cf505df0f998e34ab27514b0ffe7",
+                "69c7548c21d0dfea6b9a51c9ead4e27c33d3b3f180316e5bcab92c933f0e4dbc",
+            )
+        };
+        let params = DsaParams::new(
+            BigInt::from_hex(p),
+            BigInt::from_hex(q),
+            BigInt::from_hex(g),
+            None,
+        )
+        .unwrap();
+        DsaPrivateKey::new(&params, BigInt::from_hex(x)).unwrap()
+    }
+
+    // RFC 6979 appendix A.2.1 and A.2.2: (bits, hash, message, r, s).
+    #[rustfmt::skip]
+    const RFC6979_VECTORS: &[(usize, HashAlgorithm, &str, &str, &str)] = &[
+        (1024, HashAlgorithm::Sha224, "sample",
+         "4bc3b686aea70145856814a6f1bb53346f02101e", "410697b92295d994d21edd2f4ada85566f6f94c1"),
+        (1024, HashAlgorithm::Sha256, "sample",
+         "81f2f5850be5bc123c43f71a3033e9384611c545", "4cdd914b65eb6c66a8aaad27299bee6b035f5e89"),
+        (1024, HashAlgorithm::Sha512, "test",
+         "8ea47e475ba8ac6f2d821da3bd212d11a3deb9a0", "7c670c7ad72b6c050c109e1790008097125433e8"),
+        (2048, HashAlgorithm::Sha256, "sample",
+         "eace8bdbbe353c432a795d9ec556c6d021f7a03f42c36e9bc87e4ac7932cc809",
+         "7081e175455f9247b812b74583e9e94f9ea79bd640dc962533b0680793a38d53"),
+        (2048, HashAlgorithm::Sha256, "test",
+         "8190012a1969f9957d56fccaad223186f423398d58ef5b3cefd5a4146a4476f0",
+         "7452a53f7075d417b4b013b278d1bb8bbd21863f5e7b1cee679cf2188e1ab19e"),
+        (2048, HashAlgorithm::Sha512, "sample",
+         "2016ed092dc5fb669b8efb3d1f31a91eecb199879be0cf78f02ba062cb4c942e",
+         "d0c76f84b5f091e141572a639a4fb8c230807eea7d55c8a154a224400aff2351"),
+    ];
+
+    #[test]
+    fn test_rfc6979_vectors() {
+        let key = rfc6979_key(1024);
+        assert_eq!(
+            key.public_key().y(),
+            &BigInt::from_hex("5df5e01ded31d0297e274e1691c192fe5868fef9e19a84776454b100cf16f65392195a38b90523e2542ee61871c0440cb87c322fc4b4d2ec5e1e7ec766e1be8d4ce935437dc11c3c8fd426338933ebfe739cb3465f4d3668c5e473508253b1e682f65cbdc4fae93c2ea212390e54905a86e2223170b44eaa7da5dd9ffcfb7f3b")
+        );
+        for (bits, hash, msg, r, s) in RFC6979_VECTORS {
+            let key = rfc6979_key(*bits);
+            let sig = key.sign(*hash, msg.as_bytes()).unwrap();
+            assert_eq!(
+                sig,
+                DsaSignature::new(BigInt::from_hex(r), BigInt::from_hex(s))
+            );
+            let public = key.public_key();
+            public.verify(*hash, msg.as_bytes(), &sig).unwrap();
+            assert_eq!(
+                public.verify(*hash, b"other", &sig),
+                Err(DsaError::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sha1_verify_only() {
+        // RFC 6979 A.2.1, SHA-1, "sample".
+        let key = rfc6979_key(1024);
+        let sig = DsaSignature::new(
+            BigInt::from_hex("2e1a0c2562b2912caaf89186fb0f42001585da55"),
+            BigInt::from_hex("29efb6b0aff2d7a68eb70ca313022253b9a88df5"),
+        );
+        key.public_key()
+            .verify(HashAlgorithm::Sha1, b"sample", &sig)
+            .unwrap();
+        assert_eq!(
+            key.sign(HashAlgorithm::Sha1, b"sample"),
+            Err(DsaError::UnsupportedHash)
+        );
+    }
+
+    #[test]
+    fn test_signature_range() {
+        let key = rfc6979_key(1024);
+        let public = key.public_key();
+        let sig = key.sign(HashAlgorithm::Sha256, b"sample").unwrap();
+        let q = key.params().q();
+        for bad in [
+            DsaSignature::new(BigInt::from_u64(0), sig.s().clone()),
+            DsaSignature::new(sig.r().clone(), BigInt::from_u64(0)),
+            DsaSignature::new(sig.r() + q, sig.s().clone()),
+            DsaSignature::new(sig.r().clone(), sig.s() + q),
+        ] {
+            assert_eq!(
+                public.verify(HashAlgorithm::Sha256, b"sample", &bad),
+                Err(DsaError::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_key_validation() {
+        let params = rfc6979_key(1024).params().clone();
+        let mut p1 = params.p().clone();
+        p1.minus_one();
+        // p - 1 has order 2, and 2 is not in the order-q subgroup.
+        for y in [
+            BigInt::from_u64(0),
+            BigInt::from_u64(1),
+            p1,
+            BigInt::from_u64(2),
+        ] {
+            assert_eq!(DsaPublicKey::new(&params, y), Err(DsaError::InvalidKey));
+        }
+        assert_eq!(
+            DsaPrivateKey::new(&params, params.q().clone()).unwrap_err(),
+            DsaError::InvalidKey
+        );
+        assert_eq!(
+            DsaParams::new(
+                params.p().clone(),
+                params.q().clone(),
+                BigInt::from_u64(2),
+                None
+            ),
+            Err(DsaError::InvalidParameters)
+        );
+    }
+
+    #[test]
+    fn test_composite_q() {
+        // q = 15 passes the checks in `new`, but nonces divisible by 3 or 5
+        // have no inverse.
+        let params = DsaParams::new(
+            BigInt::from_u64(31),
+            BigInt::from_u64(15),
+            BigInt::from_u64(4),
+            None,
+        )
+        .unwrap();
+        assert_eq!(params.validate(), Err(DsaError::InvalidParameters));
+        let key = DsaPrivateKey::new(&params, BigInt::from_u64(7)).unwrap();
+        let results: Vec<_> = (0u8..32)
+            .map(|i| key.sign(HashAlgorithm::Sha256, &[i]))
+            .collect();
+        assert!(results.contains(&Err(DsaError::InvalidParameters)));
+    }
+
+    /// Parameters from `openssl genpkey -genparam -algorithm DSA -pkeyopt
+    /// type:fips186_4 -pkeyopt gindex:1`, with the seed and pcounter that
+    /// `-text` prints.
+    fn openssl_params(bits: usize) -> Arc<DsaParams> {
+        let (hash, p, q, g, seed, counter) = if bits == 1024 {
+            (
+                HashAlgorithm::Sha1,
+                "d74f2d77d0f6399ef813f395c9b16fe038446113db26d2bf8b34e996b9a6e4e12ecc17f1b5a3a78c82efebbe769d7b8341102ad0ecced25b2462744877071438eb4deb0cee6cd1fd7b844efb407687a315ccdab909cf9ceaed985beb0832396d5389387360a3dac8150370f4bd7e3cbfa0cb09805de8872e73b40b5e11d588c1",
+                "a5cb4ccaeaa30e2d342c19da201a5f4ff8cdd703",
+                "3e7a9ff684d478d336fdb772bf9e6c29f4c76771af670ff91e7005556a8661f946e5fcccf98191fb9e796b9842129376479806227f4bbf9776fbea216fc83b869fd287189ee336f2d1b9bd4635709198a8a3a28be805e2cc4a96a8071799ef99ca9286cd8f7fd77acefdcce9736a521461f7edf8107e83ef1e2b32cda49e4db1",
+                "607030f38d70fc27f0bb64cba92ce134434a5590",
+                204,
+            )
+        } else {
+            (
+                HashAlgorithm::Sha256,
+                "e82833c9f0fa9a197e5d33a9ef518394a4284ca4818a7193bfafe33452a115b787ebd7c94a49b33c520c0b4025332fdb5e0b9fa5a0afc99058f217c75c116bafb2085916560b52fbeb2e6116f4acd3d9e88fa37db2d463d50c92cf5f66ee0d44f6985f06f1fb6a4c9bc08dca2a2865647a27e69a56595f9449aabd6401da06f1302084c00da762572ca76757d495f96b69d3bb2afaed9ef4c1c52e5870b5d97a1baa94671df9e1fb833411728ece59cb3a770a5a7a5c15bc3b608e6307637203912be3a403043b945fb2d1cf00029b86b54a69acd95d21d449fda7ed4eb00c6d2fc98e36fa6422172e2653512ba6d1032c141100fe891713383f2bfcf0c6fbb3",
+                "9f95360e8ba4734b8ebc5eac7bc099b3b5ef895c06d4295b2da288f59d5e486f",
+                "1a2b745fccab0f97e2aea5ec2866a9612a32e7259b3c28f4211c18f8ed558fa597d53f37312631a2df58cc90cad4ecb977531eed217c45201b4ec8b4535de63b8822b8e6f20bab40bc16c41efcb356aad99c21aa4579de5cea67aee9effa299d5e2737fbb7c4d62ddba5571b677d1938a4175a15f9b10839c7f885decd6ce34d9ae709572a5a7d2aeb763bfdea73e22cc76d8a60210223516e407e64c4cdf27c5ab935a3554bb3cc67e266812d63f62116b9a9d636aad9186d538c79a4d87fbb8afd96965ee5e43e934d59e9dc670397bd99975ebb809f1f9df85a01d2a4052cc1f48988698f5d38351cff0abd3b8dfc82b2d9badbb00f8546d8a0f571f53cba",
+                "f80a5a2b61bf6b942d9f8361185d2aed77a34b5cbe5305bf6616c2124d9f902d",
+                251,
+            )
+        };
+        DsaParams::new(
+            BigInt::from_hex(p),
+            BigInt::from_hex(q),
+            BigInt::from_hex(g),
+            Some(ValidationParams::new(
+                hash,
+                hex::decode(seed).unwrap(),
+                counter,
+                1,
+            )),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_openssl_params() {
+        let params = openssl_params(1024);
+        params.validate().unwrap();
+
+        // Any change to the validation data must be caught.
+        let v = params.validation().unwrap();
+        let mut seed = v.seed().to_vec();
+        seed[0] ^= 1;
+        for validation in [
+            ValidationParams::new(v.hash(), seed, v.counter(), v.index()),
+            ValidationParams::new(v.hash(), v.seed().to_vec(), v.counter() - 1, v.index()),
+            ValidationParams::new(v.hash(), v.seed().to_vec(), v.counter() + 1, v.index()),
+            ValidationParams::new(v.hash(), v.seed().to_vec(), v.counter(), 2),
+        ] {
+            let tampered = DsaParams::new(
+                params.p().clone(),
+                params.q().clone(),
+                params.g().clone(),
+                Some(validation),
+            )
+            .unwrap();
+            assert_eq!(tampered.validate(), Err(DsaError::InvalidParameters));
+        }
+    }
+
+    #[test]
+    #[ignore = "walks 252 candidate 2048-bit primes; run with --ignored --release"]
+    fn test_validate_openssl_params_2048() {
+        openssl_params(2048).validate().unwrap();
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = HmacDrbg::<Sha256>::new(b"dsa parameter entropy", b"nonce", b"");
+        assert_eq!(
+            DsaParams::generate(1024, 256, HashAlgorithm::Sha256, &mut rng),
+            Err(DsaError::InvalidParameters)
+        );
+        assert_eq!(
+            DsaParams::generate(2048, 256, HashAlgorithm::Sha224, &mut rng),
+            Err(DsaError::UnsupportedHash)
+        );
+
+        let params = DsaParams::generate(1024, 160, HashAlgorithm::Sha256, &mut rng).unwrap();
+        assert_eq!(params.p().bit_length(), 1024);
+        assert_eq!(params.q().bit_length(), 160);
+        let v = params.validation().unwrap();
+        assert_eq!((v.seed().len(), v.index()), (20, 1));
+        params.validate().unwrap();
+
+        let key = DsaPrivateKey::generate(&params, &mut rng);
+        let sig = key.sign(HashAlgorithm::Sha256, b"msg").unwrap();
+        key.public_key()
+            .verify(HashAlgorithm::Sha256, b"msg", &sig)
+            .unwrap();
+    }
+}
@@ -0,0 +1,273 @@
+use super::field::{Fp, PrimeField};
+use super::point::AffinePoint;
+use crate::asn1::{oid, ObjectIdentifier};
+use crate::bigint::BigInt;
+use std::sync::{Arc, LazyLock};
+
+/// A short-Weierstrass curve y^2 = x^3 + ax + b over GF(p) with a base point
+/// of prime order n.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Curve {
+    name: &'static str,
+    field: Arc<PrimeField>,
+    a: Fp,
+    b: Fp,
+    /// 3b, used by the complete addition formulas.
+    b3: Fp,
+    gx: Fp,
+    gy: Fp,
+    order: BigInt,
+    cofactor: u64,
+    /// Endomorphism data for curves that support GLV multiplication.
+    glv: Option<Glv>,
+    /// Whether ECDSA on this curve must use s <= n/2 (the secp256k1
+    /// ecosystem's convention).
+    low_s: bool,
+}
+
+/// The GLV endomorphism phi(x, y) = (beta x, y) = lambda (x, y), with a
+/// short basis {(a1, b1), (a2, b2)} of the lattice of (x, y) such that
+/// x + y lambda = 0 mod n, used to split a scalar into two half-length
+/// ones (Gallant, Lambert and Vanstone, CRYPTO 2001).
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Glv {
+    beta: Fp,
+    lambda: BigInt,
+    a1: BigInt,
+    /// -b1, which is positive for secp256k1.
+    minus_b1: BigInt,
+    a2: BigInt,
+    /// b2, which equals a1 for secp256k1.
+    b2: BigInt,
+}
+
+static P256: LazyLock<Arc<Curve>> = LazyLock::new(|| {
+    // SEC 2 section 2.4.2 / FIPS 186-4 D.1.2.3.
+    let field = PrimeField::p256();
+    Curve::new(
+        "P-256",
+        field.clone(),
+        BigInt::from_hex("ffffffff00000001000000000000000000000000fffffffffffffffffffffffc"),
+        BigInt::from_hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b"),
+        BigInt::from_hex("6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"),
+        BigInt::from_hex("4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"),
+        BigInt::from_hex("ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632551"),
+        1,
+    )
+});
+
+static P384: LazyLock<Arc<Curve>> = LazyLock::new(|| {
+    // SEC 2 section 2.5.1 / FIPS 186-4 D.1.2.4.
+    let field = PrimeField::p384();
+    Curve::new(
+        "P-384",
+        field.clone(),
+        BigInt::from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000fffffffc"),
+        BigInt::from_hex("b3312fa7e23ee7e4988e056be3f82d19181d9c6efe8141120314088f5013875ac656398d8a2ed19d2a85c8edd3ec2aef"),
+        BigInt::from_hex("aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7"),
+        BigInt::from_hex("3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f"),
+        BigInt::from_hex("ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52973"),
+        1,
+    )
+});
+
+static SECP256K1: LazyLock<Arc<Curve>> = LazyLock::new(|| {
+    // SEC 2 section 2.4.1.
+    let field = PrimeField::secp256k1();
+    let mut curve = Curve::build(
+        "secp256k1",
+        field.clone(),
+        BigInt::from_u64(0),
+        BigInt::from_u64(7),
+        BigInt::from_hex("79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"),
+        BigInt::from_hex("483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"),
+        BigInt::from_hex("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141"),
+        1,
+    );
+    curve.glv = Some(Glv {
+        beta: field.element(&BigInt::from_hex(
+            "7ae96a2b657c07106e64479eac3434e99cf0497512f58995c1396c28719501ee",
+        )),
+        lambda: BigInt::from_hex(
+            "5363ad4cc05c30e0a5261c028812645a122e22ea20816678df02967c1b23bd72",
+        ),
+        a1: BigInt::from_hex("3086d221a7d46bcde86c90e49284eb15"),
+        minus_b1: BigInt::from_hex("e4437ed6010e88286f547fa90abfe4c3"),
+        a2: BigInt::from_hex("114ca50f7a8e2f3f657c1108d9d44cfd8"),
+        b2: BigInt::from_hex("3086d221a7d46bcde86c90e49284eb15"),
+    });
+    curve.low_s = true;
+    Arc::new(curve)
+});
+
+impl Curve {
+    /// Defines a curve from its domain parameters. The parameters are
+    /// trusted: no check is made that G lies on the curve or has order n.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &'static str,
+        field: Arc<PrimeField>,
+        a: BigInt,
+        b: BigInt,
+        gx: BigInt,
+        gy: BigInt,
+        order: BigInt,
+        cofactor: u64,
+    ) -> Arc<Self> {
+        Arc::new(Self::build(name, field, a, b, gx, gy, order, cofactor))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn build(
+        name: &'static str,
+        field: Arc<PrimeField>,
+        a: BigInt,
+        b: BigInt,
+        gx: BigInt,
+        gy: BigInt,
+        order: BigInt,
+        cofactor: u64,
+    ) -> Self {
+        let b = field.element(&b);
+        let b3 = &b.double() + &b;
+        Self {
+            name,
+            a: field.element(&a),
+            b,
+            b3,
+            gx: field.element(&gx),
+            gy: field.element(&gy),
+            field,
+            order,
+            cofactor,
+            glv: None,
+            low_s: false,
+        }
+    }
+
+    /// NIST P-256 (secp256r1).
+    pub fn p256() -> Arc<Self> {
+        P256.clone()
+    }
+
+    /// NIST P-384 (secp384r1).
+    pub fn p384() -> Arc<Self> {
+        P384.clone()
+    }
+
+    /// secp256k1, with GLV multiplication and low-s ECDSA.
+    pub fn secp256k1() -> Arc<Self> {
+        SECP256K1.clone()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The namedCurve OID (RFC 5480 section 2.1.1.1) of a built-in curve.
+    pub fn oid(&self) -> Option<ObjectIdentifier> {
+        match self.name {
+            "P-256" => Some(oid::SECP256R1),
+            "P-384" => Some(oid::SECP384R1),
+            "secp256k1" => Some(oid::SECP256K1),
+            _ => None,
+        }
+    }
+
+    /// The built-in curve named by `oid`.
+    pub fn from_oid(oid: &ObjectIdentifier) -> Option<Arc<Self>> {
+        [Self::p256(), Self::p384(), Self::secp256k1()]
+            .into_iter()
+            .find(|curve| curve.oid().as_ref() == Some(oid))
+    }
+
+    pub fn field(&self) -> &Arc<PrimeField> {
+        &self.field
+    }
+
+    pub fn a(&self) -> &Fp {
+        &self.a
+    }
+
+    pub fn b(&self) -> &Fp {
+        &self.b
+    }
+
+    pub(crate) fn b3(&self) -> &Fp {
+        &self.b3
+    }
+
+    /// The order n of the base point.
+    pub fn order(&self) -> &BigInt {
+        &self.order
+    }
+
+    pub fn cofactor(&self) -> u64 {
+        self.cofactor
+    }
+
+    pub(crate) fn glv(&self) -> Option<&Glv> {
+        self.glv.as_ref()
+    }
+
+    /// Whether ECDSA signatures on this curve are required to have a low s.
+    pub fn enforces_low_s(&self) -> bool {
+        self.low_s
+    }
+
+    /// Length of a scalar modulo n in bytes.
+    pub fn scalar_len(&self) -> usize {
+        self.order.bit_length().div_ceil(8)
+    }
+
+    /// The base point G.
+    pub fn generator(self: &Arc<Self>) -> AffinePoint {
+        AffinePoint::new(self, self.gx.clone(), self.gy.clone())
+            .expect("base point lies on the curve")
+    }
+
+    /// Whether (x, y) satisfies y^2 = x^3 + ax + b.
+    pub fn contains(&self, x: &Fp, y: &Fp) -> bool {
+        let rhs = &(&(&x.square() * x) + &(&self.a * x)) + &self.b;
+        y.square() == rhs
+    }
+}
+
+impl Glv {
+    pub(crate) fn beta(&self) -> &Fp {
+        &self.beta
+    }
+
+    #[cfg(test)]
+    pub(crate) fn lambda(&self) -> &BigInt {
+        &self.lambda
+    }
+
+    /// Splits k into (k1, k2) with k = k1 + k2 lambda mod n and both about
+    /// half the length of n. Each half is returned as a magnitude and a
+    /// flag that is set when the half is negative.
+    pub(crate) fn decompose(&self, k: &BigInt, n: &BigInt) -> ((BigInt, bool), (BigInt, bool)) {
+        let k = k % n;
+        // c1 = round(b2 k / n), c2 = round(-b1 k / n).
+        let mut half_n = n.clone();
+        half_n >>= 1;
+        let c1 = &(&(&self.b2 * &k) + &half_n) / n;
+        let c2 = &(&(&self.minus_b1 * &k) + &half_n) / n;
+        // k1 = k - c1 a1 - c2 a2, k2 = -c1 b1 - c2 b2, all mod n.
+        let k1 = sub_mod(&sub_mod(&k, &(&c1 * &self.a1), n), &(&c2 * &self.a2), n);
+        let k2 = sub_mod(&(&c1 * &self.minus_b1), &(&c2 * &self.b2), n);
+        let signed = |v: BigInt| {
+            if v > half_n {
+                (n - &v, true)
+            } else {
+                (v, false)
+            }
+        };
+        (signed(k1), signed(k2))
+    }
+}
+
+/// (a - b) mod n for non-negative a and b.
+fn sub_mod(a: &BigInt, b: &BigInt, n: &BigInt) -> BigInt {
+    &(&(a % n) + &(n - &(b % n))) % n
+}
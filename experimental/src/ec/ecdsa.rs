@@ -0,0 +1,608 @@
+use super::curve::Curve;
+use super::point::AffinePoint;
+use super::EcError;
+use crate::asn1::{DerReader, DerWriter};
+use crate::bigint::BigInt;
+use crate::digest::{Digest, HashAlgorithm};
+use crate::drbg::HmacDrbg;
+use crate::sha2::{Sha224, Sha256, Sha384, Sha512};
+use crate::sha3::{Sha3_256, Sha3_384, Sha3_512};
+use rand::RngCore;
+use std::sync::Arc;
+
+/// Whether verification accepts both s and n - s.
+///
+/// ECDSA signatures are malleable: (r, s) and (r, n - s) are both valid.
+/// Protocols that need a unique encoding (Bitcoin's BIP 62/146, for one)
+/// require the "low" s <= n/2.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SPolicy {
+    /// Accept any s in [1, n-1], as X.509 and FIPS 186 do.
+    AllowHigh,
+    /// Reject s > n/2.
+    RequireLow,
+}
+
+/// An ECDSA public key: a validated, non-identity curve point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcdsaPublicKey {
+    point: AffinePoint,
+}
+
+/// An ECDSA private key d in [1, n-1] with its public point dG.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EcdsaPrivateKey {
+    d: BigInt,
+    public: EcdsaPublicKey,
+}
+
+impl std::fmt::Debug for EcdsaPrivateKey {
+    // Never print the private scalar.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EcdsaPrivateKey")
+            .field("public", &self.public)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An ECDSA signature (r, s).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EcdsaSignature {
+    r: BigInt,
+    s: BigInt,
+}
+
+impl EcdsaPublicKey {
+    pub fn new(point: AffinePoint) -> Result<Self, EcError> {
+        if point.is_identity() {
+            return Err(EcError::Identity);
+        }
+        Ok(Self { point })
+    }
+
+    pub fn from_sec1(curve: &Arc<Curve>, bytes: &[u8]) -> Result<Self, EcError> {
+        Self::new(AffinePoint::from_sec1(curve, bytes)?)
+    }
+
+    pub fn to_sec1(&self, compressed: bool) -> Vec<u8> {
+        self.point.to_sec1(compressed)
+    }
+
+    pub fn point(&self) -> &AffinePoint {
+        &self.point
+    }
+
+    pub fn curve(&self) -> &Arc<Curve> {
+        self.point.curve()
+    }
+
+    /// ECDSA verification (SEC 1 section 4.1.4) of `msg` hashed with `hash`.
+    /// Curves that enforce low s reject high s whatever the policy.
+    pub fn verify(
+        &self,
+        hash: HashAlgorithm,
+        msg: &[u8],
+        sig: &EcdsaSignature,
+        policy: SPolicy,
+    ) -> Result<(), EcError> {
+        self.verify_digest(&hash.digest(msg), sig, policy)
+    }
+
+    /// Verifies a signature over an already computed message digest.
+    pub fn verify_digest(
+        &self,
+        digest: &[u8],
+        sig: &EcdsaSignature,
+        policy: SPolicy,
+    ) -> Result<(), EcError> {
+        let curve = self.curve();
+        let n = curve.order();
+        if sig.r.is_zero() || sig.s.is_zero() || &sig.r >= n || &sig.s >= n {
+            return Err(EcError::Verification);
+        }
+        let require_low = policy == SPolicy::RequireLow || curve.enforces_low_s();
+        if require_low && !sig.is_low_s(curve) {
+            return Err(EcError::Verification);
+        }
+        let e = bits2int(digest, n.bit_length());
+        let w = sig.s.modinv(n).ok_or(EcError::Verification)?;
+        let u1 = &(&e * &w) % n;
+        let u2 = &(&sig.r * &w) % n;
+        // Both scalars are public, so the variable-time multiply is fine.
+        let r = curve
+            .generator()
+            .to_projective()
+            .mul_vartime(&u1)
+            .add(&self.point.to_projective().mul_vartime(&u2))
+            .to_affine();
+        let Some(x) = r.x() else {
+            return Err(EcError::Verification);
+        };
+        if x.value() % n == sig.r {
+            Ok(())
+        } else {
+            Err(EcError::Verification)
+        }
+    }
+}
+
+impl EcdsaPrivateKey {
+    pub fn from_scalar(curve: &Arc<Curve>, d: BigInt) -> Result<Self, EcError> {
+        if d.is_zero() || &d >= curve.order() {
+            return Err(EcError::InvalidScalar);
+        }
+        let point = curve.generator().to_projective().mul(&d).to_affine();
+        Ok(Self {
+            d,
+            public: EcdsaPublicKey::new(point)?,
+        })
+    }
+
+    /// Draws d uniformly from [1, n-1] by rejection sampling.
+    pub fn generate<G: RngCore + ?Sized>(curve: &Arc<Curve>, rng: &mut G) -> Self {
+        let bits = curve.order().bit_length();
+        loop {
+            let d = BigInt::random_with(bits, rng);
+            if let Ok(key) = Self::from_scalar(curve, d) {
+                return key;
+            }
+        }
+    }
+
+    pub fn d(&self) -> &BigInt {
+        &self.d
+    }
+
+    pub fn public_key(&self) -> &EcdsaPublicKey {
+        &self.public
+    }
+
+    pub fn curve(&self) -> &Arc<Curve> {
+        self.public.curve()
+    }
+
+    /// Deterministic ECDSA (RFC 6979) over `msg` hashed with `hash`. SHA-1
+    /// is refused for new signatures. On curves that enforce low s (see
+    /// `Curve::enforces_low_s`) the result is normalized to s <= n/2.
+    pub fn sign(&self, hash: HashAlgorithm, msg: &[u8]) -> Result<EcdsaSignature, EcError> {
+        self.sign_digest(hash, &hash.digest(msg))
+    }
+
+    /// Deterministic ECDSA over a precomputed digest. `hash` must be the
+    /// algorithm that produced `digest`; it also keys the RFC 6979 HMAC.
+    pub fn sign_digest(
+        &self,
+        hash: HashAlgorithm,
+        digest: &[u8],
+    ) -> Result<EcdsaSignature, EcError> {
+        match hash {
+            HashAlgorithm::Sha1 => Err(EcError::UnsupportedHash),
+            HashAlgorithm::Sha224 => Ok(self.sign_rfc6979::<Sha224>(digest)),
+            HashAlgorithm::Sha256 => Ok(self.sign_rfc6979::<Sha256>(digest)),
+            HashAlgorithm::Sha384 => Ok(self.sign_rfc6979::<Sha384>(digest)),
+            HashAlgorithm::Sha512 => Ok(self.sign_rfc6979::<Sha512>(digest)),
+            HashAlgorithm::Sha3_256 => Ok(self.sign_rfc6979::<Sha3_256>(digest)),
+            HashAlgorithm::Sha3_384 => Ok(self.sign_rfc6979::<Sha3_384>(digest)),
+            HashAlgorithm::Sha3_512 => Ok(self.sign_rfc6979::<Sha3_512>(digest)),
+        }
+    }
+
+    /// RFC 6979 section 3.2. The nonce generator is HMAC_DRBG instantiated
+    /// with int2octets(x) as entropy and bits2octets(h1) as nonce (section
+    /// 3.3), and a rejected candidate is followed by the same K/V update the
+    /// DRBG performs at the end of each generate call.
+    fn sign_rfc6979<D: Digest>(&self, digest: &[u8]) -> EcdsaSignature {
+        let curve = self.curve();
+        let n = curve.order();
+        let qlen = n.bit_length();
+        let rlen = curve.scalar_len();
+
+        let e = bits2int(digest, qlen);
+        let x_octets = self.d.to_bytes_be_padded(rlen).expect("d < n");
+        let h_octets = (&e % n).to_bytes_be_padded(rlen).expect("reduced mod n");
+        let mut drbg = HmacDrbg::<D>::new(&x_octets, &h_octets, &[]);
+
+        let g = curve.generator().to_projective();
+        let mut t = vec![0u8; rlen];
+        loop {
+            drbg.generate(&mut t, &[]).expect("well within DRBG limits");
+            let k = bits2int(&t, qlen);
+            if k.is_zero() || &k >= n {
+                continue;
+            }
+            let point = g.mul(&k).to_affine();
+            let r = point.x().expect("k is in [1, n-1]").value() % n;
+            if r.is_zero() {
+                continue;
+            }
+            let kinv = k.modinv(n).expect("n is prime");
+            let s = &(&kinv * &(&e + &(&r * &self.d))) % n;
+            if s.is_zero() {
+                continue;
+            }
+            let sig = EcdsaSignature { r, s };
+            if curve.enforces_low_s() {
+                return sig.normalize_s(curve);
+            }
+            return sig;
+        }
+    }
+}
+
+/// bits2int (RFC 6979 section 2.3.2): the leftmost `qlen` bits of `bytes`
+/// as an integer. This is also how SEC 1 truncates the message hash.
+pub(crate) fn bits2int(bytes: &[u8], qlen: usize) -> BigInt {
+    let mut value = BigInt::from_bytes_be(bytes);
+    let blen = 8 * bytes.len();
+    if blen > qlen {
+        value >>= (blen - qlen) as u32;
+    }
+    value
+}
+
+impl EcdsaSignature {
+    pub fn new(r: BigInt, s: BigInt) -> Self {
+        Self { r, s }
+    }
+
+    pub fn r(&self) -> &BigInt {
+        &self.r
+    }
+
+    pub fn s(&self) -> &BigInt {
+        &self.s
+    }
+
+    pub fn is_low_s(&self, curve: &Curve) -> bool {
+        let mut half = curve.order().clone();
+        half >>= 1;
+        self.s <= half
+    }
+
+    /// The equivalent signature with s replaced by n - s if it is high.
+    pub fn normalize_s(&self, curve: &Curve) -> Self {
+        if self.is_low_s(curve) {
+            return self.clone();
+        }
+        Self {
+            r: self.r.clone(),
+            s: curve.order() - &self.s,
+        }
+    }
+
+    /// DER encoding of Ecdsa-Sig-Value ::= SEQUENCE { r INTEGER, s INTEGER }
+    /// (RFC 3279 section 2.2.3).
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.integer(&self.r);
+            w.integer(&self.s);
+        });
+        w.finish()
+    }
+
+    /// Strict DER decoding: definite minimal lengths, minimal non-negative
+    /// integers and no trailing data. Range checks against n happen at
+    /// verification time.
+    pub fn from_der(bytes: &[u8]) -> Result<Self, EcError> {
+        DerReader::parse(bytes, |r| {
+            r.sequence(|r| {
+                Ok(Self {
+                    r: r.integer()?,
+                    s: r.integer()?,
+                })
+            })
+        })
+        .map_err(|_| EcError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    // RFC 6979 appendix A.2.5 (P-256) and A.2.6 (P-384); the same values
+    // are produced by OpenSSL's deterministic signing.
+    const P256_X: &str = "c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721";
+    const P384_X: &str = "6b9d3dad2e1b8c1c05b19875b6659f4de23c3b667bf297ba9aa47740787137d896d5724e4c70a825f872c9ea60d2edf5";
+
+    #[rustfmt::skip]
+    const RFC6979_VECTORS: &[(&str, HashAlgorithm, &str, &str, &str)] = &[
+        ("P-256", HashAlgorithm::Sha224, "sample", "53b2fff5d1752b2c689df257c04c40a587fababb3f6fc2702f1343af7ca9aa3f", "b9afb64fdc03dc1a131c7d2386d11e349f070aa432a4acc918bea988bf75c74c"),
+        ("P-256", HashAlgorithm::Sha256, "sample", "efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716", "f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8"),
+        ("P-256", HashAlgorithm::Sha384, "sample", "0eafea039b20e9b42309fb1d89e213057cbf973dc0cfc8f129edddc800ef7719", "4861f0491e6998b9455193e34e7b0d284ddd7149a74b95b9261f13abde940954"),
+        ("P-256", HashAlgorithm::Sha512, "sample", "8496a60b5e9b47c825488827e0495b0e3fa109ec4568fd3f8d1097678eb97f00", "2362ab1adbe2b8adf9cb9edab740ea6049c028114f2460f96554f61fae3302fe"),
+        ("P-256", HashAlgorithm::Sha256, "test", "f1abb023518351cd71d881567b1ea663ed3efcf6c5132b354f28d3b0b7d38367", "019f4113742a2b14bd25926b49c649155f267e60d3814b4c0cc84250e46f0083"),
+        ("P-256", HashAlgorithm::Sha512, "test", "461d93f31b6540894788fd206c07cfa0cc35f46fa3c91816fff1040ad1581a04", "39af9f15de0db8d97e72719c74820d304ce5226e32dedae67519e840d1194e55"),
+        ("P-384", HashAlgorithm::Sha224, "sample", "42356e76b55a6d9b4631c865445dbe54e056d3b3431766d0509244793c3f9366450f76ee3de43f5a125333a6be060122", "9da0c81787064021e78df658f2fbb0b042bf304665db721f077a4298b095e4834c082c03d83028efbf93a3c23940ca8d"),
+        ("P-384", HashAlgorithm::Sha256, "sample", "21b13d1e013c7fa1392d03c5f99af8b30c570c6f98d4ea8e354b63a21d3daa33bde1e888e63355d92fa2b3c36d8fb2cd", "f3aa443fb107745bf4bd77cb3891674632068a10ca67e3d45db2266fa7d1feebefdc63eccd1ac42ec0cb8668a4fa0ab0"),
+        ("P-384", HashAlgorithm::Sha384, "sample", "94edbb92a5ecb8aad4736e56c691916b3f88140666ce9fa73d64c4ea95ad133c81a648152e44acf96e36dd1e80fabe46", "99ef4aeb15f178cea1fe40db2603138f130e740a19624526203b6351d0a3a94fa329c145786e679e7b82c71a38628ac8"),
+        ("P-384", HashAlgorithm::Sha512, "sample", "ed0959d5880ab2d869ae7f6c2915c6d60f96507f9cb3e047c0046861da4a799cfe30f35cc900056d7c99cd7882433709", "512c8cceee3890a84058ce1e22dbc2198f42323ce8aca9135329f03c068e5112dc7cc3ef3446defceb01a45c2667fdd5"),
+        ("P-384", HashAlgorithm::Sha384, "test", "8203b63d3c853e8d77227fb377bcf7b7b772e97892a80f36ab775d509d7a5feb0542a7f0812998da8f1dd3ca3cf023db", "ddd0760448d42d8a43af45af836fce4de8be06b485e9b61b827c2f13173923e06a739f040649a667bf3b828246baa5a5"),
+    ];
+
+    fn key(curve: &str) -> EcdsaPrivateKey {
+        match curve {
+            "P-256" => EcdsaPrivateKey::from_scalar(&Curve::p256(), BigInt::from_hex(P256_X)),
+            _ => EcdsaPrivateKey::from_scalar(&Curve::p384(), BigInt::from_hex(P384_X)),
+        }
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rfc6979_vectors() {
+        for (curve, hash, msg, r, s) in RFC6979_VECTORS {
+            let key = key(curve);
+            let sig = key.sign(*hash, msg.as_bytes()).unwrap();
+            assert_eq!(
+                sig,
+                EcdsaSignature::new(BigInt::from_hex(r), BigInt::from_hex(s))
+            );
+            key.public_key()
+                .verify(*hash, msg.as_bytes(), &sig, SPolicy::AllowHigh)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_public_key_from_rfc6979() {
+        let key = key("P-256");
+        assert_eq!(
+            hex::encode(&key.public_key().to_sec1(false)),
+            "0460fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6\
+             7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"
+        );
+    }
+
+    #[test]
+    fn test_verify_openssl_signature() {
+        // Randomized signature from OpenSSL over "rspki ecdsa" with SHA-256.
+        let public = key("P-256").public_key().clone();
+        let der = hex::decode(
+            "3046022100edf7532799bed49e9fd449702a97a47098e129c9bf71d999d1a5d02ec8130e13\
+             022100d3cabf7816efcf09edfeca4ede922212d29d39f86f1db173bb46828bcd9ababc",
+        )
+        .unwrap();
+        let sig = EcdsaSignature::from_der(&der).unwrap();
+        assert_eq!(sig.to_der(), der);
+        let msg = b"rspki ecdsa";
+        public
+            .verify(HashAlgorithm::Sha256, msg, &sig, SPolicy::AllowHigh)
+            .unwrap();
+        assert_eq!(
+            public.verify(
+                HashAlgorithm::Sha256,
+                b"rspki ecdsA",
+                &sig,
+                SPolicy::AllowHigh
+            ),
+            Err(EcError::Verification)
+        );
+        assert_eq!(
+            public.verify(HashAlgorithm::Sha384, msg, &sig, SPolicy::AllowHigh),
+            Err(EcError::Verification)
+        );
+    }
+
+    #[test]
+    fn test_s_policy() {
+        let key = key("P-256");
+        let curve = key.curve().clone();
+        let msg = b"sample";
+        // The RFC 6979 SHA-256 "sample" signature happens to have a high s.
+        let high = key.sign(HashAlgorithm::Sha256, msg).unwrap();
+        assert!(!high.is_low_s(&curve));
+        let public = key.public_key();
+        assert_eq!(
+            public.verify(HashAlgorithm::Sha256, msg, &high, SPolicy::RequireLow),
+            Err(EcError::Verification)
+        );
+        let low = high.normalize_s(&curve);
+        assert!(low.is_low_s(&curve));
+        public
+            .verify(HashAlgorithm::Sha256, msg, &low, SPolicy::AllowHigh)
+            .unwrap();
+        public
+            .verify(HashAlgorithm::Sha256, msg, &low, SPolicy::RequireLow)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_secp256k1_low_s() {
+        // RFC 6979 signatures from OpenSSL over secp256k1 with SHA-256; the
+        // "rspki secp256k1" one has a high s and is stored here normalized.
+        let curve = Curve::secp256k1();
+        let key = EcdsaPrivateKey::from_scalar(
+            &curve,
+            BigInt::from_hex("ebb2c082fd7727890a28ac82f6bdf97bad8de9f5d7c9028692de1a255cad3e0f"),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&key.public_key().to_sec1(false)),
+            "04779dd197a5df977ed2cf6cb31d82d43328b790dc6b3b7d4437a427bd5847dfcd\
+             e94b724a555b6d017bb7607c3e3281daf5b1699d6ef4124975c9237b917d426f"
+        );
+        for (msg, r, s) in [
+            (
+                "sample",
+                "663656c743dd96ddcffe7ef30188086a24e876e821fa1126ac3038b564baa65b",
+                "324d523cb5718d05c24c06f06608f3850f0117cdc2bf61f36a91110f5bbcfe2b",
+            ),
+            (
+                "test",
+                "5fabbfbf8efcfc01acd9436af44bd18b91e70791348469a7dd718c7f1f288039",
+                "122dd586e41ddc6e089be5af11e456ba084d2f28140fd424c1c32fd3baf1304a",
+            ),
+            (
+                "rspki secp256k1",
+                "5aeeefe8010c2e84cbaae854a06954cc525605f78d27ed915773ae98ad61fdb2",
+                "0dcad1a086396f3643cb108b5987f3ee6d243929e01db9683b4a622dc61aadf3",
+            ),
+        ] {
+            let sig = key.sign(HashAlgorithm::Sha256, msg.as_bytes()).unwrap();
+            assert_eq!(
+                sig,
+                EcdsaSignature::new(BigInt::from_hex(r), BigInt::from_hex(s))
+            );
+            let public = key.public_key();
+            public
+                .verify(
+                    HashAlgorithm::Sha256,
+                    msg.as_bytes(),
+                    &sig,
+                    SPolicy::AllowHigh,
+                )
+                .unwrap();
+            // The high-s twin is rejected even under AllowHigh.
+            let high = EcdsaSignature::new(sig.r().clone(), curve.order() - sig.s());
+            assert_eq!(
+                public.verify(
+                    HashAlgorithm::Sha256,
+                    msg.as_bytes(),
+                    &high,
+                    SPolicy::AllowHigh
+                ),
+                Err(EcError::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_signature() {
+        let key = key("P-256");
+        let public = key.public_key();
+        let n = key.curve().order().clone();
+        let sig = key.sign(HashAlgorithm::Sha256, b"x").unwrap();
+        for bad in [
+            EcdsaSignature::new(BigInt::from_u64(0), sig.s().clone()),
+            EcdsaSignature::new(sig.r().clone(), BigInt::from_u64(0)),
+            EcdsaSignature::new(sig.r() + &n, sig.s().clone()),
+            EcdsaSignature::new(sig.r().clone(), sig.s() + &n),
+        ] {
+            assert_eq!(
+                public.verify(HashAlgorithm::Sha256, b"x", &bad, SPolicy::AllowHigh),
+                Err(EcError::Verification)
+            );
+        }
+        assert_eq!(
+            key.sign(HashAlgorithm::Sha1, b"x"),
+            Err(EcError::UnsupportedHash)
+        );
+    }
+
+    #[test]
+    fn test_der_strictness() {
+        let good = hex::decode("3006020101020102").unwrap();
+        assert_eq!(
+            EcdsaSignature::from_der(&good),
+            Ok(EcdsaSignature::new(
+                BigInt::from_u64(1),
+                BigInt::from_u64(2)
+            ))
+        );
+        for bad in [
+            "3006020101020102ff",     // trailing data
+            "3007020101020102",       // length past the end
+            "308106020101020102",     // non-minimal long-form length
+            "300702020001020102",     // non-minimal integer
+            "3006020181020102",       // negative integer
+            "30050200020102",         // empty integer
+            "3009020101020102020103", // extra element
+            "3106020101020102",       // wrong outer tag
+        ] {
+            assert_eq!(
+                EcdsaSignature::from_der(&hex::decode(bad).unwrap()),
+                Err(EcError::InvalidSignature),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut drbg = HmacDrbg::<Sha256>::new(b"ecdsa keygen test entropy 012345", b"nonce", &[]);
+        for curve in [Curve::p256(), Curve::p384()] {
+            let key = EcdsaPrivateKey::generate(&curve, &mut drbg);
+            let sig = key.sign(HashAlgorithm::Sha384, b"message").unwrap();
+            let der = sig.to_der();
+            let parsed = EcdsaSignature::from_der(&der).unwrap();
+            key.public_key()
+                .verify(
+                    HashAlgorithm::Sha384,
+                    b"message",
+                    &parsed,
+                    SPolicy::AllowHigh,
+                )
+                .unwrap();
+            let encoded = key.public_key().to_sec1(true);
+            assert_eq!(
+                EcdsaPublicKey::from_sec1(&curve, &encoded).as_ref(),
+                Ok(key.public_key())
+            );
+        }
+    }
+
+    /// Runs a Wycheproof verification file, returning the number of
+    /// [invalid, valid] cases.
+    fn wycheproof(curve: &Arc<Curve>, hash: HashAlgorithm, vectors: &str) -> [usize; 2] {
+        let mut results = [0; 2];
+        let mut public = None;
+        for line in vectors.lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields[0] == "key" {
+                let sec1 = hex::decode(fields[1]).unwrap();
+                public = Some(EcdsaPublicKey::from_sec1(curve, &sec1).unwrap());
+                continue;
+            }
+            let [msg, der] = [fields[1], fields[2]].map(|f| match f {
+                "-" => Vec::new(),
+                f => hex::decode(f).unwrap(),
+            });
+            let valid = fields[3] == "valid";
+            results[valid as usize] += 1;
+
+            let Ok(sig) = EcdsaSignature::from_der(&der) else {
+                assert!(!valid, "{}", line);
+                continue;
+            };
+            // Wycheproof accepts both s and n - s, but curves that enforce
+            // low s reject the high one.
+            let expected = valid && (sig.is_low_s(curve) || !curve.enforces_low_s());
+            let verified = public
+                .as_ref()
+                .unwrap()
+                .verify(hash, &msg, &sig, SPolicy::AllowHigh);
+            assert_eq!(verified.is_ok(), expected, "{}", line);
+        }
+        results
+    }
+
+    #[test]
+    fn test_wycheproof_p256() {
+        let vectors = include_str!("../../testdata/ecdsa_secp256r1_sha256.txt");
+        assert_eq!(
+            wycheproof(&Curve::p256(), HashAlgorithm::Sha256, vectors),
+            [301, 170]
+        );
+    }
+
+    #[test]
+    fn test_wycheproof_p384() {
+        let vectors = include_str!("../../testdata/ecdsa_secp384r1_sha384.txt");
+        assert_eq!(
+            wycheproof(&Curve::p384(), HashAlgorithm::Sha384, vectors),
+            [239, 168]
+        );
+    }
+
+    #[test]
+    fn test_wycheproof_secp256k1() {
+        let vectors = include_str!("../../testdata/ecdsa_secp256k1_sha256.txt");
+        assert_eq!(
+            wycheproof(&Curve::secp256k1(), HashAlgorithm::Sha256, vectors),
+            [299, 164]
+        );
+    }
+}
@@ -0,0 +1,623 @@
+use crate::bigint::BigInt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::{Arc, LazyLock};
+
+/// How products are reduced modulo p.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reduction {
+    /// Long division by p; works for any modulus.
+    Generic,
+    /// FIPS 186-4 D.2.3 fast reduction for p = 2^256 - 2^224 + 2^192 + 2^96 - 1.
+    P256,
+    /// FIPS 186-4 D.2.4 fast reduction for p = 2^384 - 2^128 - 2^96 + 2^32 - 1.
+    P384,
+    /// Folding reduction for secp256k1's p = 2^256 - 2^32 - 977, using
+    /// 2^256 = 2^32 + 977 mod p.
+    Secp256k1,
+}
+
+/// A prime field GF(p). Elements hold an `Arc` to their field, so mixing
+/// elements of different fields is caught at run time.
+///
+/// Arithmetic is on `BigInt` and is not constant-time.
+#[derive(Debug, PartialEq, Eq)]
+pub struct PrimeField {
+    modulus: BigInt,
+    reduction: Reduction,
+    /// Length of an encoded element in bytes.
+    byte_len: usize,
+}
+
+static P256_FIELD: LazyLock<Arc<PrimeField>> = LazyLock::new(|| {
+    PrimeField::with_reduction(
+        BigInt::from_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff"),
+        Reduction::P256,
+    )
+});
+
+static P384_FIELD: LazyLock<Arc<PrimeField>> = LazyLock::new(|| {
+    PrimeField::with_reduction(
+        BigInt::from_hex(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffeffffffff0000000000000000ffffffff",
+        ),
+        Reduction::P384,
+    )
+});
+
+static SECP256K1_FIELD: LazyLock<Arc<PrimeField>> = LazyLock::new(|| {
+    PrimeField::with_reduction(
+        BigInt::from_hex("fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f"),
+        Reduction::Secp256k1,
+    )
+});
+
+impl PrimeField {
+    /// GF(p) with generic reduction. `modulus` must be an odd prime; this is
+    /// not checked.
+    pub fn new(modulus: BigInt) -> Arc<Self> {
+        Self::with_reduction(modulus, Reduction::Generic)
+    }
+
+    fn with_reduction(modulus: BigInt, reduction: Reduction) -> Arc<Self> {
+        let byte_len = modulus.bit_length().div_ceil(8);
+        Arc::new(Self {
+            modulus,
+            reduction,
+            byte_len,
+        })
+    }
+
+    /// The P-256 base field, with Solinas reduction.
+    pub fn p256() -> Arc<Self> {
+        P256_FIELD.clone()
+    }
+
+    /// The P-384 base field, with Solinas reduction.
+    pub fn p384() -> Arc<Self> {
+        P384_FIELD.clone()
+    }
+
+    /// The secp256k1 base field, with folding reduction.
+    pub fn secp256k1() -> Arc<Self> {
+        SECP256K1_FIELD.clone()
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    pub fn byte_len(&self) -> usize {
+        self.byte_len
+    }
+
+    /// The element `value mod p`.
+    pub fn element(self: &Arc<Self>, value: &BigInt) -> Fp {
+        Fp {
+            value: value % &self.modulus,
+            field: self.clone(),
+        }
+    }
+
+    pub fn from_u64(self: &Arc<Self>, value: u64) -> Fp {
+        self.element(&BigInt::from_u64(value))
+    }
+
+    pub fn zero(self: &Arc<Self>) -> Fp {
+        self.from_u64(0)
+    }
+
+    pub fn one(self: &Arc<Self>) -> Fp {
+        self.from_u64(1)
+    }
+
+    /// Decodes a big-endian element, rejecting values >= p or of the wrong
+    /// length (SEC 1 section 2.3.6).
+    pub fn from_bytes_be(self: &Arc<Self>, bytes: &[u8]) -> Option<Fp> {
+        if bytes.len() != self.byte_len {
+            return None;
+        }
+        let value = BigInt::from_bytes_be(bytes);
+        if value >= self.modulus {
+            return None;
+        }
+        Some(Fp {
+            value,
+            field: self.clone(),
+        })
+    }
+
+    /// Reduces a product of two reduced elements (so `value < p^2`).
+    fn reduce(&self, value: &BigInt) -> BigInt {
+        match self.reduction {
+            Reduction::Generic => value % &self.modulus,
+            Reduction::P256 => self.reduce_solinas(value, 8, &P256_TERMS),
+            Reduction::P384 => self.reduce_solinas(value, 12, &P384_TERMS),
+            Reduction::Secp256k1 => self.reduce_fold(value, 0x1_0000_03d1),
+        }
+    }
+
+    /// Reduction for p = 2^256 - c: split off everything above bit 256 and
+    /// fold it back in multiplied by c until the value fits, then subtract p
+    /// at most once.
+    fn reduce_fold(&self, value: &BigInt, c: u64) -> BigInt {
+        let c = BigInt::from_u64(c);
+        let mut result = value.clone();
+        while result.limbs().len() > 4 {
+            let limbs = result.limbs();
+            let low = BigInt::from_limbs(limbs[..4].to_vec());
+            let high = BigInt::from_limbs(limbs[4..].to_vec());
+            result = &low + &(&high * &c);
+        }
+        while result >= self.modulus {
+            result = &result - &self.modulus;
+        }
+        result
+    }
+
+    /// Solinas reduction: `value` is split into 32-bit words c0..c(2n-1) and
+    /// the result is a signed sum of n-word terms built from those words.
+    fn reduce_solinas(&self, value: &BigInt, n: usize, terms: &[SolinasTerm]) -> BigInt {
+        let mut c = vec![0i64; 2 * n];
+        for (i, limb) in value.limbs().iter().enumerate() {
+            c[2 * i] = (limb & 0xffff_ffff) as i64;
+            c[2 * i + 1] = (limb >> 32) as i64;
+        }
+
+        let mut acc = vec![0i64; n];
+        for (coefficient, words) in terms {
+            for (i, word) in words.iter().rev().enumerate() {
+                if let Some(w) = word {
+                    acc[i] += coefficient * c[*w];
+                }
+            }
+        }
+
+        // Propagate carries; whatever is left over is a small signed
+        // multiple of 2^(32n).
+        let mut carry = 0i64;
+        let mut words = Vec::with_capacity(n);
+        for a in acc {
+            let v = a + carry;
+            words.push(v.rem_euclid(1 << 32) as u64);
+            carry = v.div_euclid(1 << 32);
+        }
+        let low = BigInt::from_limbs(words.chunks(2).map(|w| w[0] | (w[1] << 32)).collect());
+
+        let top = &BigInt::from_u64(1) << (32 * n);
+        let mut result = if carry >= 0 {
+            &low + &(&top * &BigInt::from_u64(carry as u64))
+        } else {
+            // Add enough multiples of p (< 2^(32n)) to cover the deficit.
+            let deficit = &top * &BigInt::from_u64(carry.unsigned_abs());
+            let cover = &self.modulus * &BigInt::from_u64(carry.unsigned_abs() + 1);
+            &(&low + &cover) - &deficit
+        };
+        while result >= self.modulus {
+            result = &result - &self.modulus;
+        }
+        result
+    }
+}
+
+/// One term of a Solinas reduction: a coefficient and the 32-bit word indices
+/// it takes, most significant first (`None` is a zero word).
+type SolinasTerm = (i64, &'static [Option<usize>]);
+
+const fn w(i: usize) -> Option<usize> {
+    Some(i)
+}
+const Z: Option<usize> = None;
+
+/// FIPS 186-4 D.2.3: r = s1 + 2s2 + 2s3 + s4 + s5 - s6 - s7 - s8 - s9.
+const P256_TERMS: [SolinasTerm; 9] = [
+    (1, &[w(7), w(6), w(5), w(4), w(3), w(2), w(1), w(0)]),
+    (2, &[w(15), w(14), w(13), w(12), w(11), Z, Z, Z]),
+    (2, &[Z, w(15), w(14), w(13), w(12), Z, Z, Z]),
+    (1, &[w(15), w(14), Z, Z, Z, w(10), w(9), w(8)]),
+    (1, &[w(8), w(13), w(15), w(14), w(13), w(11), w(10), w(9)]),
+    (-1, &[w(10), w(8), Z, Z, Z, w(13), w(12), w(11)]),
+    (-1, &[w(11), w(9), Z, Z, w(15), w(14), w(13), w(12)]),
+    (-1, &[w(12), Z, w(10), w(9), w(8), w(15), w(14), w(13)]),
+    (-1, &[w(13), Z, w(11), w(10), w(9), Z, w(15), w(14)]),
+];
+
+/// FIPS 186-4 D.2.4: r = s1 + 2s2 + s3 + s4 + s5 + s6 + s7 - s8 - s9 - s10.
+const P384_TERMS: [SolinasTerm; 10] = [
+    (
+        1,
+        &[
+            w(11),
+            w(10),
+            w(9),
+            w(8),
+            w(7),
+            w(6),
+            w(5),
+            w(4),
+            w(3),
+            w(2),
+            w(1),
+            w(0),
+        ],
+    ),
+    (2, &[Z, Z, Z, Z, Z, w(23), w(22), w(21), Z, Z, Z, Z]),
+    (
+        1,
+        &[
+            w(23),
+            w(22),
+            w(21),
+            w(20),
+            w(19),
+            w(18),
+            w(17),
+            w(16),
+            w(15),
+            w(14),
+            w(13),
+            w(12),
+        ],
+    ),
+    (
+        1,
+        &[
+            w(20),
+            w(19),
+            w(18),
+            w(17),
+            w(16),
+            w(15),
+            w(14),
+            w(13),
+            w(12),
+            w(23),
+            w(22),
+            w(21),
+        ],
+    ),
+    (
+        1,
+        &[
+            w(19),
+            w(18),
+            w(17),
+            w(16),
+            w(15),
+            w(14),
+            w(13),
+            w(12),
+            w(20),
+            Z,
+            w(23),
+            Z,
+        ],
+    ),
+    (1, &[Z, Z, Z, Z, w(23), w(22), w(21), w(20), Z, Z, Z, Z]),
+    (1, &[Z, Z, Z, Z, Z, Z, w(23), w(22), w(21), Z, Z, w(20)]),
+    (
+        -1,
+        &[
+            w(22),
+            w(21),
+            w(20),
+            w(19),
+            w(18),
+            w(17),
+            w(16),
+            w(15),
+            w(14),
+            w(13),
+            w(12),
+            w(23),
+        ],
+    ),
+    (-1, &[Z, Z, Z, Z, Z, Z, Z, w(23), w(22), w(21), w(20), Z]),
+    (-1, &[Z, Z, Z, Z, Z, Z, Z, w(23), w(23), Z, Z, Z]),
+];
+
+/// An element of a `PrimeField`, always fully reduced.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Fp {
+    value: BigInt,
+    field: Arc<PrimeField>,
+}
+
+impl Fp {
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    pub fn field(&self) -> &Arc<PrimeField> {
+        &self.field
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+
+    /// Parity of the canonical representative, as used by SEC 1 point
+    /// compression.
+    pub fn is_odd(&self) -> bool {
+        !self.value.is_even()
+    }
+
+    /// Swaps `a` and `b` when `choice` is set, touching every limb the same
+    /// way either way.
+    pub fn conditional_swap(a: &mut Fp, b: &mut Fp, choice: bool) {
+        a.check_same_field(b);
+        let len = a.field.modulus.limbs().len();
+        BigInt::conditional_swap(&mut a.value, &mut b.value, choice, len);
+    }
+
+    /// Fixed-length big-endian encoding (FE2OS).
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        self.value
+            .to_bytes_be_padded(self.field.byte_len)
+            .expect("element is smaller than p")
+    }
+
+    fn with_value(&self, value: BigInt) -> Fp {
+        Fp {
+            value,
+            field: self.field.clone(),
+        }
+    }
+
+    fn check_same_field(&self, other: &Fp) {
+        assert!(
+            Arc::ptr_eq(&self.field, &other.field) || self.field == other.field,
+            "field elements from different fields"
+        );
+    }
+
+    pub fn square(&self) -> Fp {
+        self * self
+    }
+
+    pub fn double(&self) -> Fp {
+        self + self
+    }
+
+    /// `self^exp` by square-and-multiply, using the field's reduction.
+    pub fn pow(&self, exp: &BigInt) -> Fp {
+        let mut result = self.field.one();
+        for i in (0..exp.bit_length()).rev() {
+            result = result.square();
+            if exp.bit(i) {
+                result = &result * self;
+            }
+        }
+        result
+    }
+
+    /// Multiplicative inverse by Fermat's little theorem (a^(p-2)), or
+    /// `None` for zero.
+    pub fn inv(&self) -> Option<Fp> {
+        if self.is_zero() {
+            return None;
+        }
+        let p_minus_2 = &self.field.modulus - &BigInt::from_u64(2);
+        Some(self.pow(&p_minus_2))
+    }
+
+    /// Legendre symbol test: is `self` a non-zero square?
+    pub fn is_square(&self) -> bool {
+        let mut e = self.field.modulus.clone();
+        e.minus_one();
+        e >>= 1;
+        self.pow(&e) == self.field.one()
+    }
+
+    /// A square root, or `None` if `self` is not a square.
+    ///
+    /// Uses the (p+1)/4 exponent when p = 3 mod 4 and Tonelli-Shanks
+    /// otherwise. Which of the two roots is returned is unspecified.
+    pub fn sqrt(&self) -> Option<Fp> {
+        if self.is_zero() {
+            return Some(self.clone());
+        }
+        if !self.is_square() {
+            return None;
+        }
+        let p = &self.field.modulus;
+        if p % 4u64 == 3 {
+            let mut e = p + &BigInt::from_u64(1);
+            e >>= 2;
+            return Some(self.pow(&e));
+        }
+
+        // Tonelli-Shanks: p - 1 = q * 2^s with q odd.
+        let mut q = p.clone();
+        q.minus_one();
+        let s = q.trailing_zeros();
+        q >>= s;
+
+        // Any non-residue z works; search upward from 2.
+        let mut z = self.field.from_u64(2);
+        while z.is_square() {
+            z = &z + &self.field.one();
+        }
+
+        let mut m = s;
+        let mut c = z.pow(&q);
+        let mut t = self.pow(&q);
+        let mut q1 = q.clone();
+        q1 = &q1 + &BigInt::from_u64(1);
+        q1 >>= 1;
+        let mut r = self.pow(&q1);
+        let one = self.field.one();
+        while t != one {
+            // Least i with t^(2^i) = 1.
+            let mut i = 0;
+            let mut t2 = t.clone();
+            while t2 != one {
+                t2 = t2.square();
+                i += 1;
+            }
+            let mut b = c.clone();
+            for _ in 0..(m - i - 1) {
+                b = b.square();
+            }
+            m = i;
+            c = b.square();
+            t = &t * &c;
+            r = &r * &b;
+        }
+        Some(r)
+    }
+}
+
+impl std::fmt::Debug for Fp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Fp({})", crate::hex::encode(&self.to_bytes_be()))
+    }
+}
+
+impl Add for &Fp {
+    type Output = Fp;
+    fn add(self, rhs: &Fp) -> Fp {
+        self.check_same_field(rhs);
+        let mut sum = &self.value + &rhs.value;
+        if sum >= self.field.modulus {
+            sum = &sum - &self.field.modulus;
+        }
+        self.with_value(sum)
+    }
+}
+
+impl Sub for &Fp {
+    type Output = Fp;
+    fn sub(self, rhs: &Fp) -> Fp {
+        self.check_same_field(rhs);
+        let mut diff = &(&self.value + &self.field.modulus) - &rhs.value;
+        if diff >= self.field.modulus {
+            diff = &diff - &self.field.modulus;
+        }
+        self.with_value(diff)
+    }
+}
+
+impl Mul for &Fp {
+    type Output = Fp;
+    fn mul(self, rhs: &Fp) -> Fp {
+        self.check_same_field(rhs);
+        self.with_value(self.field.reduce(&(&self.value * &rhs.value)))
+    }
+}
+
+impl Neg for &Fp {
+    type Output = Fp;
+    fn neg(self) -> Fp {
+        if self.is_zero() {
+            return self.clone();
+        }
+        self.with_value(&self.field.modulus - &self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_reduction_matches_generic() {
+        for field in [
+            PrimeField::p256(),
+            PrimeField::p384(),
+            PrimeField::secp256k1(),
+        ] {
+            let generic = PrimeField::new(field.modulus().clone());
+            let bits = field.modulus().bit_length();
+            // Random products plus the extremes (p-1)^2 and 0.
+            let mut p1 = field.modulus().clone();
+            p1.minus_one();
+            let mut cases = vec![(p1.clone(), p1.clone()), (BigInt::from_u64(0), p1)];
+            for _ in 0..200 {
+                cases.push((BigInt::random(bits), BigInt::random(bits)));
+            }
+            for (a, b) in cases {
+                let (fa, fb) = (field.element(&a), field.element(&b));
+                let (ga, gb) = (generic.element(&a), generic.element(&b));
+                assert_eq!((&fa * &fb).value(), (&ga * &gb).value());
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_sub_neg() {
+        let f = PrimeField::p256();
+        let mut p1 = f.modulus().clone();
+        p1.minus_one();
+        let max = f.element(&p1);
+        let one = f.one();
+        assert!((&max + &one).is_zero());
+        assert_eq!(&f.zero() - &one, max);
+        assert_eq!(-&one, max);
+        assert_eq!(-&f.zero(), f.zero());
+        assert_eq!(&(&max + &max) - &max, max);
+    }
+
+    #[test]
+    fn test_inverse() {
+        for f in [
+            PrimeField::p256(),
+            PrimeField::p384(),
+            PrimeField::new(BigInt::from_u64(1_000_003)),
+        ] {
+            for _ in 0..5 {
+                let a = f.element(&BigInt::random(f.modulus().bit_length()));
+                if a.is_zero() {
+                    continue;
+                }
+                assert_eq!(&a * &a.inv().unwrap(), f.one());
+            }
+            assert!(f.zero().inv().is_none());
+        }
+    }
+
+    #[test]
+    fn test_sqrt_three_mod_four() {
+        let f = PrimeField::p256();
+        for _ in 0..5 {
+            let a = f.element(&BigInt::random(256));
+            let sq = a.square();
+            let root = sq.sqrt().unwrap();
+            assert!(root == a || root == -&a);
+        }
+        // -1 is a non-residue when p = 3 mod 4.
+        assert!((-&f.one()).sqrt().is_none());
+    }
+
+    #[test]
+    fn test_tonelli_shanks() {
+        // P-224's p = 1 mod 2^96, the hard case for Tonelli-Shanks.
+        let f = PrimeField::new(BigInt::from_hex(
+            "ffffffffffffffffffffffffffffffff000000000000000000000001",
+        ));
+        let a = f.element(&BigInt::from_hex(
+            "abe67d921e8407fcf776b89341efb4126e12f4e33d8ad2a5e1ef9e36",
+        ));
+        let root = a.sqrt().unwrap();
+        assert_eq!(root.square(), a);
+
+        let small = PrimeField::new(BigInt::from_u64(17)); // 17 = 1 mod 16
+        for x in 1..17u64 {
+            let v = small.from_u64(x);
+            match v.sqrt() {
+                Some(r) => assert_eq!(r.square(), v),
+                None => assert!(!v.is_square()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes() {
+        let f = PrimeField::p256();
+        let a = f.from_u64(0x0102);
+        let bytes = a.to_bytes_be();
+        assert_eq!(bytes.len(), 32);
+        assert_eq!(f.from_bytes_be(&bytes), Some(a));
+        assert_eq!(f.from_bytes_be(&f.modulus().to_bytes_be()), None);
+        assert_eq!(f.from_bytes_be(&[1, 2]), None);
+    }
+}
@@ -0,0 +1,22 @@
+pub mod curve;
+pub mod ecdsa;
+pub mod field;
+pub mod point;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EcError {
+    /// The SEC 1 encoding is malformed (bad tag, length or coordinate).
+    InvalidEncoding,
+    /// The coordinates do not satisfy the curve equation.
+    NotOnCurve,
+    /// The point at infinity was given where a real point is required.
+    Identity,
+    /// A private scalar outside [1, n-1].
+    InvalidScalar,
+    /// The hash may be used for verification but not for new signatures.
+    UnsupportedHash,
+    /// The DER signature is malformed or not in canonical form.
+    InvalidSignature,
+    /// Signature verification failed.
+    Verification,
+}
@@ -0,0 +1,588 @@
+use super::curve::Curve;
+use super::field::Fp;
+use super::EcError;
+use crate::bigint::BigInt;
+use std::sync::Arc;
+
+/// A point in affine coordinates, or the point at infinity.
+#[derive(Clone, Debug)]
+pub struct AffinePoint {
+    curve: Arc<Curve>,
+    /// `None` for the point at infinity.
+    coords: Option<(Fp, Fp)>,
+}
+
+/// A point in homogeneous projective coordinates (X : Y : Z), x = X/Z and
+/// y = Y/Z. The identity is (0 : 1 : 0).
+///
+/// Addition uses the complete formulas of Renes, Costello and Batina
+/// ("Complete addition formulas for prime order elliptic curves", 2015,
+/// Algorithm 1), so there are no special cases for doubling or the
+/// identity.
+#[derive(Clone, Debug)]
+pub struct ProjectivePoint {
+    curve: Arc<Curve>,
+    x: Fp,
+    y: Fp,
+    z: Fp,
+}
+
+impl AffinePoint {
+    /// Validates and builds the point (x, y). Cofactor-1 curves need no
+    /// subgroup check beyond the curve equation; on others n (x, y) must be
+    /// the identity.
+    pub fn new(curve: &Arc<Curve>, x: Fp, y: Fp) -> Result<Self, EcError> {
+        if !curve.contains(&x, &y) {
+            return Err(EcError::NotOnCurve);
+        }
+        let point = Self {
+            curve: curve.clone(),
+            coords: Some((x, y)),
+        };
+        if curve.cofactor() != 1
+            && !point
+                .to_projective()
+                .mul_unreduced(curve.order())
+                .is_identity()
+        {
+            return Err(EcError::NotOnCurve);
+        }
+        Ok(point)
+    }
+
+    pub fn identity(curve: &Arc<Curve>) -> Self {
+        Self {
+            curve: curve.clone(),
+            coords: None,
+        }
+    }
+
+    pub fn curve(&self) -> &Arc<Curve> {
+        &self.curve
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.coords.is_none()
+    }
+
+    pub fn x(&self) -> Option<&Fp> {
+        self.coords.as_ref().map(|(x, _)| x)
+    }
+
+    pub fn y(&self) -> Option<&Fp> {
+        self.coords.as_ref().map(|(_, y)| y)
+    }
+
+    pub fn to_projective(&self) -> ProjectivePoint {
+        match &self.coords {
+            None => ProjectivePoint::identity(&self.curve),
+            Some((x, y)) => ProjectivePoint {
+                curve: self.curve.clone(),
+                x: x.clone(),
+                y: y.clone(),
+                z: self.curve.field().one(),
+            },
+        }
+    }
+
+    /// SEC 1 section 2.3.3 encoding: 0x04 || X || Y, or 0x02/0x03 || X when
+    /// compressed. The identity encodes as a single zero byte.
+    pub fn to_sec1(&self, compressed: bool) -> Vec<u8> {
+        let Some((x, y)) = &self.coords else {
+            return vec![0x00];
+        };
+        let mut out = Vec::with_capacity(1 + 2 * self.curve.field().byte_len());
+        if compressed {
+            out.push(if y.is_odd() { 0x03 } else { 0x02 });
+            out.extend(x.to_bytes_be());
+        } else {
+            out.push(0x04);
+            out.extend(x.to_bytes_be());
+            out.extend(y.to_bytes_be());
+        }
+        out
+    }
+
+    /// SEC 1 section 2.3.4 decoding with full public-key validation. The
+    /// identity is rejected, since it is never a valid public key.
+    pub fn from_sec1(curve: &Arc<Curve>, bytes: &[u8]) -> Result<Self, EcError> {
+        let field = curve.field();
+        let len = field.byte_len();
+        match bytes.first() {
+            Some(0x00) if bytes.len() == 1 => Err(EcError::Identity),
+            Some(0x04) if bytes.len() == 1 + 2 * len => {
+                let x = field
+                    .from_bytes_be(&bytes[1..1 + len])
+                    .ok_or(EcError::InvalidEncoding)?;
+                let y = field
+                    .from_bytes_be(&bytes[1 + len..])
+                    .ok_or(EcError::InvalidEncoding)?;
+                Self::new(curve, x, y)
+            }
+            Some(&tag @ (0x02 | 0x03)) if bytes.len() == 1 + len => {
+                let x = field
+                    .from_bytes_be(&bytes[1..])
+                    .ok_or(EcError::InvalidEncoding)?;
+                let rhs = &(&(&x.square() * &x) + &(curve.a() * &x)) + curve.b();
+                let mut y = rhs.sqrt().ok_or(EcError::NotOnCurve)?;
+                if y.is_odd() != (tag == 0x03) {
+                    y = -&y;
+                }
+                Self::new(curve, x, y)
+            }
+            _ => Err(EcError::InvalidEncoding),
+        }
+    }
+}
+
+impl PartialEq for AffinePoint {
+    fn eq(&self, other: &Self) -> bool {
+        self.curve == other.curve && self.coords == other.coords
+    }
+}
+
+impl Eq for AffinePoint {}
+
+impl ProjectivePoint {
+    pub fn identity(curve: &Arc<Curve>) -> Self {
+        let field = curve.field();
+        Self {
+            curve: curve.clone(),
+            x: field.zero(),
+            y: field.one(),
+            z: field.zero(),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.z.is_zero()
+    }
+
+    pub fn to_affine(&self) -> AffinePoint {
+        let Some(zinv) = self.z.inv() else {
+            return AffinePoint::identity(&self.curve);
+        };
+        AffinePoint {
+            curve: self.curve.clone(),
+            coords: Some((&self.x * &zinv, &self.y * &zinv)),
+        }
+    }
+
+    /// Complete addition (RCB Algorithm 1, arbitrary a).
+    pub fn add(&self, other: &Self) -> Self {
+        assert!(self.curve == other.curve, "points on different curves");
+        let (a, b3) = (self.curve.a(), self.curve.b3());
+        let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+        let (x2, y2, z2) = (&other.x, &other.y, &other.z);
+
+        let mut t0 = x1 * x2;
+        let mut t1 = y1 * y2;
+        let mut t2 = z1 * z2;
+        let mut t3 = &(x1 + y1) * &(x2 + y2);
+        let mut t4 = &t0 + &t1;
+        t3 = &t3 - &t4;
+        t4 = &(x1 + z1) * &(x2 + z2);
+        let mut t5 = &t0 + &t2;
+        t4 = &t4 - &t5;
+        t5 = &(y1 + z1) * &(y2 + z2);
+        let mut x3 = &t1 + &t2;
+        t5 = &t5 - &x3;
+        let mut z3 = a * &t4;
+        x3 = b3 * &t2;
+        z3 = &x3 + &z3;
+        x3 = &t1 - &z3;
+        z3 = &t1 + &z3;
+        let mut y3 = &x3 * &z3;
+        t1 = &t0.double() + &t0;
+        t2 = a * &t2;
+        t4 = b3 * &t4;
+        t1 = &t1 + &t2;
+        t2 = &t0 - &t2;
+        t2 = a * &t2;
+        t4 = &t4 + &t2;
+        t0 = &t1 * &t4;
+        y3 = &y3 + &t0;
+        t0 = &t5 * &t4;
+        x3 = &t3 * &x3;
+        x3 = &x3 - &t0;
+        t0 = &t3 * &t1;
+        z3 = &t5 * &z3;
+        z3 = &z3 + &t0;
+
+        Self {
+            curve: self.curve.clone(),
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            curve: self.curve.clone(),
+            x: self.x.clone(),
+            y: -&self.y,
+            z: self.z.clone(),
+        }
+    }
+
+    /// Swaps `a` and `b` when `choice` is set, without branching on it.
+    fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        Fp::conditional_swap(&mut a.x, &mut b.x, choice);
+        Fp::conditional_swap(&mut a.y, &mut b.y, choice);
+        Fp::conditional_swap(&mut a.z, &mut b.z, choice);
+    }
+
+    /// Scalar multiplication `k * self` with a Montgomery ladder.
+    ///
+    /// The scalar is reduced mod n and then processed over a fixed number of
+    /// bits (the bit length of n) with one addition, one doubling and masked
+    /// swaps per bit, so the sequence of operations does not depend on `k`.
+    /// The underlying `BigInt` arithmetic is not itself constant-time.
+    pub fn mul(&self, k: &BigInt) -> Self {
+        let order = self.curve.order();
+        let k = k % order;
+        let mut r0 = Self::identity(&self.curve);
+        let mut r1 = self.clone();
+        for i in (0..order.bit_length()).rev() {
+            let bit = k.bit(i);
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+            r1 = r0.add(&r1);
+            r0 = r0.double();
+            Self::conditional_swap(&mut r0, &mut r1, bit);
+        }
+        r0
+    }
+
+    /// `k * self` by double-and-add, or by `mul_glv` on curves with an
+    /// endomorphism. Faster than `mul` but leaks `k` through timing, so use
+    /// it only with public scalars (e.g. in verification).
+    pub fn mul_vartime(&self, k: &BigInt) -> Self {
+        self.mul_glv(k).unwrap_or_else(|| self.mul_unreduced(k))
+    }
+
+    /// `k * self` by double-and-add over every bit of k. `mul` and
+    /// `mul_glv` reduce k mod n first, which would turn n itself into zero.
+    /// Variable-time.
+    fn mul_unreduced(&self, k: &BigInt) -> Self {
+        let mut result = Self::identity(&self.curve);
+        for i in (0..k.bit_length()).rev() {
+            result = result.double();
+            if k.bit(i) {
+                result = result.add(self);
+            }
+        }
+        result
+    }
+
+    /// `k * self` using the curve's GLV endomorphism, or `None` if it has
+    /// none. k is split as k1 + k2 lambda and k1 P + k2 phi(P) is computed
+    /// with a joint double-and-add over half as many bits. Variable-time.
+    pub fn mul_glv(&self, k: &BigInt) -> Option<Self> {
+        let glv = self.curve.glv()?;
+        let ((k1, neg1), (k2, neg2)) = glv.decompose(k, self.curve.order());
+        let p1 = if neg1 { self.neg() } else { self.clone() };
+        let phi = Self {
+            curve: self.curve.clone(),
+            x: &self.x * glv.beta(),
+            y: self.y.clone(),
+            z: self.z.clone(),
+        };
+        let p2 = if neg2 { phi.neg() } else { phi };
+        let both = p1.add(&p2);
+
+        let mut result = Self::identity(&self.curve);
+        for i in (0..k1.bit_length().max(k2.bit_length())).rev() {
+            result = result.double();
+            match (k1.bit(i), k2.bit(i)) {
+                (true, true) => result = result.add(&both),
+                (true, false) => result = result.add(&p1),
+                (false, true) => result = result.add(&p2),
+                (false, false) => {}
+            }
+        }
+        Some(result)
+    }
+}
+
+impl PartialEq for ProjectivePoint {
+    // (X1 : Y1 : Z1) = (X2 : Y2 : Z2) iff the cross products agree; this also
+    // holds for the identity, whose X is always zero.
+    fn eq(&self, other: &Self) -> bool {
+        self.curve == other.curve
+            && &self.x * &other.z == &other.x * &self.z
+            && &self.y * &other.z == &other.y * &self.z
+    }
+}
+
+impl Eq for ProjectivePoint {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ec::field::PrimeField;
+    use crate::hex;
+
+    fn point(curve: &Arc<Curve>, x: &str, y: &str) -> AffinePoint {
+        let field = curve.field();
+        AffinePoint::new(
+            curve,
+            field.element(&BigInt::from_hex(x)),
+            field.element(&BigInt::from_hex(y)),
+        )
+        .unwrap()
+    }
+
+    fn check_vectors(curve: &Arc<Curve>, vectors: &[(&str, &str, &str)]) {
+        let g = curve.generator().to_projective();
+        for (k, x, y) in vectors {
+            let k = BigInt::from_hex(k);
+            let expected = point(curve, x, y);
+            assert_eq!(g.mul(&k).to_affine(), expected);
+            assert_eq!(g.mul_vartime(&k).to_affine(), expected);
+        }
+    }
+
+    // k*G computed with OpenSSL (via python-cryptography's derive_private_key).
+    #[test]
+    fn test_p256_scalar_mult() {
+        check_vectors(
+            &Curve::p256(),
+            &[
+                (
+                    "1",
+                    "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+                    "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5",
+                ),
+                (
+                    "2",
+                    "7cf27b188d034f7e8a52380304b51ac3c08969e277f21b35a60b48fc47669978",
+                    "07775510db8ed040293d9ac69f7430dbba7dade63ce982299e04b79d227873d1",
+                ),
+                (
+                    "3",
+                    "5ecbe4d1a6330a44c8f7ef951d4bf165e6c6b721efada985fb41661bc6e7fd6c",
+                    "8734640c4998ff7e374b06ce1a64a2ecd82ab036384fb83d9a79b127a27d5032",
+                ),
+                (
+                    "18ebbb95eed0e13",
+                    "339150844ec15234807fe862a86be77977dbfb3ae3d96f4c22795513aeaab82f",
+                    "b1c14ddfdc8ec1b2583f51e85a5eb3a155840f2034730e9b5ada38b674336a21",
+                ),
+                (
+                    "ffffffff00000000ffffffffffffffffbce6faada7179e84f3b9cac2fc632550",
+                    "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296",
+                    "b01cbd1c01e58065711814b583f061e9d431cca994cea1313449bf97c840ae0a",
+                ),
+                (
+                    "07a81949e60d93473ab434fed7e439fe07158ab795f381835b6913cd87684f35",
+                    "5acb764f5282c4fedc3fc308feb92ae001bdd60b3f36a4ccbea20c661b73589f",
+                    "a98458d88aef4d83d70340ecdd17711198ec745d6e4e0ee5043a73c0bbe14dbc",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_p384_scalar_mult() {
+        check_vectors(
+            &Curve::p384(),
+            &[
+                ("1", "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7", "3617de4a96262c6f5d9e98bf9292dc29f8f41dbd289a147ce9da3113b5f0b8c00a60b1ce1d7e819d7a431d7c90ea0e5f"),
+                ("2", "08d999057ba3d2d969260045c55b97f089025959a6f434d651d207d19fb96e9e4fe0e86ebe0e64f85b96a9c75295df61", "8e80f1fa5b1b3cedb7bfe8dffd6dba74b275d875bc6cc43e904e505f256ab4255ffd43e94d39e22d61501e700a940e80"),
+                ("3", "077a41d4606ffa1464793c7e5fdc7d98cb9d3910202dcd06bea4f240d3566da6b408bbae5026580d02d7e5c70500c831", "c995f7ca0b0c42837d0bbe9602a9fc998520b41c85115aa5f7684c0edc111eacc24abd6be4b5d298b65f28600a2f1df1"),
+                ("18ebbb95eed0e13", "a499efe48839bc3abcd1c5cedbdd51904f9514db44f4686db918983b0c9dc3aee05a88b72433e9515f91a329f5f4fa60", "3b7ca28ef31f809c2f1ba24aaed847d0f8b406a4b8968542de139db5828ca410e615d1182e25b91b1131e230b727d36a"),
+                ("ffffffffffffffffffffffffffffffffffffffffffffffffc7634d81f4372ddf581a0db248b0a77aecec196accc52972", "aa87ca22be8b05378eb1c71ef320ad746e1d3b628ba79b9859f741e082542a385502f25dbf55296c3a545e3872760ab7", "c9e821b569d9d390a26167406d6d23d6070be242d765eb831625ceec4a0f473ef59f4e30e2817e6285bce2846f15f1a0"),
+                ("9584375618334edc57548d5f4e620f38f49b20846c9025f8108797d6f2e7351df45ed8c55d5cb4226399227ae1d6f9f6", "53fcddfa040ca3aa9837b1b070f514b0380ce4669a6fd6cfd7c4a8f902b60e73b624675b6482b14c2ba278e34467ebd1", "c7d6f787676683e24ab5de2991fe7ec49a8d66d6f97d1baaba32b70201d880c76fdea66831a95a44be48049532df6f7a"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_secp256k1_scalar_mult() {
+        check_vectors(
+            &Curve::secp256k1(),
+            &[
+                (
+                    "2",
+                    "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5",
+                    "1ae168fea63dc339a3c58419466ceaeef7f632653266d0e1236431a950cfe52a",
+                ),
+                (
+                    "aa5e28d6a97a2479a65527f7290311a3624d4cc0fa1578598ee3c2613bf99522",
+                    "34f9460f0e4f08393d192b3c5133a6ba099aa0ad9fd54ebccfacdfa239ff49c6",
+                    "0b71ea9bd730fd8923f6d25a7a91e7dd7728a960686cb5a901bb419e0f2ca232",
+                ),
+                (
+                    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364140",
+                    "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798",
+                    "b7c52588d95c3b9aa25b0403f1eef75702e84bb7597aabe663b82f6f04ef2777",
+                ),
+                (
+                    "5dcc39d710f48bb91a004483b96ba5cbc12776e46dd451b26bcefab3a3b48c4a",
+                    "838703abf205df7d270f9be169bbb08e30514ccb892deaf9ec59e0e4acbe036c",
+                    "f630f007d09de6e2809c7ab5e8d0a1cd52420512f1ee39c5027158847f3330d0",
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_glv() {
+        let curve = Curve::secp256k1();
+        let glv = curve.glv().unwrap();
+        let g = curve.generator().to_projective();
+        // phi(G) = lambda G.
+        let phi = ProjectivePoint {
+            curve: curve.clone(),
+            x: &g.x * glv.beta(),
+            y: g.y.clone(),
+            z: g.z.clone(),
+        };
+        assert_eq!(phi, g.mul(glv.lambda()));
+
+        let n = curve.order();
+        let mut half = n.clone();
+        half >>= 1;
+        for _ in 0..20 {
+            let k = &BigInt::random(256) % n;
+            let ((k1, _), (k2, _)) = glv.decompose(&k, n);
+            assert!(k1.bit_length() <= 129 && k2.bit_length() <= 129);
+            assert_eq!(g.mul_glv(&k).unwrap(), g.mul(&k));
+        }
+        for k in [BigInt::from_u64(0), BigInt::from_u64(1), half, n.clone()] {
+            assert_eq!(g.mul_glv(&k).unwrap(), g.mul(&k));
+        }
+        assert!(Curve::p256()
+            .generator()
+            .to_projective()
+            .mul_glv(&BigInt::from_u64(3))
+            .is_none());
+    }
+
+    #[test]
+    fn test_group_laws() {
+        let curve = Curve::p256();
+        let g = curve.generator().to_projective();
+        let o = ProjectivePoint::identity(&curve);
+        assert_eq!(g.add(&o), g);
+        assert_eq!(o.add(&g), g);
+        assert!(o.double().is_identity());
+        assert!(g.add(&g.neg()).is_identity());
+        assert!(g.mul(curve.order()).is_identity());
+        assert!(g.mul(&BigInt::from_u64(0)).is_identity());
+        assert_eq!(g.double().add(&g), g.mul(&BigInt::from_u64(3)));
+        assert!(o.to_affine().is_identity());
+    }
+
+    #[test]
+    fn test_sec1_encoding() {
+        let curve = Curve::p256();
+        let g = curve.generator();
+        let uncompressed = g.to_sec1(false);
+        assert_eq!(uncompressed.len(), 65);
+        assert_eq!(AffinePoint::from_sec1(&curve, &uncompressed), Ok(g.clone()));
+        assert_eq!(
+            hex::encode(&g.to_sec1(true)),
+            "036b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296"
+        );
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &g.to_sec1(true)),
+            Ok(g.clone())
+        );
+
+        // -G has the same x and the other parity.
+        let neg = g.to_projective().neg().to_affine();
+        assert_eq!(neg.to_sec1(true)[0], 0x02);
+        assert_eq!(AffinePoint::from_sec1(&curve, &neg.to_sec1(true)), Ok(neg));
+
+        let p384 = Curve::p384();
+        let h = p384
+            .generator()
+            .to_projective()
+            .mul(&BigInt::from_u64(5))
+            .to_affine();
+        assert_eq!(AffinePoint::from_sec1(&p384, &h.to_sec1(true)), Ok(h));
+    }
+
+    #[test]
+    fn test_sec1_validation() {
+        let curve = Curve::p256();
+        let mut bad = curve.generator().to_sec1(false);
+        *bad.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &bad),
+            Err(EcError::NotOnCurve)
+        );
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &[0x00]),
+            Err(EcError::Identity)
+        );
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &bad[..64]),
+            Err(EcError::InvalidEncoding)
+        );
+        bad[0] = 0x05;
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &bad),
+            Err(EcError::InvalidEncoding)
+        );
+
+        // A coordinate equal to p is not a field element.
+        let mut big = vec![0x04];
+        big.extend(curve.field().modulus().to_bytes_be());
+        big.extend(vec![0u8; 32]);
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &big),
+            Err(EcError::InvalidEncoding)
+        );
+
+        // x = 1 gives y^2 = b - 2, which is not a square on P-256.
+        let mut no_root = vec![0x02];
+        no_root.extend(vec![0u8; 31]);
+        no_root.push(1);
+        assert_eq!(
+            AffinePoint::from_sec1(&curve, &no_root),
+            Err(EcError::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_subgroup_check() {
+        // y^2 = x^3 + x + 3 over GF(101) has 87 = 3 * 29 points. (4, 24)
+        // generates the order-29 subgroup; (1, 45) has order 87.
+        let field = PrimeField::new(BigInt::from_u64(101));
+        let curve = Curve::new(
+            "toy",
+            field.clone(),
+            BigInt::from_u64(1),
+            BigInt::from_u64(3),
+            BigInt::from_u64(4),
+            BigInt::from_u64(24),
+            BigInt::from_u64(29),
+            3,
+        );
+        let element = |v| field.element(&BigInt::from_u64(v));
+        let g = AffinePoint::new(&curve, element(4), element(24)).unwrap();
+        assert_eq!(g, curve.generator());
+        assert_eq!(
+            AffinePoint::new(&curve, element(1), element(45)),
+            Err(EcError::NotOnCurve)
+        );
+        // 3 (1, 45) has order 29 and passes.
+        let t = AffinePoint {
+            curve: curve.clone(),
+            coords: Some((element(1), element(45))),
+        };
+        let t3 = t
+            .to_projective()
+            .mul_unreduced(&BigInt::from_u64(3))
+            .to_affine();
+        let (x, y) = t3.coords.clone().unwrap();
+        assert_eq!(AffinePoint::new(&curve, x, y), Ok(t3));
+    }
+}
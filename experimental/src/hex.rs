@@ -0,0 +1,37 @@
+/// Lower-case hex rendering of a byte string (digests, fingerprints, dumps).
+pub fn encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Parses a hex string (either case, no separators). Returns `None` on odd
+/// length or a non-hex character.
+pub fn decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    // Not u8::from_str_radix, which accepts a leading '+'.
+    let nibble = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(decode("00fF7a"), Some(vec![0x00, 0xff, 0x7a]));
+        assert_eq!(encode(&[0x00, 0xff, 0x7a]), "00ff7a");
+        assert_eq!(decode(""), Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_rejects_non_hex() {
+        for bad in ["abc", "zz", "+f", "-f", " f", "0x", "\u{e9}"] {
+            assert_eq!(decode(bad), None, "{:?}", bad);
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::ct::ct_eq;
+use crate::digest::Digest;
+
+/// HMAC (RFC 2104) over any `Digest`.
+#[derive(Clone)]
+pub struct Hmac<D: Digest> {
+    inner: D,
+    outer: D,
+}
+
+impl<D: Digest> Hmac<D> {
+    pub fn new(key: &[u8]) -> Self {
+        // Keys longer than the block are hashed first; shorter ones are zero padded.
+        let mut block = if key.len() > D::BLOCK_LEN {
+            D::digest(key)
+        } else {
+            key.to_vec()
+        };
+        block.resize(D::BLOCK_LEN, 0);
+
+        let mut inner = D::new();
+        inner.update(&block.iter().map(|b| b ^ 0x36).collect::<Vec<u8>>());
+        let mut outer = D::new();
+        outer.update(&block.iter().map(|b| b ^ 0x5c).collect::<Vec<u8>>());
+        Self { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> Vec<u8> {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+
+    /// Checks the MAC against `tag` in constant time.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        ct_eq(&self.finalize(), tag)
+    }
+
+    /// One-shot convenience: HMAC of `data` under `key`.
+    pub fn mac(key: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut h = Self::new(key);
+        h.update(data);
+        h.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+    use crate::sha1::Sha1;
+    use crate::sha2::{Sha224, Sha256, Sha384, Sha512};
+
+    struct Case {
+        key: Vec<u8>,
+        data: Vec<u8>,
+        sha224: &'static str,
+        sha256: &'static str,
+        sha384: &'static str,
+        sha512: &'static str,
+    }
+
+    /// RFC 4231 test cases 1-4, 6 and 7 (case 5 tests truncation only).
+    fn rfc4231_cases() -> Vec<Case> {
+        vec![
+            // Test case 1
+            Case {
+                key: vec![0x0b; 20],
+                data: b"Hi There".to_vec(),
+                sha224: "896fb1128abbdf196832107cd49df33f47b4b1169912ba4f53684b22",
+                sha256: "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                sha384: "afd03944d84895626b0825f4ab46907f15f9dadbe4101ec682aa034c7cebc59cfaea9ea9076ede7f4af152e8b2fa9cb6",
+                sha512: "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            },
+            // Test case 2
+            Case {
+                key: b"Jefe".to_vec(),
+                data: b"what do ya want for nothing?".to_vec(),
+                sha224: "a30e01098bc6dbbf45690f3a7e9e6d0f8bbea2a39e6148008fd05e44",
+                sha256: "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                sha384: "af45d2e376484031617f78d2b58a6b1b9c7ef464f5a01b47e42ec3736322445e8e2240ca5e69e2c78b3239ecfab21649",
+                sha512: "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            },
+            // Test case 3
+            Case {
+                key: vec![0xaa; 20],
+                data: vec![0xdd; 50],
+                sha224: "7fb3cb3588c6c1f6ffa9694d7d6ad2649365b0c1f65d69d1ec8333ea",
+                sha256: "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+                sha384: "88062608d3e6ad8a0aa2ace014c8a86f0aa635d947ac9febe83ef4e55966144b2a5ab39dc13814b94e3ab6e101a34f27",
+                sha512: "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+            },
+            // Test case 4
+            Case {
+                key: (1..=25).collect(),
+                data: vec![0xcd; 50],
+                sha224: "6c11506874013cac6a2abc1bb382627cec6a90d86efc012de7afec5a",
+                sha256: "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                sha384: "3e8a69b7783c25851933ab6290af6ca77a9981480850009cc5577c6e1f573b4e6801dd23c4a7d679ccf8a386c674cffb",
+                sha512: "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            },
+            // Test case 6
+            Case {
+                key: vec![0xaa; 131],
+                data: b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                sha224: "95e9a0db962095adaebe9b2d6f0dbce2d499f112f2d2b7273fa6870e",
+                sha256: "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                sha384: "4ece084485813e9088d2c63a041bc5b44f9ef1012a2b588f3cd11f05033ac4c60c2ef6ab4030fe8296248df163f44952",
+                sha512: "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            },
+            // Test case 7
+            Case {
+                key: vec![0xaa; 131],
+                data: b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.".to_vec(),
+                sha224: "3a854166ac5d9f023f54d517d0b39dbd946770db9c2b95c9f6f565d1",
+                sha256: "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+                sha384: "6617178e941f020d351e2f254e8fd32c602420feb0b8fb9adccebb82461e99c5a678cc31e799176d3860e6110c46523e",
+                sha512: "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+            },
+        ]
+    }
+
+    #[test]
+    fn test_rfc4231() {
+        for case in rfc4231_cases() {
+            assert_eq!(
+                hex::encode(&Hmac::<Sha224>::mac(&case.key, &case.data)),
+                case.sha224
+            );
+            assert_eq!(
+                hex::encode(&Hmac::<Sha256>::mac(&case.key, &case.data)),
+                case.sha256
+            );
+            assert_eq!(
+                hex::encode(&Hmac::<Sha384>::mac(&case.key, &case.data)),
+                case.sha384
+            );
+            assert_eq!(
+                hex::encode(&Hmac::<Sha512>::mac(&case.key, &case.data)),
+                case.sha512
+            );
+        }
+    }
+
+    #[test]
+    fn test_hmac_sha1() {
+        // RFC 2202 test case 2.
+        assert_eq!(
+            hex::encode(&Hmac::<Sha1>::mac(b"Jefe", b"what do ya want for nothing?")),
+            "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79"
+        );
+    }
+
+    #[test]
+    fn test_streaming_and_verify() {
+        let mut h = Hmac::<Sha256>::new(b"Jefe");
+        h.update(b"what do ya ");
+        h.update(b"want for nothing?");
+        let tag = Hmac::<Sha256>::mac(b"Jefe", b"what do ya want for nothing?");
+        assert!(h.clone().verify(&tag));
+
+        let mut bad = tag.clone();
+        bad[31] ^= 1;
+        assert!(!h.clone().verify(&bad));
+        assert!(!h.verify(&tag[..16]));
+    }
+}
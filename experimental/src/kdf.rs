@@ -0,0 +1,340 @@
+use crate::blowfish::Blowfish;
+use crate::digest::Digest;
+use crate::hmac::Hmac;
+use crate::sha2::{Sha256, Sha512};
+
+/// HKDF-Extract (RFC 5869 section 2.2). An empty salt is treated as a
+/// string of `OUTPUT_LEN` zero bytes.
+pub fn hkdf_extract<D: Digest>(salt: &[u8], ikm: &[u8]) -> Vec<u8> {
+    let zeros = vec![0u8; D::OUTPUT_LEN];
+    let salt = if salt.is_empty() { &zeros[..] } else { salt };
+    Hmac::<D>::mac(salt, ikm)
+}
+
+/// HKDF-Expand (RFC 5869 section 2.3).
+///
+/// Panics if `len` exceeds `255 * OUTPUT_LEN`, the most HKDF can produce.
+pub fn hkdf_expand<D: Digest>(prk: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    assert!(
+        len <= 255 * D::OUTPUT_LEN,
+        "HKDF output length {} exceeds 255 * HashLen",
+        len
+    );
+    let mut okm = Vec::with_capacity(len);
+    let mut t: Vec<u8> = Vec::new();
+    let mut counter = 1u8;
+    while okm.len() < len {
+        let mut h = Hmac::<D>::new(prk);
+        h.update(&t);
+        h.update(info);
+        h.update(&[counter]);
+        t = h.finalize();
+        okm.extend_from_slice(&t);
+        counter = counter.wrapping_add(1);
+    }
+    okm.truncate(len);
+    okm
+}
+
+/// PBKDF2 (RFC 8018 section 5.2) with HMAC-`D` as the PRF.
+pub fn pbkdf2_hmac<D: Digest>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    len: usize,
+) -> Vec<u8> {
+    assert!(iterations > 0, "PBKDF2 needs at least one iteration");
+    let prf = Hmac::<D>::new(password);
+    let mut dk = Vec::with_capacity(len);
+    let mut block_index = 1u32;
+    while dk.len() < len {
+        let mut h = prf.clone();
+        h.update(salt);
+        h.update(&block_index.to_be_bytes());
+        let mut u = h.finalize();
+        let mut t = u.clone();
+        for _ in 1..iterations {
+            let mut h = prf.clone();
+            h.update(&u);
+            u = h.finalize();
+            for (ti, ui) in t.iter_mut().zip(&u) {
+                *ti ^= ui;
+            }
+        }
+        dk.extend_from_slice(&t);
+        block_index += 1;
+    }
+    dk.truncate(len);
+    dk
+}
+
+/// scrypt (RFC 7914) with cost N = 2^`log_n`, block size `r` and
+/// parallelization `p`. Uses 128 * r * (N + p) bytes of memory and time
+/// proportional to N * r * p.
+///
+/// Panics unless `log_n` is 1 to 63 and `r` and `p` are non-zero.
+pub fn scrypt(password: &[u8], salt: &[u8], log_n: u8, r: u32, p: u32, len: usize) -> Vec<u8> {
+    assert!(
+        (1..64).contains(&log_n) && r > 0 && p > 0,
+        "invalid scrypt parameters"
+    );
+    let block_len = 128 * r as usize;
+    let mut b = pbkdf2_hmac::<Sha256>(password, salt, 1, p as usize * block_len);
+    for chunk in b.chunks_mut(block_len) {
+        let mut x: Vec<u32> = chunk
+            .chunks(4)
+            .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+            .collect();
+        ro_mix(&mut x, 1 << log_n);
+        for (out, w) in chunk.chunks_mut(4).zip(&x) {
+            out.copy_from_slice(&w.to_le_bytes());
+        }
+    }
+    pbkdf2_hmac::<Sha256>(password, &b, 1, len)
+}
+
+/// scryptROMix (RFC 7914 section 5) on one 128 * r byte block as words.
+fn ro_mix(x: &mut [u32], n: u64) {
+    let mut v = Vec::with_capacity(x.len() * n as usize);
+    for _ in 0..n {
+        v.extend_from_slice(x);
+        block_mix(x);
+    }
+    for _ in 0..n {
+        // Integerify: the first word of the last 64-byte block.
+        let last = x.len() - 16;
+        let j = (u64::from(x[last]) | u64::from(x[last + 1]) << 32) & (n - 1);
+        let j = j as usize * x.len();
+        for (xi, vi) in x.iter_mut().zip(&v[j..]) {
+            *xi ^= vi;
+        }
+        block_mix(x);
+    }
+}
+
+/// scryptBlockMix (RFC 7914 section 4) with Salsa20/8.
+fn block_mix(b: &mut [u32]) {
+    let blocks = b.len() / 16;
+    let mut x: [u32; 16] = b[b.len() - 16..].try_into().unwrap();
+    let mut y = vec![0u32; b.len()];
+    for i in 0..blocks {
+        for (xk, bk) in x.iter_mut().zip(&b[16 * i..]) {
+            *xk ^= bk;
+        }
+        salsa20_8(&mut x);
+        // Even blocks go to the first half of the output, odd to the second.
+        let dest = (i / 2 + (i % 2) * blocks / 2) * 16;
+        y[dest..dest + 16].copy_from_slice(&x);
+    }
+    b.copy_from_slice(&y);
+}
+
+fn salsa20_8(block: &mut [u32; 16]) {
+    let mut x = *block;
+    for _ in 0..4 {
+        // Four column quarter-rounds, then four row quarter-rounds.
+        for [a, b, c, d] in [
+            [0, 4, 8, 12],
+            [5, 9, 13, 1],
+            [10, 14, 2, 6],
+            [15, 3, 7, 11],
+            [0, 1, 2, 3],
+            [5, 6, 7, 4],
+            [10, 11, 8, 9],
+            [15, 12, 13, 14],
+        ] {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+    }
+    for (bi, xi) in block.iter_mut().zip(&x) {
+        *bi = bi.wrapping_add(*xi);
+    }
+}
+
+/// bcrypt-pbkdf, the OpenBSD KDF behind encrypted OpenSSH private keys:
+/// PBKDF2's structure around a bcrypt-based hash, with the output of each
+/// block spread across the key at intervals of the block count.
+///
+/// Panics if `rounds` is zero or `len` is 0 or above 1024.
+pub fn bcrypt_pbkdf(password: &[u8], salt: &[u8], rounds: u32, len: usize) -> Vec<u8> {
+    assert!(rounds > 0, "bcrypt-pbkdf needs at least one round");
+    assert!((1..=32 * 32).contains(&len), "bcrypt-pbkdf output length");
+    let stride = len.div_ceil(32);
+    let sha2pass = Sha512::digest(password);
+    let mut key = vec![0u8; len];
+    for block in 0..stride {
+        let mut count_salt = salt.to_vec();
+        count_salt.extend_from_slice(&(block as u32 + 1).to_be_bytes());
+        let mut t = bcrypt_hash(&sha2pass, &Sha512::digest(&count_salt));
+        let mut out = t;
+        for _ in 1..rounds {
+            t = bcrypt_hash(&sha2pass, &Sha512::digest(&t));
+            for (o, ti) in out.iter_mut().zip(&t) {
+                *o ^= ti;
+            }
+        }
+        for (dest, &byte) in key.iter_mut().skip(block).step_by(stride).zip(&out) {
+            *dest = byte;
+        }
+    }
+    key
+}
+
+/// The bcrypt core with SHA-512 digests as key and salt, encrypting
+/// "OxychromaticBlowfishSwatDynamite" instead of bcrypt's own string.
+fn bcrypt_hash(sha2pass: &[u8], sha2salt: &[u8]) -> [u8; 32] {
+    let mut state = Blowfish::initial();
+    state.expand(sha2salt, sha2pass);
+    for _ in 0..64 {
+        state.expand(&[0], sha2salt);
+        state.expand(&[0], sha2pass);
+    }
+    let mut words: Vec<u32> = b"OxychromaticBlowfishSwatDynamite"
+        .chunks(4)
+        .map(|w| u32::from_be_bytes(w.try_into().unwrap()))
+        .collect();
+    for _ in 0..64 {
+        for pair in words.chunks_mut(2) {
+            (pair[0], pair[1]) = state.encipher(pair[0], pair[1]);
+        }
+    }
+    // The words come out little-endian.
+    let mut out = [0u8; 32];
+    for (chunk, word) in out.chunks_mut(4).zip(&words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+    use crate::sha1::Sha1;
+
+    #[test]
+    fn test_rfc5869_case1() {
+        let ikm = [0x0bu8; 22];
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let prk = hkdf_extract::<Sha256>(&salt, &ikm);
+        assert_eq!(
+            hex::encode(&prk),
+            "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"
+        );
+        assert_eq!(
+            hex::encode(&hkdf_expand::<Sha256>(&prk, &info, 42)),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn test_rfc5869_case2() {
+        let ikm: Vec<u8> = (0x00..=0x4f).collect();
+        let salt: Vec<u8> = (0x60..=0xaf).collect();
+        let info: Vec<u8> = (0xb0..=0xff).collect();
+        let prk = hkdf_extract::<Sha256>(&salt, &ikm);
+        assert_eq!(
+            hex::encode(&prk),
+            "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244"
+        );
+        assert_eq!(
+            hex::encode(&hkdf_expand::<Sha256>(&prk, &info, 82)),
+            "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87"
+        );
+    }
+
+    #[test]
+    fn test_rfc5869_case3_empty_salt_and_info() {
+        let prk = hkdf_extract::<Sha256>(&[], &[0x0bu8; 22]);
+        assert_eq!(
+            hex::encode(&prk),
+            "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04"
+        );
+        assert_eq!(
+            hex::encode(&hkdf_expand::<Sha256>(&prk, &[], 42)),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn test_rfc5869_case4_sha1() {
+        let salt: Vec<u8> = (0x00..=0x0c).collect();
+        let info: Vec<u8> = (0xf0..=0xf9).collect();
+        let prk = hkdf_extract::<Sha1>(&salt, &[0x0bu8; 11]);
+        assert_eq!(
+            hex::encode(&prk),
+            "9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243"
+        );
+        assert_eq!(
+            hex::encode(&hkdf_expand::<Sha1>(&prk, &info, 42)),
+            "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2c22e422478d305f3f896"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds 255 * HashLen")]
+    fn test_hkdf_expand_too_long() {
+        hkdf_expand::<Sha256>(&[0u8; 32], &[], 255 * 32 + 1);
+    }
+
+    #[test]
+    fn test_rfc6070() {
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha1>(b"password", b"salt", 1, 20)),
+            "0c60c80f961f0e71f3a9b524af6012062fe037a6"
+        );
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha1>(b"password", b"salt", 2, 20)),
+            "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"
+        );
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha1>(b"password", b"salt", 4096, 20)),
+            "4b007901b765489abead49d926f721d065a429c1"
+        );
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha1>(
+                b"passwordPASSWORDpassword",
+                b"saltSALTsaltSALTsaltSALTsaltSALTsalt",
+                4096,
+                25
+            )),
+            "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"
+        );
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha1>(b"pass\0word", b"sa\0lt", 4096, 16)),
+            "56fa6aa75548099dcc37d7f03425e0c3"
+        );
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_multi_block() {
+        assert_eq!(
+            hex::encode(&pbkdf2_hmac::<Sha256>(b"password", b"salt", 2, 40)),
+            "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43830651afcb5c862f"
+        );
+    }
+
+    #[test]
+    fn test_rfc7914_scrypt() {
+        assert_eq!(
+            hex::encode(&scrypt(b"", b"", 4, 1, 1, 64)),
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906"
+        );
+        assert_eq!(
+            hex::encode(&scrypt(b"password", b"NaCl", 10, 8, 16, 64)),
+            "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b3731622eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640"
+        );
+    }
+
+    #[test]
+    fn test_bcrypt_pbkdf() {
+        assert_eq!(
+            hex::encode(&bcrypt_pbkdf(b"password", b"salt", 4, 32)),
+            "5bbf0cc293587f1c3635555c27796598d47e579071bf427e9d8fbe842aba34d9"
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use experimental::curve25519::ed25519::Ed25519PrivateKey;
+use experimental::ec::curve::Curve;
+use experimental::ec::ecdsa::EcdsaPrivateKey;
+use experimental::pkcs8::PrivateKey;
+use experimental::rsa::RsaPrivateKey;
+use experimental::ssh::SshPrivateKey;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+
+fn arg<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter()
+        .find_map(|arg| arg.strip_prefix(name)?.strip_prefix('='))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let key_type = arg(&args, "--type").unwrap_or("rsa");
+    let bits: Option<usize> = arg(&args, "--bits").and_then(|s| s.parse().ok());
+    let comment = arg(&args, "--comment").unwrap_or("");
+    let out = arg(&args, "--out").unwrap_or("/tmp/.ssh/test_key");
+
+    let mut rng = rand::thread_rng();
+    let key = match (key_type, bits) {
+        ("rsa", bits) => PrivateKey::Rsa(RsaPrivateKey::generate(bits.unwrap_or(3072), &mut rng)),
+        ("ed25519", _) => PrivateKey::Ed25519(Ed25519PrivateKey::generate(&mut rng)),
+        ("ecdsa", None | Some(256)) => {
+            PrivateKey::Ec(EcdsaPrivateKey::generate(&Curve::p256(), &mut rng))
+        }
+        ("ecdsa", Some(384)) => PrivateKey::Ec(EcdsaPrivateKey::generate(&Curve::p384(), &mut rng)),
+        _ => {
+            eprintln!("Unsupported key type {} with {:?} bits", key_type, bits);
+            std::process::exit(1);
+        }
+    };
+    let key = SshPrivateKey::new(key, comment).unwrap();
+
+    let path = Path::new(out);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    // ssh-keygen refuses private keys others can read. The mode only
+    // applies to a new file, so tighten an existing one before writing.
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600)).unwrap();
+        }
+    }
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(key.to_openssh().as_bytes()))
+        .unwrap();
+    fs::write(format!("{}.pub", out), key.public_key().to_openssh()).unwrap();
+    println!("Wrote {} and {}.pub", out, out);
+}
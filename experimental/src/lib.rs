@@ -0,0 +1,24 @@
+pub mod aes;
+pub mod asn1;
+pub mod base64;
+pub mod bigint;
+pub mod blowfish;
+pub mod ct;
+pub mod curve25519;
+pub mod dh;
+pub mod digest;
+pub mod drbg;
+pub mod dsa;
+pub mod ec;
+pub mod hex;
+pub mod hmac;
+pub mod kdf;
+pub mod pem;
+pub mod pkcs5;
+pub mod pkcs8;
+pub mod prime;
+pub mod rsa;
+pub mod sha1;
+pub mod sha2;
+pub mod sha3;
+pub mod ssh;
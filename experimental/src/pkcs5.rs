@@ -0,0 +1,587 @@
+//! Password-based encryption: PBES2 (RFC 8018 section 6.2) with PBKDF2 or
+//! scrypt (RFC 7914 section 7) as the key derivation and AES-CBC, the
+//! combination OpenSSL writes for "ENCRYPTED PRIVATE KEY", or AES-GCM
+//! (RFC 5084 section 3.2) as the cipher.
+
+use crate::aes::gcm::Gcm;
+use crate::aes::{cbc, Aes, BLOCK_LEN};
+use crate::asn1::reader::Tlv;
+use crate::asn1::{oid, Asn1Error, DerReader, DerWriter, ObjectIdentifier, Tag};
+use crate::digest::HashAlgorithm;
+use crate::kdf::{pbkdf2_hmac, scrypt};
+use crate::sha1::Sha1;
+use crate::sha2::{Sha256, Sha384, Sha512};
+use rand::RngCore;
+
+/// The most memory (128 * r * (N + p) bytes) scrypt parameters may ask
+/// for when decrypting, so a hostile file cannot exhaust memory. OpenSSL's
+/// default limit is 32 MiB.
+pub const MAX_SCRYPT_MEMORY: u64 = 256 << 20;
+
+/// The most work (N * r * p) scrypt parameters may ask for when
+/// decrypting: 128 times OpenSSL's default of N = 2^14, r = 8, p = 1.
+pub const MAX_SCRYPT_WORK: u64 = 1 << 24;
+
+const SALT_LEN: usize = 16;
+
+/// The `aes-ICVlen` DEFAULT in `GCMParameters`.
+const GCM_DEFAULT_TAG_LEN: usize = 12;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Pkcs5Error {
+    /// Malformed parameters.
+    InvalidEncoding,
+    /// A scheme, key derivation, PRF or cipher not implemented here.
+    UnsupportedAlgorithm(ObjectIdentifier),
+    /// Parameters out of range: no iterations, a keyLength that does not
+    /// fit the cipher, a GCM tag length outside 12 to 16, or scrypt costs
+    /// beyond `MAX_SCRYPT_MEMORY` or `MAX_SCRYPT_WORK`.
+    InvalidParameters,
+    /// The padding or GCM tag was wrong after decryption, almost always
+    /// because the password was.
+    Decryption,
+}
+
+impl From<Asn1Error> for Pkcs5Error {
+    fn from(_: Asn1Error) -> Self {
+        Pkcs5Error::InvalidEncoding
+    }
+}
+
+/// The key derivation to use when encrypting.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// PBKDF2 with HMAC-SHA-256.
+    Pbkdf2 { iterations: u32 },
+    /// scrypt with cost N = 2^`log_n`.
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum KdfParams {
+    Pbkdf2 {
+        salt: Vec<u8>,
+        iterations: u32,
+        prf: HashAlgorithm,
+    },
+    Scrypt {
+        salt: Vec<u8>,
+        log_n: u8,
+        r: u32,
+        p: u32,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CipherParams {
+    Cbc {
+        iv: [u8; BLOCK_LEN],
+    },
+    /// The tag, `tag_len` bytes, follows the ciphertext.
+    Gcm {
+        nonce: Vec<u8>,
+        tag_len: usize,
+    },
+}
+
+/// PBES2 parameters: a key derivation with its salt, and an AES key size
+/// with the CBC IV or GCM nonce.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pbes2 {
+    kdf: KdfParams,
+    key_len: usize,
+    cipher: CipherParams,
+}
+
+impl Pbes2 {
+    /// Parameters for AES-256-CBC with a fresh random salt and IV.
+    pub fn new<G: RngCore + ?Sized>(kdf: Kdf, rng: &mut G) -> Self {
+        let mut salt = vec![0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let mut iv = [0u8; BLOCK_LEN];
+        rng.fill_bytes(&mut iv);
+        let kdf = match kdf {
+            Kdf::Pbkdf2 { iterations } => KdfParams::Pbkdf2 {
+                salt,
+                iterations,
+                prf: HashAlgorithm::Sha256,
+            },
+            Kdf::Scrypt { log_n, r, p } => KdfParams::Scrypt { salt, log_n, r, p },
+        };
+        Self {
+            kdf,
+            key_len: 32,
+            cipher: CipherParams::Cbc { iv },
+        }
+    }
+
+    /// Parses an AlgorithmIdentifier naming PBES2, such as the
+    /// encryptionAlgorithm of an `EncryptedPrivateKeyInfo`.
+    pub fn from_der(der: &[u8]) -> Result<Self, Pkcs5Error> {
+        let (scheme, params) = DerReader::parse(der, |r| r.algorithm_identifier())?;
+        if scheme != oid::PBES2 {
+            return Err(Pkcs5Error::UnsupportedAlgorithm(scheme));
+        }
+        let params = params.ok_or(Pkcs5Error::InvalidEncoding)?;
+        let (kdf, cipher) = DerReader::parse(params.encoded, |r| {
+            r.sequence(|r| Ok((r.algorithm_identifier()?, r.algorithm_identifier()?)))
+        })?;
+
+        let (key_len, cipher) = read_cipher(cipher)?;
+        let (kdf, kdf_key_len) = read_kdf(kdf)?;
+        if kdf_key_len.is_some_and(|len| len != key_len) {
+            return Err(Pkcs5Error::InvalidParameters);
+        }
+        Ok(Self {
+            kdf,
+            key_len,
+            cipher,
+        })
+    }
+
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.oid(&oid::PBES2);
+            w.sequence(|w| {
+                w.sequence(|w| match &self.kdf {
+                    KdfParams::Pbkdf2 {
+                        salt,
+                        iterations,
+                        prf,
+                    } => {
+                        w.oid(&oid::PBKDF2);
+                        w.sequence(|w| {
+                            w.octet_string(salt);
+                            w.integer_i64(i64::from(*iterations));
+                            // The DEFAULT is left out, as DER requires.
+                            if *prf != HashAlgorithm::Sha1 {
+                                w.sequence(|w| {
+                                    w.oid(&prf_oid(*prf));
+                                    w.null();
+                                });
+                            }
+                        });
+                    }
+                    KdfParams::Scrypt { salt, log_n, r, p } => {
+                        w.oid(&oid::SCRYPT);
+                        w.sequence(|w| {
+                            w.octet_string(salt);
+                            w.integer_i64(1 << log_n);
+                            w.integer_i64(i64::from(*r));
+                            w.integer_i64(i64::from(*p));
+                        });
+                    }
+                });
+                w.sequence(|w| match &self.cipher {
+                    CipherParams::Cbc { iv } => {
+                        w.oid(&match self.key_len {
+                            16 => oid::AES128_CBC,
+                            24 => oid::AES192_CBC,
+                            _ => oid::AES256_CBC,
+                        });
+                        w.octet_string(iv);
+                    }
+                    CipherParams::Gcm { nonce, tag_len } => {
+                        w.oid(&match self.key_len {
+                            16 => oid::AES128_GCM,
+                            24 => oid::AES192_GCM,
+                            _ => oid::AES256_GCM,
+                        });
+                        w.sequence(|w| {
+                            w.octet_string(nonce);
+                            if *tag_len != GCM_DEFAULT_TAG_LEN {
+                                w.integer_i64(*tag_len as i64);
+                            }
+                        });
+                    }
+                });
+            });
+        });
+        w.finish()
+    }
+
+    /// With GCM the tag follows the ciphertext, and there is no AAD.
+    pub fn encrypt(&self, password: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let key = self.key(password);
+        match &self.cipher {
+            CipherParams::Cbc { iv } => cbc::encrypt(&aes(&key), iv, plaintext),
+            CipherParams::Gcm { nonce, tag_len } => {
+                gcm(&key, *tag_len).encrypt(nonce, &[], plaintext)
+            }
+        }
+    }
+
+    pub fn decrypt(&self, password: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Pkcs5Error> {
+        let key = self.key(password);
+        match &self.cipher {
+            CipherParams::Cbc { iv } => cbc::decrypt(&aes(&key), iv, ciphertext),
+            CipherParams::Gcm { nonce, tag_len } => {
+                gcm(&key, *tag_len).decrypt(nonce, &[], ciphertext)
+            }
+        }
+        .map_err(|_| Pkcs5Error::Decryption)
+    }
+
+    fn key(&self, password: &[u8]) -> Vec<u8> {
+        let len = self.key_len;
+        match &self.kdf {
+            KdfParams::Pbkdf2 {
+                salt,
+                iterations,
+                prf,
+            } => match prf {
+                HashAlgorithm::Sha1 => pbkdf2_hmac::<Sha1>(password, salt, *iterations, len),
+                HashAlgorithm::Sha256 => pbkdf2_hmac::<Sha256>(password, salt, *iterations, len),
+                HashAlgorithm::Sha384 => pbkdf2_hmac::<Sha384>(password, salt, *iterations, len),
+                _ => pbkdf2_hmac::<Sha512>(password, salt, *iterations, len),
+            },
+            KdfParams::Scrypt { salt, log_n, r, p } => scrypt(password, salt, *log_n, *r, *p, len),
+        }
+    }
+}
+
+fn aes(key: &[u8]) -> Aes {
+    Aes::new(key).expect("key length comes from the cipher OID")
+}
+
+fn gcm(key: &[u8], tag_len: usize) -> Gcm {
+    Gcm::with_tag_len(key, tag_len).expect("key and tag lengths checked when parsed")
+}
+
+/// `PBKDF2-params` or `scrypt-params`, with the optional keyLength.
+fn read_kdf(
+    (kdf, params): (ObjectIdentifier, Option<Tlv>),
+) -> Result<(KdfParams, Option<usize>), Pkcs5Error> {
+    let params = params.ok_or(Pkcs5Error::InvalidEncoding)?;
+    match kdf {
+        oid::PBKDF2 => {
+            let (salt, iterations, key_len, prf) = DerReader::parse(params.encoded, |r| {
+                r.sequence(|r| {
+                    // The otherSource CHOICE for the salt is not supported.
+                    let salt = r.octet_string()?;
+                    let iterations = r.integer_i64()?;
+                    let key_len = match r.peek_tag()? {
+                        Some(Tag::INTEGER) => Some(r.integer_i64()?),
+                        _ => None,
+                    };
+                    let prf = if r.is_empty() {
+                        None
+                    } else {
+                        Some(r.algorithm_identifier()?)
+                    };
+                    Ok((salt, iterations, key_len, prf))
+                })
+            })?;
+            let prf = match prf {
+                None => HashAlgorithm::Sha1,
+                Some((prf, params)) => {
+                    if params.is_some_and(|p| p.tag != Tag::NULL || !p.contents.is_empty()) {
+                        return Err(Pkcs5Error::InvalidEncoding);
+                    }
+                    [
+                        HashAlgorithm::Sha1,
+                        HashAlgorithm::Sha256,
+                        HashAlgorithm::Sha384,
+                        HashAlgorithm::Sha512,
+                    ]
+                    .into_iter()
+                    .find(|&hash| prf_oid(hash) == prf)
+                    .ok_or(Pkcs5Error::UnsupportedAlgorithm(prf))?
+                }
+            };
+            let iterations = u32::try_from(iterations)
+                .ok()
+                .filter(|&n| n > 0)
+                .ok_or(Pkcs5Error::InvalidParameters)?;
+            let kdf = KdfParams::Pbkdf2 {
+                salt: salt.to_vec(),
+                iterations,
+                prf,
+            };
+            Ok((kdf, key_len_param(key_len)?))
+        }
+        oid::SCRYPT => {
+            let (salt, n, r, p, key_len) = DerReader::parse(params.encoded, |r| {
+                r.sequence(|r| {
+                    let salt = r.octet_string()?;
+                    let n = r.integer_i64()?;
+                    let block_size = r.integer_i64()?;
+                    let p = r.integer_i64()?;
+                    let key_len = if r.is_empty() {
+                        None
+                    } else {
+                        Some(r.integer_i64()?)
+                    };
+                    Ok((salt, n, block_size, p, key_len))
+                })
+            })?;
+            // N must be a power of two above 1, and p * r below 2^30
+            // (RFC 7914 section 2).
+            if n < 2 || n.count_ones() != 1 || r < 1 || p < 1 || p.saturating_mul(r) >= 1 << 30 {
+                return Err(Pkcs5Error::InvalidParameters);
+            }
+            // V takes 128 * r * N bytes and B another 128 * r * p.
+            let (n, r, p) = (n as u64, r as u64, p as u64);
+            let memory = (128 * r).saturating_mul(n + p);
+            if memory > MAX_SCRYPT_MEMORY || n.saturating_mul(r * p) > MAX_SCRYPT_WORK {
+                return Err(Pkcs5Error::InvalidParameters);
+            }
+            let kdf = KdfParams::Scrypt {
+                salt: salt.to_vec(),
+                log_n: n.trailing_zeros() as u8,
+                r: r as u32,
+                p: p as u32,
+            };
+            Ok((kdf, key_len_param(key_len)?))
+        }
+        _ => Err(Pkcs5Error::UnsupportedAlgorithm(kdf)),
+    }
+}
+
+/// An AES-CBC or AES-GCM AlgorithmIdentifier: the key length, and the IV
+/// or the `GCMParameters` nonce and ICV (tag) length.
+fn read_cipher(
+    (cipher, params): (ObjectIdentifier, Option<Tlv>),
+) -> Result<(usize, CipherParams), Pkcs5Error> {
+    let (key_len, gcm) = match cipher {
+        oid::AES128_CBC => (16, false),
+        oid::AES192_CBC => (24, false),
+        oid::AES256_CBC => (32, false),
+        oid::AES128_GCM => (16, true),
+        oid::AES192_GCM => (24, true),
+        oid::AES256_GCM => (32, true),
+        _ => return Err(Pkcs5Error::UnsupportedAlgorithm(cipher)),
+    };
+    let params = params.ok_or(Pkcs5Error::InvalidEncoding)?;
+    if !gcm {
+        let iv = match params {
+            Tlv {
+                tag: Tag::OCTET_STRING,
+                contents,
+                ..
+            } => contents
+                .try_into()
+                .map_err(|_| Pkcs5Error::InvalidEncoding)?,
+            _ => return Err(Pkcs5Error::InvalidEncoding),
+        };
+        return Ok((key_len, CipherParams::Cbc { iv }));
+    }
+    let (nonce, tag_len) = DerReader::parse(params.encoded, |r| {
+        r.sequence(|r| {
+            let nonce = r.octet_string()?;
+            let tag_len = if r.is_empty() {
+                None
+            } else {
+                Some(r.integer_i64()?)
+            };
+            Ok((nonce, tag_len))
+        })
+    })?;
+    if nonce.is_empty() {
+        return Err(Pkcs5Error::InvalidEncoding);
+    }
+    // DER leaves out the DEFAULT.
+    let tag_len = match tag_len {
+        None => GCM_DEFAULT_TAG_LEN,
+        Some(12) => return Err(Pkcs5Error::InvalidEncoding),
+        Some(len @ 13..=16) => len as usize,
+        Some(_) => return Err(Pkcs5Error::InvalidParameters),
+    };
+    let cipher = CipherParams::Gcm {
+        nonce: nonce.to_vec(),
+        tag_len,
+    };
+    Ok((key_len, cipher))
+}
+
+fn key_len_param(key_len: Option<i64>) -> Result<Option<usize>, Pkcs5Error> {
+    key_len
+        .map(|len| usize::try_from(len).map_err(|_| Pkcs5Error::InvalidParameters))
+        .transpose()
+}
+
+fn prf_oid(hash: HashAlgorithm) -> ObjectIdentifier {
+    match hash {
+        HashAlgorithm::Sha1 => oid::HMAC_WITH_SHA1,
+        HashAlgorithm::Sha256 => oid::HMAC_WITH_SHA256,
+        HashAlgorithm::Sha384 => oid::HMAC_WITH_SHA384,
+        _ => oid::HMAC_WITH_SHA512,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = HmacDrbg::<Sha256>::new(b"pkcs5 test", b"", b"");
+        for kdf in [
+            Kdf::Pbkdf2 { iterations: 100 },
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 2,
+                p: 1,
+            },
+        ] {
+            let pbes2 = Pbes2::new(kdf, &mut rng);
+            assert_eq!(Pbes2::from_der(&pbes2.to_der()), Ok(pbes2.clone()));
+            let ciphertext = pbes2.encrypt(b"secret", b"attack at dawn");
+            assert_eq!(
+                pbes2.decrypt(b"secret", &ciphertext),
+                Ok(b"attack at dawn".to_vec())
+            );
+            // Wrong passwords almost always fail the padding check; this
+            // one happens to with this seed.
+            assert_eq!(
+                pbes2.decrypt(b"Secret", &ciphertext),
+                Err(Pkcs5Error::Decryption)
+            );
+        }
+    }
+
+    #[test]
+    fn test_gcm_parameters() {
+        let gcm = |f: &dyn Fn(&mut DerWriter)| {
+            let mut w = DerWriter::new();
+            w.sequence(|w| {
+                w.oid(&oid::PBES2);
+                w.sequence(|w| {
+                    w.sequence(|w| {
+                        w.oid(&oid::PBKDF2);
+                        w.sequence(|w| {
+                            w.octet_string(b"salt");
+                            w.integer_i64(1);
+                        });
+                    });
+                    w.sequence(|w| {
+                        w.oid(&oid::AES128_GCM);
+                        w.sequence(f);
+                    });
+                });
+            });
+            let der = w.finish();
+            Pbes2::from_der(&der).map(|pbes2| (pbes2, der))
+        };
+        for tag_len in [None, Some(13), Some(16)] {
+            let (pbes2, der) = gcm(&|w| {
+                w.octet_string(&[1; 12]);
+                if let Some(len) = tag_len {
+                    w.integer_i64(len);
+                }
+            })
+            .unwrap();
+            assert_eq!(pbes2.to_der(), der);
+            let sealed = pbes2.encrypt(b"secret", b"attack at dawn");
+            let len = tag_len.unwrap_or(12) as usize;
+            assert_eq!(sealed.len(), 14 + len);
+            assert_eq!(
+                pbes2.decrypt(b"secret", &sealed),
+                Ok(b"attack at dawn".to_vec())
+            );
+            assert_eq!(
+                pbes2.decrypt(b"secret", &sealed[..sealed.len() - 1]),
+                Err(Pkcs5Error::Decryption)
+            );
+        }
+        // An empty nonce, an explicit DEFAULT, and ICVs out of range.
+        assert_eq!(
+            gcm(&|w| w.octet_string(&[])).err(),
+            Some(Pkcs5Error::InvalidEncoding)
+        );
+        for (tag_len, err) in [
+            (12, Pkcs5Error::InvalidEncoding),
+            (11, Pkcs5Error::InvalidParameters),
+            (17, Pkcs5Error::InvalidParameters),
+        ] {
+            let result = gcm(&|w| {
+                w.octet_string(&[1; 12]);
+                w.integer_i64(tag_len);
+            });
+            assert_eq!(result.err(), Some(err));
+        }
+    }
+
+    #[test]
+    fn test_rejects_parameters() {
+        let pbes2 = |kdf: &ObjectIdentifier, f: &dyn Fn(&mut DerWriter)| {
+            let mut w = DerWriter::new();
+            w.sequence(|w| {
+                w.oid(&oid::PBES2);
+                w.sequence(|w| {
+                    w.sequence(|w| {
+                        w.oid(kdf);
+                        w.sequence(f);
+                    });
+                    w.sequence(|w| {
+                        w.oid(&oid::AES256_CBC);
+                        w.octet_string(&[0; 16]);
+                    });
+                });
+            });
+            Pbes2::from_der(&w.finish())
+        };
+        let pbkdf2 = |f: &dyn Fn(&mut DerWriter)| pbes2(&oid::PBKDF2, f);
+        assert!(pbkdf2(&|w| {
+            w.octet_string(b"salt");
+            w.integer_i64(1);
+            w.integer_i64(32);
+        })
+        .is_ok());
+        assert_eq!(
+            pbkdf2(&|w| {
+                w.octet_string(b"salt");
+                w.integer_i64(0);
+            }),
+            Err(Pkcs5Error::InvalidParameters)
+        );
+        assert_eq!(
+            pbkdf2(&|w| {
+                w.octet_string(b"salt");
+                w.integer_i64(1);
+                w.integer_i64(16);
+            }),
+            Err(Pkcs5Error::InvalidParameters)
+        );
+        assert_eq!(
+            pbkdf2(&|w| {
+                w.octet_string(b"salt");
+                w.integer_i64(1);
+                w.sequence(|w| w.oid(&oid::SHA256));
+            }),
+            Err(Pkcs5Error::UnsupportedAlgorithm(oid::SHA256))
+        );
+
+        let scrypt = |n: i64, r: i64, p: i64| {
+            pbes2(&oid::SCRYPT, &|w| {
+                w.octet_string(b"salt");
+                w.integer_i64(n);
+                w.integer_i64(r);
+                w.integer_i64(p);
+            })
+        };
+        assert!(scrypt(2, 1, 1).is_ok());
+        assert_eq!(scrypt(3, 1, 1), Err(Pkcs5Error::InvalidParameters));
+        assert_eq!(scrypt(1, 1, 1), Err(Pkcs5Error::InvalidParameters));
+        assert_eq!(scrypt(2, 0, 1), Err(Pkcs5Error::InvalidParameters));
+        assert_eq!(scrypt(2, 1, 0), Err(Pkcs5Error::InvalidParameters));
+        assert_eq!(scrypt(1 << 62, 1, 1), Err(Pkcs5Error::InvalidParameters));
+        // A V table of 2 blocks, but a B buffer of 64 GiB.
+        assert_eq!(scrypt(2, 1, 1 << 29), Err(Pkcs5Error::InvalidParameters));
+        // 128 MiB of V and little B, but 2^26 block mixes.
+        assert!(scrypt(1 << 20, 1, 16).is_ok());
+        assert_eq!(scrypt(1 << 20, 1, 64), Err(Pkcs5Error::InvalidParameters));
+
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.oid(&oid::PBKDF2);
+            w.null();
+        });
+        assert_eq!(
+            Pbes2::from_der(&w.finish()),
+            Err(Pkcs5Error::UnsupportedAlgorithm(oid::PBKDF2))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::bigint::BigInt;
+
+/// Miller-Rabin primality test.
+pub fn miller_rabin(n: &BigInt, k: u64) -> bool {
+    n.assert_valid();
+    if n < &2u64 {
+        return false;
+    }
+    if n == &2u64 || n == &3u64 {
+        return true;
+    }
+    if n.is_even() {
+        return false;
+    }
+
+    let mut n1 = n.clone();
+    n1.minus_one();
+    let mut d = n1.clone();
+    let s = d.trailing_zeros();
+    d >>= s;
+
+    for _ in 0..k {
+        // Witnesses 0 and 1 would make a prime look composite; redraw them.
+        let mut a = BigInt::random(n.bit_length() - 1);
+        while a < 2u64 {
+            a = BigInt::random(n.bit_length() - 1);
+        }
+        n.assert_valid();
+        let mut x = a.modpow(&d, n);
+
+        if x == 1 || x == n1 {
+            continue;
+        }
+
+        let mut passed_round = false;
+        for _ in 0..((s as usize) - 1) {
+            x = x.modpow_u32(2, n);
+            if x == n1 {
+                passed_round = true;
+                break;
+            }
+        }
+
+        if !passed_round {
+            return false;
+        }
+    }
+    true
+}
+
+/// Generates a prime with the given bit length.
+pub fn generate_prime(n_bits: usize, primes: &[u64]) -> BigInt {
+    loop {
+        let mut candidate = BigInt::random(n_bits);
+        candidate |= 1; // Ensure the candidate is odd.
+        if is_probable_prime(&candidate, primes, 40) {
+            return candidate;
+        }
+    }
+}
+
+/// Trial division by `primes`, then `rounds` of Miller-Rabin on the
+/// survivors.
+pub fn is_probable_prime(n: &BigInt, primes: &[u64], rounds: u64) -> bool {
+    if primes.iter().any(|&prime| n % prime == 0 && *n != prime) {
+        return false;
+    }
+    miller_rabin(n, rounds)
+}
+
+/// The first `count` primes, by trial division.
+pub fn small_primes(count: usize) -> Vec<u64> {
+    let mut primes = Vec::with_capacity(count);
+    let mut candidate: u64 = 2;
+    while primes.len() < count {
+        let mut is_prime = true;
+        let sqrt_candidate = (candidate as f64).sqrt() as u64;
+        for &p in &primes {
+            if p > sqrt_candidate {
+                break;
+            }
+            if candidate.is_multiple_of(p) {
+                is_prime = false;
+                break;
+            }
+        }
+        if is_prime {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_miller_rabin() {
+        for p in [2u64, 3, 5, 7, 65537, 2147483647, 18446744073709551557] {
+            assert!(miller_rabin(&BigInt::from_u64(p), 20), "{} is prime", p);
+        }
+        // 561 and 41041 are Carmichael numbers, which fool the Fermat test.
+        for c in [0u64, 1, 4, 9, 561, 41041, 4294967297] {
+            assert!(
+                !miller_rabin(&BigInt::from_u64(c), 20),
+                "{} is composite",
+                c
+            );
+        }
+        // 2^127 - 1 is a Mersenne prime; 2^128 + 1 is not.
+        assert!(miller_rabin(&BigInt::from_binary(&"1".repeat(127)), 20));
+        assert!(!miller_rabin(
+            &BigInt::from_binary(&format!("1{}1", "0".repeat(127))),
+            20
+        ));
+    }
+
+    #[test]
+    fn test_generate_prime() {
+        let primes = small_primes(100);
+        assert_eq!(primes[..5], [2, 3, 5, 7, 11]);
+        assert_eq!(primes[99], 541);
+        let p = generate_prime(96, &primes);
+        assert!(p.bit_length() <= 96);
+        assert!(miller_rabin(&p, 20));
+        assert!(is_probable_prime(&p, &primes, 20));
+        // Small primes pass the sieve; their multiples do not.
+        assert!(is_probable_prime(&BigInt::from_u64(541), &primes, 20));
+        assert!(!is_probable_prime(
+            &BigInt::from_u64(541 * 547),
+            &primes,
+            20
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! PKCS #1 key syntax (RFC 8017 appendix A.1), in DER and in the
+//! "RSA PUBLIC KEY" / "RSA PRIVATE KEY" PEM forms OpenSSL calls
+//! traditional.
+
+use super::{RsaError, RsaPrivateKey, RsaPublicKey};
+use crate::asn1::{DerReader, DerWriter, Tag};
+use crate::bigint::BigInt;
+use crate::pem::{self, Pem};
+
+const PUBLIC_LABEL: &str = "RSA PUBLIC KEY";
+const PRIVATE_LABEL: &str = "RSA PRIVATE KEY";
+
+impl RsaPublicKey {
+    /// `RSAPublicKey ::= SEQUENCE { modulus INTEGER, publicExponent INTEGER }`
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.integer(&self.n);
+            w.integer(&self.e);
+        });
+        w.finish()
+    }
+
+    pub fn from_der(der: &[u8]) -> Result<Self, RsaError> {
+        let (n, e) = DerReader::parse(der, |r| r.sequence(|r| Ok((r.integer()?, r.integer()?))))
+            .map_err(|_| RsaError::InvalidEncoding)?;
+        Self::new(n, e)
+    }
+
+    pub fn to_pem(&self) -> String {
+        Pem::new(PUBLIC_LABEL, self.to_der()).encode()
+    }
+
+    pub fn from_pem(text: &str) -> Result<Self, RsaError> {
+        Self::from_der(&read_pem(text, PUBLIC_LABEL)?)
+    }
+}
+
+impl RsaPrivateKey {
+    /// The two-prime `RSAPrivateKey` (version 0).
+    pub fn to_der(&self) -> Vec<u8> {
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.integer_i64(0);
+            for value in [
+                &self.public.n,
+                &self.public.e,
+                &self.d,
+                &self.p,
+                &self.q,
+                &self.dp,
+                &self.dq,
+                &self.qinv,
+            ] {
+                w.integer(value);
+            }
+        });
+        w.finish()
+    }
+
+    /// Parses an `RSAPrivateKey` and checks that the CRT values agree with
+    /// the primes. Multi-prime keys (version 1, with `otherPrimeInfos`)
+    /// are recognized and refused with `RsaError::MultiPrime`.
+    pub fn from_der(der: &[u8]) -> Result<Self, RsaError> {
+        let (version, values, multi_prime) = DerReader::parse(der, |r| {
+            r.sequence(|r| {
+                let version = r.integer_i64()?;
+                let mut values = Vec::with_capacity(8);
+                for _ in 0..8 {
+                    values.push(r.integer()?);
+                }
+                let multi_prime = r.read_optional(Tag::SEQUENCE)?.is_some();
+                Ok((version, values, multi_prime))
+            })
+        })
+        .map_err(|_| RsaError::InvalidEncoding)?;
+        match (version, multi_prime) {
+            (0, false) => {}
+            (1, true) => return Err(RsaError::MultiPrime),
+            _ => return Err(RsaError::InvalidEncoding),
+        }
+
+        let [n, e, d, p, q, dp, dq, qinv]: [BigInt; 8] =
+            values.try_into().expect("eight integers were read");
+        let key = Self::from_components(n, e, d, p, q)?;
+        if key.dp != dp || key.dq != dq || key.qinv != qinv {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(key)
+    }
+
+    pub fn to_pem(&self) -> String {
+        Pem::new(PRIVATE_LABEL, self.to_der()).encode()
+    }
+
+    /// Reads an unencrypted "RSA PRIVATE KEY" block; blocks with a
+    /// `Proc-Type: 4,ENCRYPTED` header fail with `InvalidEncoding`.
+    pub fn from_pem(text: &str) -> Result<Self, RsaError> {
+        Self::from_der(&read_pem(text, PRIVATE_LABEL)?)
+    }
+}
+
+fn read_pem(text: &str, label: &str) -> Result<Vec<u8>, RsaError> {
+    pem::parse(text)
+        .and_then(|block| block.expect(label))
+        .map_err(|_| RsaError::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::test_key;
+    use super::*;
+
+    // `openssl rsa -traditional` and `openssl rsa -RSAPublicKey_out` for
+    // the shared test key, and a key from `openssl genpkey -pkeyopt
+    // rsa_keygen_primes:3`.
+    const PRIVATE_PEM: &str = include_str!("../../testdata/rsa_pkcs1.pem");
+    const PUBLIC_PEM: &str = include_str!("../../testdata/rsa_pkcs1_public.pem");
+    const THREE_PRIME_PEM: &str = include_str!("../../testdata/rsa_three_prime.pem");
+
+    #[test]
+    fn test_matches_openssl() {
+        let key = test_key();
+        assert_eq!(key.to_pem(), PRIVATE_PEM);
+        assert_eq!(key.public_key().to_pem(), PUBLIC_PEM);
+        assert_eq!(RsaPrivateKey::from_pem(PRIVATE_PEM), Ok(key.clone()));
+        assert_eq!(
+            RsaPublicKey::from_pem(PUBLIC_PEM).as_ref(),
+            Ok(key.public_key())
+        );
+        assert_eq!(RsaPrivateKey::from_der(&key.to_der()), Ok(key));
+    }
+
+    #[test]
+    fn test_rejects_wrong_inputs() {
+        assert_eq!(
+            RsaPrivateKey::from_pem(THREE_PRIME_PEM),
+            Err(RsaError::MultiPrime)
+        );
+        assert_eq!(
+            RsaPrivateKey::from_pem(PUBLIC_PEM),
+            Err(RsaError::InvalidEncoding)
+        );
+        let encrypted = PRIVATE_PEM.replacen(
+            "KEY-----\n",
+            "KEY-----\nProc-Type: 4,ENCRYPTED\nDEK-Info: AES-128-CBC,00\n\n",
+            1,
+        );
+        assert_eq!(
+            RsaPrivateKey::from_pem(&encrypted),
+            Err(RsaError::InvalidEncoding)
+        );
+
+        let key = test_key();
+        let der = key.to_der();
+        // Version 1 without otherPrimeInfos.
+        let mut bad = der.clone();
+        bad[6] = 1;
+        assert_eq!(
+            RsaPrivateKey::from_der(&bad),
+            Err(RsaError::InvalidEncoding)
+        );
+        // Trailing data after the SEQUENCE.
+        let mut bad = der.clone();
+        bad.push(0);
+        assert_eq!(
+            RsaPrivateKey::from_der(&bad),
+            Err(RsaError::InvalidEncoding)
+        );
+
+        let encode = |values: [&BigInt; 8]| {
+            let mut w = DerWriter::new();
+            w.sequence(|w| {
+                w.integer_i64(0);
+                for value in values {
+                    w.integer(value);
+                }
+            });
+            w.finish()
+        };
+        let (n, e) = (key.public_key().n(), key.public_key().e());
+
+        // A coefficient that does not match the primes.
+        let der = encode([
+            n,
+            e,
+            key.d(),
+            key.p(),
+            key.q(),
+            key.dp(),
+            key.dq(),
+            key.dp(),
+        ]);
+        assert_eq!(RsaPrivateKey::from_der(&der), Err(RsaError::InvalidKey));
+
+        // A private exponent that is not the inverse of e, with CRT
+        // exponents derived from it.
+        let d = key.d() + &BigInt::from_u64(2);
+        let mut p1 = key.p().clone();
+        p1.minus_one();
+        let mut q1 = key.q().clone();
+        q1.minus_one();
+        let (dp, dq) = (&d % &p1, &d % &q1);
+        let der = encode([n, e, &d, key.p(), key.q(), &dp, &dq, key.qinv()]);
+        assert_eq!(RsaPrivateKey::from_der(&der), Err(RsaError::InvalidKey));
+        // The trivial factorization 1 * n.
+        let one = BigInt::from_u64(1);
+        let zero = BigInt::from_u64(0);
+        let der = encode([n, e, key.d(), &one, n, &zero, key.d(), &zero]);
+        assert_eq!(RsaPrivateKey::from_der(&der), Err(RsaError::InvalidKey));
+    }
+}
@@ -0,0 +1,388 @@
+use super::pss::mgf1;
+use super::{RsaError, RsaPrivateKey, RsaPublicKey};
+use crate::bigint::BigInt;
+use crate::digest::{Digest, HashAlgorithm};
+use crate::hmac::Hmac;
+use crate::sha2::Sha256;
+use rand::RngCore;
+
+/// RSAES-OAEP parameters: label hash, MGF1 hash and the (often empty) label.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OaepParams {
+    pub hash: HashAlgorithm,
+    pub mgf_hash: HashAlgorithm,
+    pub label: Vec<u8>,
+}
+
+impl OaepParams {
+    /// MGF1 with the same hash and an empty label.
+    pub fn new(hash: HashAlgorithm) -> Self {
+        Self {
+            hash,
+            mgf_hash: hash,
+            label: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, label: &[u8]) -> Self {
+        self.label = label.to_vec();
+        self
+    }
+}
+
+impl Default for OaepParams {
+    /// SHA-256 for both the label hash and MGF1, empty label.
+    fn default() -> Self {
+        Self::new(HashAlgorithm::Sha256)
+    }
+}
+
+fn xor_in_place(dst: &mut [u8], mask: &[u8]) {
+    for (d, m) in dst.iter_mut().zip(mask) {
+        *d ^= m;
+    }
+}
+
+/// Constant-time select: `a` if `choice` is 0xff, `b` if it is 0x00.
+fn ct_select(choice: u8, a: u8, b: u8) -> u8 {
+    (a & choice) | (b & !choice)
+}
+
+/// 0xff if `x == 0`, else 0x00, without branching.
+fn ct_is_zero(x: u8) -> u8 {
+    ((x as u16).wrapping_sub(1) >> 8) as u8
+}
+
+/// 0xff if `a < b`, else 0x00, for values below 2^31.
+fn ct_lt(a: usize, b: usize) -> u8 {
+    ((a.wrapping_sub(b) >> (usize::BITS - 1)) as u8).wrapping_neg()
+}
+
+impl RsaPublicKey {
+    /// RSAES-OAEP-ENCRYPT (RFC 8017 section 7.1.1).
+    pub fn encrypt_oaep<G: RngCore + ?Sized>(
+        &self,
+        params: &OaepParams,
+        msg: &[u8],
+        rng: &mut G,
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        let h_len = params.hash.output_len();
+        if k < 2 * h_len + 2 {
+            return Err(RsaError::KeyTooShort);
+        }
+        if msg.len() > k - 2 * h_len - 2 {
+            return Err(RsaError::MessageTooLong);
+        }
+
+        // DB = lHash || PS || 0x01 || M
+        let mut db = params.hash.digest(&params.label);
+        db.resize(k - msg.len() - h_len - 2, 0x00);
+        db.push(0x01);
+        db.extend_from_slice(msg);
+
+        let mut seed = vec![0u8; h_len];
+        rng.fill_bytes(&mut seed);
+        xor_in_place(&mut db, &mgf1(params.mgf_hash, &seed, k - h_len - 1));
+        xor_in_place(&mut seed, &mgf1(params.mgf_hash, &db, h_len));
+
+        let mut em = vec![0x00];
+        em.extend_from_slice(&seed);
+        em.extend_from_slice(&db);
+        self.encrypt_raw(&em)
+    }
+
+    /// RSAES-PKCS1-v1_5-ENCRYPT (RFC 8017 section 7.2.1). Legacy; prefer OAEP.
+    pub fn encrypt_pkcs1v15<G: RngCore + ?Sized>(
+        &self,
+        msg: &[u8],
+        rng: &mut G,
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.size();
+        if msg.len() + 11 > k {
+            return Err(RsaError::MessageTooLong);
+        }
+        // EM = 0x00 || 0x02 || PS (non-zero random) || 0x00 || M
+        let mut em = vec![0x00, 0x02];
+        while em.len() < k - msg.len() - 1 {
+            let mut byte = [0u8; 1];
+            rng.fill_bytes(&mut byte);
+            if byte[0] != 0 {
+                em.push(byte[0]);
+            }
+        }
+        em.push(0x00);
+        em.extend_from_slice(msg);
+        self.encrypt_raw(&em)
+    }
+
+    fn encrypt_raw(&self, em: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let c = self.rsaep(&BigInt::from_bytes_be(em))?;
+        Ok(c.to_bytes_be_padded(self.size())
+            .expect("ciphertext is smaller than n"))
+    }
+}
+
+impl RsaPrivateKey {
+    /// RSADP on a ciphertext, returned as the k-byte encoded message.
+    fn decrypt_raw(&self, ciphertext: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key().size();
+        if ciphertext.len() != k {
+            return Err(RsaError::Decryption);
+        }
+        let m = self
+            .rsadp(&BigInt::from_bytes_be(ciphertext))
+            .map_err(|_| RsaError::Decryption)?;
+        m.to_bytes_be_padded(k).ok_or(RsaError::Decryption)
+    }
+
+    /// RSAES-OAEP-DECRYPT (RFC 8017 section 7.1.2).
+    ///
+    /// Every padding failure yields the same `Decryption` error, and the
+    /// checks run over the whole block without early exit (Manger's attack).
+    pub fn decrypt_oaep(
+        &self,
+        params: &OaepParams,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key().size();
+        let h_len = params.hash.output_len();
+        if k < 2 * h_len + 2 {
+            return Err(RsaError::Decryption);
+        }
+        let mut em = self.decrypt_raw(ciphertext)?;
+
+        let (y, rest) = em.split_at_mut(1);
+        let (seed, db) = rest.split_at_mut(h_len);
+        xor_in_place(seed, &mgf1(params.mgf_hash, db, h_len));
+        xor_in_place(db, &mgf1(params.mgf_hash, seed, k - h_len - 1));
+
+        let l_hash = params.hash.digest(&params.label);
+        let mut bad = y[0];
+        for (a, b) in db[..h_len].iter().zip(&l_hash) {
+            bad |= a ^ b;
+        }
+
+        // Find the 0x01 separator after PS, touching every byte.
+        let mut looking = 0xffu8;
+        let mut index = 0usize;
+        for (i, &b) in db.iter().enumerate().skip(h_len) {
+            let is_one = ct_is_zero(b ^ 0x01);
+            let is_zero = ct_is_zero(b);
+            let found = looking & is_one;
+            index = if found != 0 { i } else { index };
+            bad |= looking & !is_one & !is_zero;
+            looking &= !is_one;
+        }
+        bad |= looking;
+
+        if bad != 0 {
+            return Err(RsaError::Decryption);
+        }
+        Ok(db[index + 1..].to_vec())
+    }
+
+    /// RSAES-PKCS1-v1_5-DECRYPT (RFC 8017 section 7.2.2) with implicit
+    /// rejection.
+    ///
+    /// A ciphertext with bad padding does not produce an error: it decrypts
+    /// to a synthetic message derived from the key and the ciphertext, so a
+    /// caller cannot be turned into a Bleichenbacher padding oracle. The
+    /// synthetic message matches what OpenSSL 3.2+ returns
+    /// (draft-irtf-cfrg-rsa-guidance). Errors are only returned for a
+    /// wrong-length or out-of-range ciphertext.
+    pub fn decrypt_pkcs1v15(&self, ciphertext: &[u8]) -> Result<Vec<u8>, RsaError> {
+        let k = self.public_key().size();
+        if k < 11 {
+            return Err(RsaError::KeyTooShort);
+        }
+        let em = self.decrypt_raw(ciphertext)?;
+        let (synthetic, synthetic_len) = self.implicit_rejection_message(ciphertext);
+
+        // EM = 0x00 || 0x02 || PS (>= 8 non-zero bytes) || 0x00 || M
+        let mut good = ct_is_zero(em[0]) & ct_is_zero(em[1] ^ 0x02);
+        let mut looking = 0xffu8;
+        let mut zero_index = 0usize;
+        for (i, &b) in em.iter().enumerate().skip(2) {
+            let found = looking & ct_is_zero(b);
+            zero_index = if found != 0 { i } else { zero_index };
+            looking &= !ct_is_zero(b);
+        }
+        good &= !looking;
+        good &= !ct_lt(zero_index, 2 + 8);
+
+        // Both candidates are computed; the output is chosen byte by byte.
+        let msg_index = zero_index + 1;
+        let real_len = k - msg_index.min(k);
+        let out_len = if good != 0 { real_len } else { synthetic_len };
+        let mut out = vec![0u8; out_len];
+        for (i, byte) in out.iter_mut().enumerate() {
+            let real = em.get(k - out_len + i).copied().unwrap_or(0);
+            let fake = synthetic[k - out_len + i];
+            *byte = ct_select(good, real, fake);
+        }
+        Ok(out)
+    }
+
+    /// The synthetic message and its length for implicit rejection.
+    fn implicit_rejection_message(&self, ciphertext: &[u8]) -> (Vec<u8>, usize) {
+        let k = self.public_key().size();
+        // KDK = HMAC-SHA256(SHA256(I2OSP(d, k)), ciphertext)
+        let d = self.d().to_bytes_be_padded(k).expect("d is smaller than n");
+        let kdk = Hmac::<Sha256>::mac(&Sha256::digest(&d), ciphertext);
+
+        let synthetic = implicit_rejection_prf(&kdk, b"message", k);
+        let candidates = implicit_rejection_prf(&kdk, b"length", 256);
+
+        // The last candidate below the maximum message length wins.
+        let max_len = k - 2 - 8;
+        let mut mask = max_len;
+        for shift in [1, 2, 4, 8] {
+            mask |= mask >> shift;
+        }
+        let mut synthetic_len = 0;
+        for pair in candidates.chunks_exact(2) {
+            let candidate = (u16::from_be_bytes([pair[0], pair[1]]) as usize) & mask;
+            if candidate < max_len {
+                synthetic_len = candidate;
+            }
+        }
+        (synthetic, synthetic_len)
+    }
+}
+
+/// The implicit-rejection PRF: HMAC-SHA256 in counter mode over
+/// `I2OSP(i, 2) || label || I2OSP(bits, 2)`.
+fn implicit_rejection_prf(kdk: &[u8], label: &[u8], len: usize) -> Vec<u8> {
+    let bits = ((len * 8) as u16).to_be_bytes();
+    let mut out = Vec::with_capacity(len + 32);
+    let mut i = 0u16;
+    while out.len() < len {
+        let mut h = Hmac::<Sha256>::new(kdk);
+        h.update(&i.to_be_bytes());
+        h.update(label);
+        h.update(&bits);
+        out.extend_from_slice(&h.finalize());
+        i += 1;
+    }
+    out.truncate(len);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+    use crate::hex;
+    use crate::rsa::tests::test_key;
+
+    const OPENSSL_VECTORS: &str = include_str!("../../testdata/rsa_encryption.txt");
+
+    fn hash_by_name(name: &str) -> HashAlgorithm {
+        match name {
+            "sha1" => HashAlgorithm::Sha1,
+            "sha256" => HashAlgorithm::Sha256,
+            "sha384" => HashAlgorithm::Sha384,
+            "sha512" => HashAlgorithm::Sha512,
+            _ => panic!("unknown hash {}", name),
+        }
+    }
+
+    fn field(s: &str) -> Vec<u8> {
+        if s == "-" {
+            Vec::new()
+        } else {
+            hex::decode(s).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_decrypt_openssl_ciphertexts() {
+        let key = test_key();
+        for line in OPENSSL_VECTORS.lines().filter(|l| !l.starts_with('#')) {
+            let f: Vec<&str> = line.split_whitespace().collect();
+            let msg = field(f[4]);
+            let ct = field(f[5]);
+            let decrypted = match f[0] {
+                "oaep" => {
+                    let params = OaepParams {
+                        hash: hash_by_name(f[1]),
+                        mgf_hash: hash_by_name(f[2]),
+                        label: field(f[3]),
+                    };
+                    key.decrypt_oaep(&params, &ct)
+                }
+                _ => key.decrypt_pkcs1v15(&ct),
+            };
+            assert_eq!(decrypted, Ok(msg), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_oaep_roundtrip_and_rejects() {
+        let key = test_key();
+        let public = key.public_key();
+        let mut rng = HmacDrbg::<Sha256>::new(b"oaep seed entropy 0123456789abcdef", b"", &[]);
+        let params = OaepParams::default().with_label(b"key escrow");
+
+        let wrapped = public.encrypt_oaep(&params, &[0x42; 32], &mut rng).unwrap();
+        assert_eq!(key.decrypt_oaep(&params, &wrapped), Ok(vec![0x42; 32]));
+
+        // Wrong label, corrupted ciphertext and wrong length all fail alike.
+        assert_eq!(
+            key.decrypt_oaep(&OaepParams::default(), &wrapped),
+            Err(RsaError::Decryption)
+        );
+        let mut corrupted = wrapped.clone();
+        corrupted[10] ^= 0x80;
+        assert_eq!(
+            key.decrypt_oaep(&params, &corrupted),
+            Err(RsaError::Decryption)
+        );
+        assert_eq!(
+            key.decrypt_oaep(&params, &wrapped[1..]),
+            Err(RsaError::Decryption)
+        );
+
+        // At most k - 2hLen - 2 bytes fit.
+        let max = 256 - 2 * 32 - 2;
+        assert!(public
+            .encrypt_oaep(&params, &vec![1; max], &mut rng)
+            .is_ok());
+        assert_eq!(
+            public.encrypt_oaep(&params, &vec![1; max + 1], &mut rng),
+            Err(RsaError::MessageTooLong)
+        );
+    }
+
+    #[test]
+    fn test_pkcs1v15_roundtrip() {
+        let key = test_key();
+        let mut rng = HmacDrbg::<Sha256>::new(b"pkcs1 padding entropy 0123456789", b"", &[]);
+        for msg in [&b""[..], b"short", &[0xab; 245]] {
+            let ct = key.public_key().encrypt_pkcs1v15(msg, &mut rng).unwrap();
+            assert_eq!(key.decrypt_pkcs1v15(&ct), Ok(msg.to_vec()));
+        }
+        assert_eq!(
+            key.public_key().encrypt_pkcs1v15(&[0; 246], &mut rng),
+            Err(RsaError::MessageTooLong)
+        );
+    }
+
+    #[test]
+    fn test_pkcs1v15_implicit_rejection_is_deterministic() {
+        let key = test_key();
+        // Valid RSA input whose plaintext does not start with 00 02.
+        let bogus = key
+            .public_key()
+            .rsaep(&BigInt::from_hex("0123456789abcdef"))
+            .unwrap()
+            .to_bytes_be_padded(256)
+            .unwrap();
+        let first = key.decrypt_pkcs1v15(&bogus).unwrap();
+        assert_eq!(key.decrypt_pkcs1v15(&bogus), Ok(first.clone()));
+        assert!(first.len() <= 256 - 11);
+        assert_ne!(hex::encode(&first), "0123456789abcdef");
+        assert_eq!(key.decrypt_pkcs1v15(&bogus[1..]), Err(RsaError::Decryption));
+    }
+}
@@ -0,0 +1,307 @@
+use crate::bigint::BigInt;
+use crate::prime::{miller_rabin, small_primes};
+use rand::RngCore;
+
+mod encoding;
+pub mod encrypt;
+pub mod pkcs1v15;
+pub mod pss;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RsaError {
+    /// The input integer is not in [0, n-1] ("message/ciphertext/signature
+    /// representative out of range").
+    OutOfRange,
+    /// The message is too long for the key size and padding scheme.
+    MessageTooLong,
+    /// The modulus is too short for the requested encoding.
+    KeyTooShort,
+    /// The key components are inconsistent.
+    InvalidKey,
+    /// The hash may be used for verification but not for new signatures.
+    UnsupportedHash,
+    /// Signature verification failed.
+    Verification,
+    /// Decryption failed. Deliberately carries no detail about why.
+    Decryption,
+    /// A DER or PEM key encoding is malformed, has the wrong label or is
+    /// encrypted.
+    InvalidEncoding,
+    /// The key has more than two primes, which this implementation does
+    /// not support.
+    MultiPrime,
+}
+
+/// RSA public key (n, e).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    n: BigInt,
+    e: BigInt,
+}
+
+/// RSA private key with its CRT parameters (RFC 8017 section 3.2, the
+/// two-prime form).
+#[derive(Clone, PartialEq, Eq)]
+pub struct RsaPrivateKey {
+    public: RsaPublicKey,
+    d: BigInt,
+    p: BigInt,
+    q: BigInt,
+    dp: BigInt,
+    dq: BigInt,
+    qinv: BigInt,
+}
+
+impl std::fmt::Debug for RsaPrivateKey {
+    // Never print the private exponent or primes.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RsaPrivateKey")
+            .field("n", &self.public.n)
+            .field("e", &self.public.e)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RsaPublicKey {
+    pub fn new(n: BigInt, e: BigInt) -> Result<Self, RsaError> {
+        if n.is_even() || n < 3u64 || e.is_even() || e < 3u64 || e >= n {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(Self { n, e })
+    }
+
+    pub fn n(&self) -> &BigInt {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigInt {
+        &self.e
+    }
+
+    /// Modulus length in bytes (`k` in RFC 8017).
+    pub fn size(&self) -> usize {
+        self.n.bit_length().div_ceil(8)
+    }
+
+    /// RSAEP (RFC 8017 section 5.1.1): c = m^e mod n.
+    pub fn rsaep(&self, m: &BigInt) -> Result<BigInt, RsaError> {
+        if m >= &self.n {
+            return Err(RsaError::OutOfRange);
+        }
+        Ok(m.modpow(&self.e, &self.n))
+    }
+
+    /// RSAVP1 (RFC 8017 section 5.2.2): m = s^e mod n.
+    pub fn rsavp1(&self, s: &BigInt) -> Result<BigInt, RsaError> {
+        self.rsaep(s)
+    }
+}
+
+impl RsaPrivateKey {
+    /// Builds a key from n, e, d and the two primes, deriving the CRT values.
+    /// Fails with `InvalidKey` unless p q = n with neither factor below 3,
+    /// and e d = 1 mod lcm(p - 1, q - 1).
+    pub fn from_components(
+        n: BigInt,
+        e: BigInt,
+        d: BigInt,
+        p: BigInt,
+        q: BigInt,
+    ) -> Result<Self, RsaError> {
+        let public = RsaPublicKey::new(n, e)?;
+        // A factor of 1 would leave lcm(p - 1, q - 1) zero.
+        if p < 3u64 || q < 3u64 || &p * &q != public.n {
+            return Err(RsaError::InvalidKey);
+        }
+        let mut p1 = p.clone();
+        p1.minus_one();
+        let mut q1 = q.clone();
+        q1.minus_one();
+        let lambda = &(&p1 * &q1) / &p1.gcd(&q1);
+        if &(&public.e * &d) % &lambda != 1u64 {
+            return Err(RsaError::InvalidKey);
+        }
+        let dp = &d % &p1;
+        let dq = &d % &q1;
+        let qinv = q.modinv(&p).ok_or(RsaError::InvalidKey)?;
+        Ok(Self {
+            public,
+            d,
+            p,
+            q,
+            dp,
+            dq,
+            qinv,
+        })
+    }
+
+    /// Generates a two-prime key with e = 65537 and an exactly `bits`-bit
+    /// modulus, drawing candidates from `rng`.
+    pub fn generate<G: RngCore + ?Sized>(bits: usize, rng: &mut G) -> Self {
+        assert!(bits >= 64 && bits.is_multiple_of(2), "unsupported RSA size");
+        let e = BigInt::from_u64(65537);
+        let sieve = small_primes(2048);
+        loop {
+            let p = generate_rsa_prime(bits / 2, &e, &sieve, rng);
+            let q = generate_rsa_prime(bits / 2, &e, &sieve, rng);
+            if p == q {
+                continue;
+            }
+            // d = e^-1 mod lcm(p-1, q-1)
+            let mut p1 = p.clone();
+            p1.minus_one();
+            let mut q1 = q.clone();
+            q1.minus_one();
+            let lambda = &(&p1 * &q1) / &p1.gcd(&q1);
+            let Some(d) = e.modinv(&lambda) else {
+                continue;
+            };
+            let (p, q) = if p > q { (p, q) } else { (q, p) };
+            let n = &p * &q;
+            return Self::from_components(n, e, d, p, q)
+                .expect("freshly generated RSA key is consistent");
+        }
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public
+    }
+
+    pub fn d(&self) -> &BigInt {
+        &self.d
+    }
+
+    pub fn p(&self) -> &BigInt {
+        &self.p
+    }
+
+    pub fn q(&self) -> &BigInt {
+        &self.q
+    }
+
+    pub fn dp(&self) -> &BigInt {
+        &self.dp
+    }
+
+    pub fn dq(&self) -> &BigInt {
+        &self.dq
+    }
+
+    pub fn qinv(&self) -> &BigInt {
+        &self.qinv
+    }
+
+    /// RSADP (RFC 8017 section 5.1.2) using the CRT: m = c^d mod n.
+    pub fn rsadp(&self, c: &BigInt) -> Result<BigInt, RsaError> {
+        if c >= &self.public.n {
+            return Err(RsaError::OutOfRange);
+        }
+        let m1 = c.modpow(&self.dp, &self.p);
+        let m2 = c.modpow(&self.dq, &self.q);
+        // h = (m1 - m2) * qInv mod p, computed without going negative.
+        let m2_mod_p = &m2 % &self.p;
+        let diff = &(&m1 + &self.p) - &m2_mod_p;
+        let h = &(&diff * &self.qinv) % &self.p;
+        let m = &m2 + &(&h * &self.q);
+
+        // Guard against CRT faults leaking the factorization (Boneh et al.).
+        if m.modpow(&self.public.e, &self.public.n) != *c {
+            return Err(RsaError::InvalidKey);
+        }
+        Ok(m)
+    }
+
+    /// RSASP1 (RFC 8017 section 5.2.1): s = m^d mod n.
+    pub fn rsasp1(&self, m: &BigInt) -> Result<BigInt, RsaError> {
+        self.rsadp(m)
+    }
+}
+
+/// A random `bits`-bit prime with its top two bits set (so two of them
+/// multiply to exactly `2 * bits` bits) and p - 1 coprime to `e`.
+fn generate_rsa_prime<G: RngCore + ?Sized>(
+    bits: usize,
+    e: &BigInt,
+    sieve: &[u64],
+    rng: &mut G,
+) -> BigInt {
+    loop {
+        let mut candidate = BigInt::random_with(bits, rng);
+        candidate.set_bit(bits - 1);
+        candidate.set_bit(bits - 2);
+        candidate |= 1;
+        if sieve.iter().any(|&p| &candidate % p == 0 && candidate != p) {
+            continue;
+        }
+        let mut c1 = candidate.clone();
+        c1.minus_one();
+        if (&c1 % e).is_zero() {
+            continue;
+        }
+        if miller_rabin(&candidate, 40) {
+            return candidate;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+    use crate::sha2::Sha256;
+
+    /// A 2048-bit key generated with `openssl genpkey`, shared by the RSA
+    /// tests.
+    pub(crate) fn test_key() -> RsaPrivateKey {
+        RsaPrivateKey::from_components(
+            BigInt::from_hex("b484843b8e44aa3c4f08be04abf3dd3667b56cda1213b81b3fe9c77aedc65087cdbacec27dc1d00fda8640a1e9a26e3596cec28cb7ab754818e4753c2ac20194f78e39ebe794f91b77065fe3b868c1bf44818c49a277a439cffe2f5a85286629c8d2b3f2676414d0dc8352750c01928705a49eba2e6dd2d3e778573ad583f1eeb7869c6a7aeadcf942dcc06311fe764529cfe354aa5cf049e1badcb7d689d295078b903451c0f83e5568645909a37307891e108122209ffa2337ae940390ffd427bb1575c14b0105badc97a451a43fbc11f2fd1efb1d2a2db53e8d892cacc8ed9b89035b63de615f35352577c70d06834969d14a3948dd6e6c1026c4945d7211"),
+            BigInt::from_u64(65537),
+            BigInt::from_hex("1acb90d54648eafc08bf43756e10c4c54e9f9f88bf262e2ddd4ed64d68fe06f62193991d458d7f54db1b127cf631262ace37e2a9009a72ce40e3a88345d58666965886407e1f9eda74d07d6b4ff43cce275d1375daa7e7b8ad270de061e39d469688b02d4b2b8feb70d00d6d525ae96521714e1e54c5f8857bd6611c5a953b53f3cfc4cc9349dc6aa83b3fbb5437b8c41b5078ad0377f4fcfd2ab0966d6bb57892b39f91409472dab8c354767a76d9ebe9b17c0432c6b585cd8a88a7948c83f1ed2cf8c2b9a5ab8c75c58198bb00a822ba5ea843fdd6cbd9a72620be42f34f8075f5ac859a8d34d0571b890e3b6c4e06b48e2c945fcbf9d38aadd1ee2c4fd81"),
+            BigInt::from_hex("ec31b8f1284321b8ab7a241bae0c647e09c0f1ea99c49521f8ce1b3cf518bc5c0cc5b7ef694ba3531b1f61efc9e287463e20b42fd09c1312fd56255907517143b0fda40d1ca7be2d2669a81467188fb05d18c6886a48f8f33f28d7e9633972afd7b3e509ba0d6c8f23f8dbcb204542973f71099c7fa1d5c1769dd71747cdb837"),
+            BigInt::from_hex("c3a79bfdbddaafc3d6633e3605f876f0b3ba34d952fd9b726124d09bc6702b299b4b423f0269df778db4736a8a59a5c99e6d35da313b11a0549be63a378247de543e20fee54248437e14d8df29e0a860b172909fab1240cba53f9b6744c70fd38417945687a31409044a996ad9734e5e7f512bd273bf9e6cd7184b9265e073f7"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_primitives_roundtrip() {
+        let key = test_key();
+        assert_eq!(key.public_key().size(), 256);
+        let m = BigInt::from_hex("123456789abcdef");
+        let c = key.public_key().rsaep(&m).unwrap();
+        assert_eq!(key.rsadp(&c).unwrap(), m);
+        // CRT result matches the plain d exponentiation.
+        assert_eq!(c.modpow(key.d(), key.public_key().n()), m);
+
+        let n = key.public_key().n().clone();
+        assert_eq!(key.public_key().rsaep(&n), Err(RsaError::OutOfRange));
+        assert_eq!(key.rsadp(&n), Err(RsaError::OutOfRange));
+    }
+
+    #[test]
+    fn test_invalid_components() {
+        let key = test_key();
+        let bad = RsaPrivateKey::from_components(
+            key.public_key().n().clone(),
+            BigInt::from_u64(65537),
+            key.d().clone(),
+            key.p().clone(),
+            BigInt::from_u64(3),
+        );
+        assert_eq!(bad.unwrap_err(), RsaError::InvalidKey);
+        assert_eq!(
+            RsaPublicKey::new(BigInt::from_u64(15), BigInt::from_u64(2)),
+            Err(RsaError::InvalidKey)
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut drbg = HmacDrbg::<Sha256>::new(b"rsa keygen test entropy 0123456", b"nonce", &[]);
+        let key = RsaPrivateKey::generate(512, &mut drbg);
+        assert_eq!(key.public_key().n().bit_length(), 512);
+        let m = BigInt::from_u64(42);
+        let c = key.public_key().rsaep(&m).unwrap();
+        assert_eq!(key.rsadp(&c).unwrap(), m);
+    }
+}
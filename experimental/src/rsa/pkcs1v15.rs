@@ -0,0 +1,249 @@
+use super::{RsaError, RsaPrivateKey, RsaPublicKey};
+use crate::bigint::BigInt;
+use crate::ct::ct_eq;
+use crate::digest::HashAlgorithm;
+
+/// DER encoding of the DigestInfo `SEQUENCE { AlgorithmIdentifier, OCTET STRING }`
+/// up to (not including) the hash value (RFC 8017 section 9.2, note 1).
+pub fn digest_info_prefix(hash: HashAlgorithm) -> &'static [u8] {
+    match hash {
+        HashAlgorithm::Sha1 => &[
+            0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+            0x14,
+        ],
+        HashAlgorithm::Sha224 => &[
+            0x30, 0x2d, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x04, 0x05, 0x00, 0x04, 0x1c,
+        ],
+        HashAlgorithm::Sha256 => &[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x01, 0x05, 0x00, 0x04, 0x20,
+        ],
+        HashAlgorithm::Sha384 => &[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x02, 0x05, 0x00, 0x04, 0x30,
+        ],
+        HashAlgorithm::Sha512 => &[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x03, 0x05, 0x00, 0x04, 0x40,
+        ],
+        HashAlgorithm::Sha3_256 => &[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x08, 0x05, 0x00, 0x04, 0x20,
+        ],
+        HashAlgorithm::Sha3_384 => &[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x09, 0x05, 0x00, 0x04, 0x30,
+        ],
+        HashAlgorithm::Sha3_512 => &[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x0a, 0x05, 0x00, 0x04, 0x40,
+        ],
+    }
+}
+
+/// EMSA-PKCS1-v1_5-ENCODE (RFC 8017 section 9.2):
+/// `0x00 || 0x01 || 0xff.. || 0x00 || DigestInfo`.
+fn emsa_pkcs1v15_encode(
+    hash: HashAlgorithm,
+    msg: &[u8],
+    em_len: usize,
+) -> Result<Vec<u8>, RsaError> {
+    let prefix = digest_info_prefix(hash);
+    let t_len = prefix.len() + hash.output_len();
+    if em_len < t_len + 11 {
+        return Err(RsaError::KeyTooShort);
+    }
+    let mut em = Vec::with_capacity(em_len);
+    em.push(0x00);
+    em.push(0x01);
+    em.resize(em_len - t_len - 1, 0xff);
+    em.push(0x00);
+    em.extend_from_slice(prefix);
+    em.extend_from_slice(&hash.digest(msg));
+    Ok(em)
+}
+
+impl RsaPrivateKey {
+    /// RSASSA-PKCS1-v1_5-SIGN (RFC 8017 section 8.2.1).
+    ///
+    /// SHA-1 is refused: it is accepted for verifying old signatures only.
+    pub fn sign_pkcs1v15(&self, hash: HashAlgorithm, msg: &[u8]) -> Result<Vec<u8>, RsaError> {
+        if hash == HashAlgorithm::Sha1 {
+            return Err(RsaError::UnsupportedHash);
+        }
+        let k = self.public_key().size();
+        let em = emsa_pkcs1v15_encode(hash, msg, k)?;
+        let s = self.rsasp1(&BigInt::from_bytes_be(&em))?;
+        Ok(s.to_bytes_be_padded(k)
+            .expect("signature is smaller than n"))
+    }
+}
+
+impl RsaPublicKey {
+    /// RSASSA-PKCS1-v1_5-VERIFY (RFC 8017 section 8.2.2).
+    ///
+    /// The expected encoding is rebuilt from `msg` and compared byte for byte
+    /// with the recovered one. Nothing in the signature is parsed, so the
+    /// lax-parser forgeries against small exponents (Bleichenbacher 2006) do
+    /// not apply.
+    pub fn verify_pkcs1v15(
+        &self,
+        hash: HashAlgorithm,
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), RsaError> {
+        let k = self.size();
+        if signature.len() != k {
+            return Err(RsaError::Verification);
+        }
+        let m = self
+            .rsavp1(&BigInt::from_bytes_be(signature))
+            .map_err(|_| RsaError::Verification)?;
+        let em = m.to_bytes_be_padded(k).ok_or(RsaError::Verification)?;
+        let expected = emsa_pkcs1v15_encode(hash, msg, k)?;
+        if ct_eq(&em, &expected) {
+            Ok(())
+        } else {
+            Err(RsaError::Verification)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+    use crate::rsa::tests::test_key;
+
+    const MSG: &[u8] = b"rspki pkcs1v15 test message";
+
+    // Signatures over MSG produced by OpenSSL with the shared test key.
+    const SIG_SHA224: &str = "\
+             9ac76525df2d05b2cff744ad12ab8588126380e983e5b6fdca705ddf2d880b38\
+             e46180e99ef4833c7d2e63d18d0bc7ccb3a64ddc54f1b172b23b5cd2be80f6c2\
+             996da9e7151d85d0a9481077264ec2e3e43794a4e906bc326afb7eea3fec07e7\
+             cd2d24c63fa6611bdd52eb25bd92381117d6b1b447aef71df01871d7f1d2d4f4\
+             7249d8f186a1ce80c6ecb056d3cc51e6944f3b98dcb163cbb098ac4e7466689c\
+             e869385229ac5dcaf5a4964b68bf8cfdc99faf9ddf31486ab20334687a41b0b9\
+             9fff6fb28fb9e255f100a77351d05f2f655c2a73f611c7d3b833f43daad14b0f\
+             9496f1f778fd7dc78e3576b5d6a2f4dcee99ab14fa7d06cc2fded76d1de53c2d";
+    const SIG_SHA256: &str = "\
+             5cbb4b4ee0a563c6c0c3eb3c7216896ce3b87f41681de073da5dfd946da12bc9\
+             640eb4d759d6ef7f200caca732e387cc1ef35072c8abe8ff7cd84f886012c565\
+             b77125ace48f7b425ab5c5870f1ee4524a89a5466ecb5c5d440b7a1509cf652b\
+             6150ff6c0c68d33bd6633c44d47981ce256353cb1f8867d6932741375cd8a7de\
+             c87ba370e4add4343206be3046fa04fdda0e555e3a1649d3b1fb64cc1486dc02\
+             87643bf0fb724802644e929c37b011ecaa1641ad5bef26849015e67f2690219b\
+             dbbbea161e491071045cd0be600d96a84180cfc5dd42b6b9b502320118595c30\
+             087ff89d7aef951ff079f199c6086df5fcb2be1cd28d5e64e24b7686153e4e66";
+    const SIG_SHA384: &str = "\
+             a4165b9d18c7ad2665dc12702080c83d6e73af11c32fed66a5d1b12046365923\
+             8d705b5d858ad5939f1ff36522354ab7d5361bb1b4395734fb63a8c76be44c8c\
+             61a76a6cd6f77c8a4fc0a7257675996c6fb0d3aa67b44875722886f6cc35e4d3\
+             89af54281ca0db83f85191827489fccded4cef083796edd35e2ffae4fd6ebf03\
+             dda4a23a79b097763ccdc2368ed84145ddc74f4484a5a628366651d7e160388a\
+             00789a3e23c970650014c45594cdc89935bf7e2135ccfff1d6aa2505ba77d413\
+             3082711d516ca601ae0bd0b65ba91263cb24e74a1d606a2c77fb4b0ea0424a6c\
+             1c4a196a1482e35140b5ef5ad141fad456dfba8973715577328a8252c3b10e6d";
+    const SIG_SHA512: &str = "\
+             2afb3462302f0bebddb657da7bb060e010f1681f786743bbbd867d6f7c004c37\
+             19923a7d5f22a2dfc87a03c735d7c7ffe66d4a65e9b47758aa420ffc2dbf10f8\
+             2fd584b35a8b81b060dea7d2be7cac22835de35a6e5a47a5f2a834031cc6b5bc\
+             84fdbd6ff2ca1fe0d25ab55582a5904ce6c4f2a921b28490062343cb4dc6a208\
+             c813bc7b0fad018e0a1fbd4bc2b242f13c0b112f38cb5849b870465b039f09b2\
+             23220b89808ceee773081e560a89a0e9ca010e3a02126ff1f378bfc3151d5ec3\
+             f81a6be0d0641ad13d499a4c6be5553a629249f43ec4e97cec38cee62a3e1cca\
+             0b30380f12ac4e978133fff8bc655747dad27ba098f100f475ba0ab52d69f1fc";
+
+    #[test]
+    fn test_sign_matches_openssl() {
+        let key = test_key();
+        for (hash, expected) in [
+            (HashAlgorithm::Sha224, SIG_SHA224),
+            (HashAlgorithm::Sha256, SIG_SHA256),
+            (HashAlgorithm::Sha384, SIG_SHA384),
+            (HashAlgorithm::Sha512, SIG_SHA512),
+        ] {
+            let sig = key.sign_pkcs1v15(hash, MSG).unwrap();
+            assert_eq!(hex::encode(&sig), expected, "{:?}", hash);
+            assert_eq!(key.public_key().verify_pkcs1v15(hash, MSG, &sig), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects() {
+        let key = test_key();
+        let public = key.public_key();
+        let sig = hex::decode(SIG_SHA256).unwrap();
+
+        // Wrong message, wrong hash, flipped bit.
+        assert_eq!(
+            public.verify_pkcs1v15(HashAlgorithm::Sha256, b"other message", &sig),
+            Err(RsaError::Verification)
+        );
+        assert_eq!(
+            public.verify_pkcs1v15(HashAlgorithm::Sha384, MSG, &sig),
+            Err(RsaError::Verification)
+        );
+        let mut flipped = sig.clone();
+        flipped[100] ^= 0x01;
+        assert_eq!(
+            public.verify_pkcs1v15(HashAlgorithm::Sha256, MSG, &flipped),
+            Err(RsaError::Verification)
+        );
+
+        // Signatures must be exactly k bytes and smaller than n.
+        assert_eq!(
+            public.verify_pkcs1v15(HashAlgorithm::Sha256, MSG, &sig[1..]),
+            Err(RsaError::Verification)
+        );
+        let too_big = public.n().to_bytes_be();
+        assert_eq!(
+            public.verify_pkcs1v15(HashAlgorithm::Sha256, MSG, &too_big),
+            Err(RsaError::Verification)
+        );
+    }
+
+    #[test]
+    fn test_rejects_trailing_garbage_encoding() {
+        // An encoding with a short padding string and junk after the digest
+        // is what lax parsers accept; strict comparison must not.
+        let key = test_key();
+        let k = key.public_key().size();
+        let digest = HashAlgorithm::Sha256.digest(MSG);
+        let prefix = digest_info_prefix(HashAlgorithm::Sha256);
+        let mut em = vec![
+            0x00, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00,
+        ];
+        em.extend_from_slice(prefix);
+        em.extend_from_slice(&digest);
+        em.resize(k, 0x42);
+        let forged = key.rsasp1(&BigInt::from_bytes_be(&em)).unwrap();
+        let forged = forged.to_bytes_be_padded(k).unwrap();
+        assert_eq!(
+            key.public_key()
+                .verify_pkcs1v15(HashAlgorithm::Sha256, MSG, &forged),
+            Err(RsaError::Verification)
+        );
+    }
+
+    #[test]
+    fn test_sha1_verify_only() {
+        let key = test_key();
+        assert_eq!(
+            key.sign_pkcs1v15(HashAlgorithm::Sha1, MSG),
+            Err(RsaError::UnsupportedHash)
+        );
+        // A legacy SHA-1 signature still verifies.
+        let k = key.public_key().size();
+        let em = emsa_pkcs1v15_encode(HashAlgorithm::Sha1, MSG, k).unwrap();
+        let sig = key.rsasp1(&BigInt::from_bytes_be(&em)).unwrap();
+        let sig = sig.to_bytes_be_padded(k).unwrap();
+        assert_eq!(
+            key.public_key()
+                .verify_pkcs1v15(HashAlgorithm::Sha1, MSG, &sig),
+            Ok(())
+        );
+    }
+}
@@ -0,0 +1,529 @@
+use super::pkcs1v15::digest_info_prefix;
+use super::{RsaError, RsaPrivateKey, RsaPublicKey};
+use crate::asn1::{oid, DerWriter};
+use crate::bigint::BigInt;
+use crate::ct::ct_eq;
+use crate::digest::HashAlgorithm;
+use rand::RngCore;
+
+/// MGF1 mask generation (RFC 8017 appendix B.2.1).
+pub fn mgf1(hash: HashAlgorithm, seed: &[u8], len: usize) -> Vec<u8> {
+    let mut mask = Vec::with_capacity(len + hash.output_len());
+    let mut counter = 0u32;
+    while mask.len() < len {
+        let mut input = seed.to_vec();
+        input.extend_from_slice(&counter.to_be_bytes());
+        mask.extend_from_slice(&hash.digest(&input));
+        counter += 1;
+    }
+    mask.truncate(len);
+    mask
+}
+
+/// RSASSA-PSS parameters: message hash, MGF1 hash and salt length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PssParams {
+    pub hash: HashAlgorithm,
+    pub mgf_hash: HashAlgorithm,
+    pub salt_len: usize,
+}
+
+impl PssParams {
+    /// The usual profile: MGF1 with the same hash and a salt as long as the
+    /// digest (what TLS 1.3 and most CAs use).
+    pub fn new(hash: HashAlgorithm) -> Self {
+        Self {
+            hash,
+            mgf_hash: hash,
+            salt_len: hash.output_len(),
+        }
+    }
+
+    /// DER `AlgorithmIdentifier` for id-RSASSA-PSS with RSASSA-PSS-params
+    /// (RFC 4055 section 3.1). Fields equal to their DEFAULT (SHA-1, MGF1
+    /// with SHA-1, salt length 20, trailer field 1) are omitted, as DER
+    /// requires.
+    pub fn algorithm_identifier(&self) -> Vec<u8> {
+        let mut w = DerWriter::new();
+        w.sequence(|w| {
+            w.oid(&oid::RSASSA_PSS);
+            w.sequence(|w| {
+                if self.hash != HashAlgorithm::Sha1 {
+                    w.explicit(0, |w| w.raw(hash_algorithm_identifier(self.hash)));
+                }
+                if self.mgf_hash != HashAlgorithm::Sha1 {
+                    w.explicit(1, |w| {
+                        w.sequence(|w| {
+                            w.oid(&oid::MGF1);
+                            w.raw(hash_algorithm_identifier(self.mgf_hash));
+                        })
+                    });
+                }
+                if self.salt_len != 20 {
+                    w.explicit(2, |w| w.integer_i64(self.salt_len as i64));
+                }
+            });
+        });
+        w.finish()
+    }
+}
+
+/// The hash's `AlgorithmIdentifier`, lifted out of its DigestInfo prefix
+/// (`30 len [AlgorithmIdentifier] 04 len`).
+fn hash_algorithm_identifier(hash: HashAlgorithm) -> &'static [u8] {
+    let prefix = digest_info_prefix(hash);
+    &prefix[2..prefix.len() - 2]
+}
+
+/// How the verifier learns the salt length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SaltLength {
+    Exact(usize),
+    /// Recover it from the position of the 0x01 separator in DB.
+    Auto,
+}
+
+/// `H = Hash(0x00 * 8 || mHash || salt)`.
+fn pss_hash(hash: HashAlgorithm, m_hash: &[u8], salt: &[u8]) -> Vec<u8> {
+    let mut m_prime = vec![0u8; 8];
+    m_prime.extend_from_slice(m_hash);
+    m_prime.extend_from_slice(salt);
+    hash.digest(&m_prime)
+}
+
+/// EMSA-PSS-ENCODE (RFC 8017 section 9.1.1).
+fn emsa_pss_encode(
+    params: &PssParams,
+    msg: &[u8],
+    salt: &[u8],
+    em_bits: usize,
+) -> Result<Vec<u8>, RsaError> {
+    let h_len = params.hash.output_len();
+    let em_len = em_bits.div_ceil(8);
+    if em_len < h_len + salt.len() + 2 {
+        return Err(RsaError::KeyTooShort);
+    }
+    let h = pss_hash(params.hash, &params.hash.digest(msg), salt);
+
+    // DB = PS || 0x01 || salt, masked with MGF1(H).
+    let mut db = vec![0u8; em_len - salt.len() - h_len - 2];
+    db.push(0x01);
+    db.extend_from_slice(salt);
+    let mask = mgf1(params.mgf_hash, &h, db.len());
+    for (b, m) in db.iter_mut().zip(&mask) {
+        *b ^= m;
+    }
+    db[0] &= 0xff >> (8 * em_len - em_bits);
+
+    let mut em = db;
+    em.extend_from_slice(&h);
+    em.push(0xbc);
+    Ok(em)
+}
+
+/// EMSA-PSS-VERIFY (RFC 8017 section 9.1.2). Returns the salt length on success.
+fn emsa_pss_verify(
+    hash: HashAlgorithm,
+    mgf_hash: HashAlgorithm,
+    salt_len: SaltLength,
+    msg: &[u8],
+    em: &[u8],
+    em_bits: usize,
+) -> Result<usize, RsaError> {
+    let h_len = hash.output_len();
+    let em_len = em_bits.div_ceil(8);
+    if em.len() != em_len || em_len < h_len + 2 || em[em_len - 1] != 0xbc {
+        return Err(RsaError::Verification);
+    }
+    let (masked_db, rest) = em.split_at(em_len - h_len - 1);
+    let h = &rest[..h_len];
+    let top_mask = !(0xffu8 >> (8 * em_len - em_bits));
+    if masked_db[0] & top_mask != 0 {
+        return Err(RsaError::Verification);
+    }
+
+    let mask = mgf1(mgf_hash, h, masked_db.len());
+    let mut db: Vec<u8> = masked_db.iter().zip(&mask).map(|(a, b)| a ^ b).collect();
+    db[0] &= !top_mask;
+
+    // DB must be zero bytes, then 0x01, then the salt.
+    let separator = match salt_len {
+        SaltLength::Exact(s) => {
+            if em_len < h_len + s + 2 {
+                return Err(RsaError::Verification);
+            }
+            db.len() - s - 1
+        }
+        SaltLength::Auto => db
+            .iter()
+            .position(|&b| b != 0)
+            .ok_or(RsaError::Verification)?,
+    };
+    if db[..separator].iter().any(|&b| b != 0) || db[separator] != 0x01 {
+        return Err(RsaError::Verification);
+    }
+    let salt = &db[separator + 1..];
+
+    let expected = pss_hash(hash, &hash.digest(msg), salt);
+    if ct_eq(h, &expected) {
+        Ok(salt.len())
+    } else {
+        Err(RsaError::Verification)
+    }
+}
+
+impl RsaPrivateKey {
+    /// RSASSA-PSS-SIGN (RFC 8017 section 8.1.1) with a fresh random salt.
+    pub fn sign_pss<G: RngCore + ?Sized>(
+        &self,
+        params: &PssParams,
+        msg: &[u8],
+        rng: &mut G,
+    ) -> Result<Vec<u8>, RsaError> {
+        let mut salt = vec![0u8; params.salt_len];
+        rng.fill_bytes(&mut salt);
+        self.sign_pss_with_salt(params, msg, &salt)
+    }
+
+    /// RSASSA-PSS-SIGN with a caller-chosen salt, for reproducible output.
+    /// `salt.len()` overrides `params.salt_len`.
+    pub fn sign_pss_with_salt(
+        &self,
+        params: &PssParams,
+        msg: &[u8],
+        salt: &[u8],
+    ) -> Result<Vec<u8>, RsaError> {
+        if params.hash == HashAlgorithm::Sha1 {
+            return Err(RsaError::UnsupportedHash);
+        }
+        let public = self.public_key();
+        let em_bits = public.n().bit_length() - 1;
+        let em = emsa_pss_encode(params, msg, salt, em_bits)?;
+        let s = self.rsasp1(&BigInt::from_bytes_be(&em))?;
+        Ok(s.to_bytes_be_padded(public.size())
+            .expect("signature is smaller than n"))
+    }
+}
+
+impl RsaPublicKey {
+    /// RSASSA-PSS-VERIFY (RFC 8017 section 8.1.2), requiring exactly
+    /// `params.salt_len` bytes of salt.
+    pub fn verify_pss(
+        &self,
+        params: &PssParams,
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<(), RsaError> {
+        self.verify_pss_inner(
+            params.hash,
+            params.mgf_hash,
+            SaltLength::Exact(params.salt_len),
+            msg,
+            signature,
+        )
+        .map(|_| ())
+    }
+
+    /// RSASSA-PSS-VERIFY accepting any salt length, which is recovered from
+    /// the encoding and returned.
+    pub fn verify_pss_auto(
+        &self,
+        hash: HashAlgorithm,
+        mgf_hash: HashAlgorithm,
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<usize, RsaError> {
+        self.verify_pss_inner(hash, mgf_hash, SaltLength::Auto, msg, signature)
+    }
+
+    fn verify_pss_inner(
+        &self,
+        hash: HashAlgorithm,
+        mgf_hash: HashAlgorithm,
+        salt_len: SaltLength,
+        msg: &[u8],
+        signature: &[u8],
+    ) -> Result<usize, RsaError> {
+        if signature.len() != self.size() {
+            return Err(RsaError::Verification);
+        }
+        let m = self
+            .rsavp1(&BigInt::from_bytes_be(signature))
+            .map_err(|_| RsaError::Verification)?;
+        let em_bits = self.n().bit_length() - 1;
+        let em = m
+            .to_bytes_be_padded(em_bits.div_ceil(8))
+            .ok_or(RsaError::Verification)?;
+        emsa_pss_verify(hash, mgf_hash, salt_len, msg, &em, em_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+    use crate::hex;
+    use crate::rsa::tests::test_key;
+    use crate::sha2::Sha256;
+
+    const OPENSSL_VECTORS: &str = include_str!("../../testdata/rsa_pss.txt");
+    const PSS_VECT: &str = include_str!("../../testdata/rsa_pss_vect.txt");
+    const CAVP_VECTORS: &str = include_str!("../../testdata/rsa_pss_cavp.txt");
+    const WYCHEPROOF_VECTORS: &str = include_str!("../../testdata/rsa_pss_wycheproof.txt");
+
+    fn hash_by_name(name: &str) -> HashAlgorithm {
+        match name {
+            "sha1" => HashAlgorithm::Sha1,
+            "sha256" => HashAlgorithm::Sha256,
+            "sha384" => HashAlgorithm::Sha384,
+            "sha512" => HashAlgorithm::Sha512,
+            _ => panic!("unknown hash {}", name),
+        }
+    }
+
+    #[test]
+    fn test_verify_openssl_signatures() {
+        let key = test_key();
+        let public = key.public_key();
+        for line in OPENSSL_VECTORS.lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let params = PssParams {
+                hash: hash_by_name(fields[0]),
+                mgf_hash: hash_by_name(fields[1]),
+                salt_len: fields[2].parse().unwrap(),
+            };
+            let msg = if fields[3] == "-" {
+                Vec::new()
+            } else {
+                hex::decode(fields[3]).unwrap()
+            };
+            let sig = hex::decode(fields[4]).unwrap();
+
+            assert_eq!(public.verify_pss(&params, &msg, &sig), Ok(()), "{}", line);
+            assert_eq!(
+                public.verify_pss_auto(params.hash, params.mgf_hash, &msg, &sig),
+                Ok(params.salt_len)
+            );
+            // A different expected salt length or message must fail.
+            let wrong_salt = PssParams {
+                salt_len: params.salt_len + 1,
+                ..params
+            };
+            assert_eq!(
+                public.verify_pss(&wrong_salt, &msg, &sig),
+                Err(RsaError::Verification)
+            );
+            assert_eq!(
+                public.verify_pss(&params, b"tampered", &sig),
+                Err(RsaError::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_pss_vect_examples() {
+        for line in PSS_VECT.lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [p, q, n, e] = [1, 2, 3, 4].map(|i| BigInt::from_hex(fields[i]));
+            let (msg, salt, sig) = (
+                hex::decode(fields[5]).unwrap(),
+                hex::decode(fields[6]).unwrap(),
+                hex::decode(fields[7]).unwrap(),
+            );
+            let mut p1 = p.clone();
+            p1.minus_one();
+            let mut q1 = q.clone();
+            q1.minus_one();
+            let d = e.modinv(&(&p1 * &q1)).unwrap();
+            let key = RsaPrivateKey::from_components(n, e, d, p, q).unwrap();
+            let public = key.public_key();
+            let params = PssParams {
+                hash: HashAlgorithm::Sha1,
+                mgf_hash: HashAlgorithm::Sha1,
+                salt_len: salt.len(),
+            };
+
+            // sign_pss_with_salt refuses SHA-1, so sign through the encoding.
+            let em_bits = public.n().bit_length() - 1;
+            let em = emsa_pss_encode(&params, &msg, &salt, em_bits).unwrap();
+            let s = key.rsasp1(&BigInt::from_bytes_be(&em)).unwrap();
+            assert_eq!(
+                s.to_bytes_be_padded(public.size()),
+                Some(sig.clone()),
+                "{}",
+                fields[0]
+            );
+
+            assert_eq!(public.verify_pss(&params, &msg, &sig), Ok(()));
+            assert_eq!(
+                public.verify_pss_auto(params.hash, params.mgf_hash, &msg, &sig),
+                Ok(salt.len())
+            );
+        }
+    }
+
+    #[test]
+    fn test_cavp_verify_vectors() {
+        let mut results = [0; 2];
+        for line in CAVP_VECTORS.lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let hash = hash_by_name(fields[0]);
+            let msg = hex::decode(fields[2]).unwrap();
+            let sig = hex::decode(fields[3]).unwrap();
+            let valid = fields[4] == "P";
+            results[valid as usize] += 1;
+
+            let public = RsaPublicKey::from_der(&hex::decode(fields[1]).unwrap()).unwrap();
+            let salt_len = public.verify_pss_auto(hash, hash, &msg, &sig);
+            assert_eq!(salt_len.is_ok(), valid, "{}", line);
+            if let Ok(salt_len) = salt_len {
+                let params = PssParams {
+                    hash,
+                    mgf_hash: hash,
+                    salt_len,
+                };
+                assert_eq!(public.verify_pss(&params, &msg, &sig), Ok(()));
+            }
+        }
+        assert_eq!(results, [15, 3]);
+    }
+
+    #[test]
+    fn test_wycheproof_vectors() {
+        let mut results = [0; 3];
+        let mut key = None;
+        for line in WYCHEPROOF_VECTORS.lines().filter(|l| !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields[0] == "key" {
+                let public = RsaPublicKey::from_der(&hex::decode(fields[2]).unwrap()).unwrap();
+                key = Some((public, fields[1].parse().unwrap()));
+                continue;
+            }
+            let (public, salt_len) = key.as_ref().unwrap();
+            let [msg, sig] = [fields[1], fields[2]].map(|f| match f {
+                "-" => Vec::new(),
+                f => hex::decode(f).unwrap(),
+            });
+            let verdict = ["invalid", "valid", "acceptable"]
+                .iter()
+                .position(|&r| r == fields[3])
+                .unwrap();
+            results[verdict] += 1;
+
+            let params = PssParams {
+                hash: HashAlgorithm::Sha256,
+                mgf_hash: HashAlgorithm::Sha256,
+                salt_len: *salt_len,
+            };
+            let exact = public.verify_pss(&params, &msg, &sig);
+            let auto = public.verify_pss_auto(params.hash, params.mgf_hash, &msg, &sig);
+            match verdict {
+                1 => {
+                    assert_eq!(exact, Ok(()), "{}", line);
+                    assert_eq!(auto, Ok(*salt_len), "{}", line);
+                }
+                // Signatures with another salt length are only invalid
+                // when the length is fixed.
+                0 => {
+                    assert_eq!(exact, Err(RsaError::Verification), "{}", line);
+                    assert_ne!(auto, Ok(*salt_len), "{}", line);
+                }
+                _ => assert_eq!(exact.is_ok(), auto == Ok(*salt_len), "{}", line),
+            }
+        }
+        assert_eq!(results, [87, 124, 0]);
+    }
+
+    #[test]
+    fn test_sign_with_fixed_salt() {
+        // Known answer: this signature was checked with OpenSSL's PSS verify.
+        let key = test_key();
+        let params = PssParams::new(HashAlgorithm::Sha256);
+        let salt: Vec<u8> = (0..32).collect();
+        let sig = key
+            .sign_pss_with_salt(&params, b"rspki pss test message", &salt)
+            .unwrap();
+        assert_eq!(
+            hex::encode(&sig),
+            "61503a1ed61656dc4c06efdd684aa6efe17db8cdf78b0d4692f4db45c7340634\
+             47e6f9f4337ab418f859a76bf9ecd86b50f4876e40f626afb0a1f47f78f6a3d2\
+             6c0a57be2774f029e98a0e6c8727d0c597ee80a5cd3abdabbb82487882147ee1\
+             167066041cf6a85b53c4e6c51bec0c6bedd4c382782ce7c053ff53823f51bb34\
+             907e097e11a27791595c99e05eb731013fe8f696d890d37042f56facc4fb0f47\
+             1e3df82d28668dbb57d1a9bad14bd71aa6b8099a8ee1bfb18b5639e6a5499ca5\
+             c57279716d33d5769ff70758be180811c6e9e3f8b503699b3a6a05323b530850\
+             0808e83b7e7612fbbad0209c8c0797f171cc7d34026a1c1a6f71e8f6209a755d"
+        );
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        let key = test_key();
+        let mut rng = HmacDrbg::<Sha256>::new(b"pss salt entropy 0123456789abcdef", b"", &[]);
+        for params in [
+            PssParams::new(HashAlgorithm::Sha384),
+            PssParams::new(HashAlgorithm::Sha3_256),
+            PssParams {
+                hash: HashAlgorithm::Sha512,
+                mgf_hash: HashAlgorithm::Sha256,
+                salt_len: 0,
+            },
+        ] {
+            let sig = key.sign_pss(&params, b"roundtrip", &mut rng).unwrap();
+            assert_eq!(
+                key.public_key().verify_pss(&params, b"roundtrip", &sig),
+                Ok(())
+            );
+        }
+
+        // The salt cannot exceed emLen - hLen - 2.
+        let too_long = PssParams {
+            salt_len: 256 - 32 - 1,
+            ..PssParams::new(HashAlgorithm::Sha256)
+        };
+        assert_eq!(
+            key.sign_pss(&too_long, b"x", &mut rng),
+            Err(RsaError::KeyTooShort)
+        );
+        assert_eq!(
+            key.sign_pss(&PssParams::new(HashAlgorithm::Sha1), b"x", &mut rng),
+            Err(RsaError::UnsupportedHash)
+        );
+    }
+
+    #[test]
+    fn test_algorithm_identifier() {
+        // Byte-for-byte what OpenSSL writes into PSS-signed certificates.
+        assert_eq!(
+            hex::encode(&PssParams::new(HashAlgorithm::Sha256).algorithm_identifier()),
+            "304106092a864886f70d01010a3034a00f300d06096086480165030402010500\
+             a11c301a06092a864886f70d010108300d06096086480165030402010500a203020120"
+        );
+        assert_eq!(
+            hex::encode(&PssParams::new(HashAlgorithm::Sha384).algorithm_identifier()),
+            "304106092a864886f70d01010a3034a00f300d06096086480165030402020500\
+             a11c301a06092a864886f70d010108300d06096086480165030402020500a203020130"
+        );
+        // Salt length 20 is the DEFAULT and is left out.
+        let params = PssParams {
+            salt_len: 20,
+            ..PssParams::new(HashAlgorithm::Sha512)
+        };
+        assert_eq!(
+            hex::encode(&params.algorithm_identifier()),
+            "303c06092a864886f70d01010a302fa00f300d06096086480165030402030500\
+             a11c301a06092a864886f70d010108300d06096086480165030402030500"
+        );
+    }
+
+    #[test]
+    fn test_mgf1() {
+        // MGF1-SHA1("foo", 3) and ("bar", 50), the widely reproduced examples.
+        assert_eq!(hex::encode(&mgf1(HashAlgorithm::Sha1, b"foo", 3)), "1ac907");
+        assert_eq!(
+            hex::encode(&mgf1(HashAlgorithm::Sha256, b"bar", 50)),
+            "382576a7841021cc28fc4c0948753fb8312090cea942ea4c4e735d10dc724b15\
+             5f9f6069f289d61daca0cb814502ef04eae1"
+        );
+    }
+}
@@ -0,0 +1,274 @@
+use crate::digest::Digest;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets for lane (x, y), indexed by x + 5y.
+const ROTATIONS: [u32; 25] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// The Keccak-f[1600] permutation (FIPS 202 section 3).
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                a[x + 5 * y] ^= d;
+            }
+        }
+        // rho and pi
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] = a[x + 5 * y].rotate_left(ROTATIONS[x + 5 * y]);
+            }
+        }
+        // chi
+        for y in 0..5 {
+            for x in 0..5 {
+                a[x + 5 * y] = b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// Keccak sponge with rate `RATE` bytes and domain-separation suffix `DS`
+/// (0x06 for SHA-3, 0x1f for SHAKE).
+#[derive(Clone)]
+struct Sponge<const RATE: usize, const DS: u8> {
+    state: [u64; 25],
+    /// Bytes absorbed into the current block.
+    pos: usize,
+}
+
+impl<const RATE: usize, const DS: u8> Sponge<RATE, DS> {
+    fn new() -> Self {
+        Self {
+            state: [0u64; 25],
+            pos: 0,
+        }
+    }
+
+    fn xor_byte(&mut self, i: usize, byte: u8) {
+        self.state[i / 8] ^= (byte as u64) << (8 * (i % 8));
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.xor_byte(self.pos, byte);
+            self.pos += 1;
+            if self.pos == RATE {
+                keccak_f(&mut self.state);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Pads the final block and squeezes `out_len` bytes.
+    fn squeeze(mut self, out_len: usize) -> Vec<u8> {
+        self.xor_byte(self.pos, DS);
+        self.xor_byte(RATE - 1, 0x80);
+        keccak_f(&mut self.state);
+
+        let mut out = Vec::with_capacity(out_len);
+        loop {
+            for i in 0..RATE {
+                if out.len() == out_len {
+                    return out;
+                }
+                out.push((self.state[i / 8] >> (8 * (i % 8))) as u8);
+            }
+            keccak_f(&mut self.state);
+        }
+    }
+}
+
+/// Defines a fixed-output SHA-3 or SHAKE type over the sponge. SHAKE types
+/// use their RFC 8692 output length for the `Digest` impl and additionally
+/// offer `finalize_xof` for arbitrary-length output.
+macro_rules! keccak_variant {
+    ($(#[$doc:meta])* $name:ident, $rate:literal, $ds:literal, $out:literal) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sponge: Sponge<$rate, $ds>,
+        }
+
+        impl Digest for $name {
+            const OUTPUT_LEN: usize = $out;
+            const BLOCK_LEN: usize = $rate;
+
+            fn new() -> Self {
+                Self {
+                    sponge: Sponge::new(),
+                }
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                self.sponge.absorb(data);
+            }
+
+            fn finalize(self) -> Vec<u8> {
+                self.sponge.squeeze($out)
+            }
+        }
+    };
+}
+
+keccak_variant!(
+    /// SHA3-256 (FIPS 202).
+    Sha3_256, 136, 0x06, 32
+);
+keccak_variant!(
+    /// SHA3-384 (FIPS 202).
+    Sha3_384, 104, 0x06, 48
+);
+keccak_variant!(
+    /// SHA3-512 (FIPS 202).
+    Sha3_512, 72, 0x06, 64
+);
+keccak_variant!(
+    /// SHAKE128 (FIPS 202). As a `Digest` it yields 32 bytes (RFC 8692);
+    /// use `finalize_xof` for other lengths.
+    Shake128, 168, 0x1f, 32
+);
+keccak_variant!(
+    /// SHAKE256 (FIPS 202). As a `Digest` it yields 64 bytes (RFC 8692);
+    /// use `finalize_xof` for other lengths.
+    Shake256, 136, 0x1f, 64
+);
+
+impl Shake128 {
+    /// Squeezes `out_len` bytes of output.
+    pub fn finalize_xof(self, out_len: usize) -> Vec<u8> {
+        self.sponge.squeeze(out_len)
+    }
+}
+
+impl Shake256 {
+    /// Squeezes `out_len` bytes of output.
+    pub fn finalize_xof(self, out_len: usize) -> Vec<u8> {
+        self.sponge.squeeze(out_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex;
+
+    // NIST "SHA3 examples": the 1600-bit message of repeated 0xa3 bytes.
+    const A3_1600: [u8; 200] = [0xa3; 200];
+
+    #[test]
+    fn test_sha3_nist_vectors() {
+        assert_eq!(
+            hex::encode(&Sha3_256::digest(b"")),
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_256::digest(b"abc")),
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_256::digest(&A3_1600)),
+            "79f38adec5c20307a98ef76e8324afbfd46cfd81b22e3973c65fa1bd9de31787"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_384::digest(b"abc")),
+            "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b298d88cea927ac7f539f1edf228376d25"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_384::digest(&A3_1600)),
+            "1881de2ca7e41ef95dc4732b8f5f002b189cc1e42b74168ed1732649ce1dbcdd76197a31fd55ee989f2d7050dd473e8f"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_512::digest(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+        );
+        assert_eq!(
+            hex::encode(&Sha3_512::digest(&A3_1600)),
+            "e76dfad22084a8b1467fcf2ffa58361bec7628edf5f3fdc0e4805dc48caeeca81b7c13c30adf52a3659584739a2df46be589c51ca1a4a8416df6545a1ce8ba00"
+        );
+    }
+
+    #[test]
+    fn test_shake_nist_vectors() {
+        assert_eq!(
+            hex::encode(&Shake128::digest(b"")),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(
+            hex::encode(&Shake256::digest(b"")),
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762fd75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+        );
+        let mut h = Shake128::new();
+        h.update(&A3_1600);
+        let out = h.finalize_xof(512);
+        assert_eq!(
+            hex::encode(&out[480..]),
+            "44c9fb359fd56ac0a9a75a743cff6862f17d7259ab075216c0699511643b6439"
+        );
+        let mut h = Shake256::new();
+        h.update(&A3_1600);
+        let out = h.finalize_xof(512);
+        assert_eq!(
+            hex::encode(&out[480..]),
+            "6a1a9d7846436e4dca5728b6f760eef0ca92bf0be5615e96959d767197a0beeb"
+        );
+    }
+
+    #[test]
+    fn test_xof_prefix_consistency() {
+        // A shorter squeeze is a prefix of a longer one.
+        let mut a = Shake256::new();
+        a.update(b"rspki");
+        let long = a.clone().finalize_xof(300);
+        assert_eq!(a.finalize_xof(7), long[..7]);
+    }
+
+    #[test]
+    fn test_streaming_across_rate_boundary() {
+        let data: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+        let mut h = Sha3_256::new();
+        for chunk in data.chunks(135) {
+            h.update(chunk);
+        }
+        assert_eq!(
+            hex::encode(&h.finalize()),
+            "14e5de35911194ddad95ac1572e2b6ce054ed2146cd0562280fcab04ccfecbd8"
+        );
+    }
+}
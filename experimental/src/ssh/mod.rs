@@ -0,0 +1,345 @@
+//! OpenSSH key formats: public keys as the one-line `type base64 comment`
+//! form of authorized_keys and .pub files (RFC 4253 section 6.6, RFC 5656
+//! section 3.1 and RFC 8709 section 4), and private keys in the
+//! `openssh-key-v1` container, optionally encrypted with bcrypt-pbkdf and
+//! AES. RSA, Ed25519 and ECDSA on P-256 and P-384 are supported.
+
+mod private;
+mod wire;
+
+use crate::base64;
+use crate::curve25519::ed25519::Ed25519PublicKey;
+use crate::digest::Digest;
+use crate::ec::curve::Curve;
+use crate::ec::ecdsa::EcdsaPublicKey;
+use crate::pkcs8::{KeyError, PrivateKey, PublicKey};
+use crate::rsa::RsaPublicKey;
+use crate::sha2::Sha256;
+use std::sync::Arc;
+use wire::{Reader, Writer};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SshError {
+    /// Malformed wire data, base64 or armor, or a key type that does not
+    /// match the one inside its blob.
+    InvalidEncoding,
+    /// A key type, curve, cipher or KDF with no support here.
+    Unsupported(String),
+    /// The private key is encrypted and no passphrase was given.
+    Encrypted,
+    /// The passphrase is wrong or the encrypted section is corrupt.
+    Decryption,
+    /// The key material itself is invalid.
+    Key(KeyError),
+}
+
+/// A public key with the comment that follows it on its line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SshPublicKey {
+    key: PublicKey,
+    comment: String,
+}
+
+/// A private key with the comment stored alongside it.
+#[derive(Clone, Debug)]
+pub struct SshPrivateKey {
+    key: PrivateKey,
+    comment: String,
+}
+
+impl SshPublicKey {
+    /// Fails with `Unsupported` for X25519 and curves OpenSSH has no
+    /// name for.
+    pub fn new(key: PublicKey, comment: &str) -> Result<Self, SshError> {
+        key_type(&key)?;
+        Ok(Self {
+            key,
+            comment: comment.to_string(),
+        })
+    }
+
+    pub fn key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    /// Parses `type base64 [comment]`. Leading options, as in
+    /// authorized_keys, are not supported.
+    pub fn from_openssh(line: &str) -> Result<Self, SshError> {
+        let line = line.trim();
+        let mut fields = line.splitn(3, [' ', '\t']);
+        let (Some(name), Some(blob)) = (fields.next(), fields.next()) else {
+            return Err(SshError::InvalidEncoding);
+        };
+        let blob = base64::STANDARD
+            .decode(blob)
+            .map_err(|_| SshError::InvalidEncoding)?;
+        let key = Self::from_blob(&blob)?;
+        if key_type(&key.key)? != name {
+            return Err(SshError::InvalidEncoding);
+        }
+        let comment = fields.next().unwrap_or("").trim();
+        Ok(Self {
+            comment: comment.to_string(),
+            ..key
+        })
+    }
+
+    /// The line as ssh-keygen writes it, with a trailing newline.
+    pub fn to_openssh(&self) -> String {
+        let mut line = format!(
+            "{} {}",
+            key_type(&self.key).unwrap(),
+            base64::STANDARD.encode(&self.to_blob())
+        );
+        if !self.comment.is_empty() {
+            line.push(' ');
+            line.push_str(&self.comment);
+        }
+        line.push('\n');
+        line
+    }
+
+    /// Decodes the wire-format key blob, which carries no comment.
+    pub fn from_blob(blob: &[u8]) -> Result<Self, SshError> {
+        let mut r = Reader::new(blob);
+        let key = read_public(&mut r)?;
+        r.finish()?;
+        Self::new(key, "")
+    }
+
+    /// The wire-format key blob, as signed over and sent in SSH messages.
+    pub fn to_blob(&self) -> Vec<u8> {
+        let mut w = Writer::new();
+        write_public(&mut w, &self.key);
+        w.finish()
+    }
+
+    /// The `SHA256:` fingerprint ssh-keygen -l prints: unpadded base64 of
+    /// the blob's hash.
+    pub fn fingerprint(&self) -> String {
+        let hash = Sha256::digest(&self.to_blob());
+        format!("SHA256:{}", base64::STANDARD_NO_PAD.encode(&hash))
+    }
+}
+
+impl SshPrivateKey {
+    /// Fails with `Unsupported` for key types OpenSSH has no name for.
+    pub fn new(key: PrivateKey, comment: &str) -> Result<Self, SshError> {
+        key_type(&key.public_key())?;
+        Ok(Self {
+            key,
+            comment: comment.to_string(),
+        })
+    }
+
+    pub fn key(&self) -> &PrivateKey {
+        &self.key
+    }
+
+    pub fn comment(&self) -> &str {
+        &self.comment
+    }
+
+    pub fn public_key(&self) -> SshPublicKey {
+        SshPublicKey {
+            key: self.key.public_key(),
+            comment: self.comment.clone(),
+        }
+    }
+}
+
+/// The key type name, which starts every blob.
+fn key_type(key: &PublicKey) -> Result<&'static str, SshError> {
+    match key {
+        PublicKey::Rsa(_) => Ok("ssh-rsa"),
+        PublicKey::Ed25519(_) => Ok("ssh-ed25519"),
+        PublicKey::Ec(key) => match curve_name(key.curve())? {
+            "nistp256" => Ok("ecdsa-sha2-nistp256"),
+            _ => Ok("ecdsa-sha2-nistp384"),
+        },
+        PublicKey::X25519(_) => Err(SshError::Unsupported("X25519".to_string())),
+    }
+}
+
+/// The RFC 5656 curve identifier.
+fn curve_name(curve: &Curve) -> Result<&'static str, SshError> {
+    match curve.name() {
+        "P-256" => Ok("nistp256"),
+        "P-384" => Ok("nistp384"),
+        name => Err(SshError::Unsupported(name.to_string())),
+    }
+}
+
+fn curve_from_name(name: &[u8]) -> Result<Arc<Curve>, SshError> {
+    match name {
+        b"nistp256" => Ok(Curve::p256()),
+        b"nistp384" => Ok(Curve::p384()),
+        _ => Err(SshError::Unsupported(
+            String::from_utf8_lossy(name).into_owned(),
+        )),
+    }
+}
+
+/// Reads a key type and the public fields that follow it.
+fn read_public(r: &mut Reader) -> Result<PublicKey, SshError> {
+    let name = r.utf8()?;
+    Ok(match name {
+        "ssh-rsa" => {
+            let e = r.mpint()?;
+            let n = r.mpint()?;
+            PublicKey::Rsa(RsaPublicKey::new(n, e).map_err(|e| SshError::Key(KeyError::Rsa(e)))?)
+        }
+        "ssh-ed25519" => {
+            let bytes = r
+                .string()?
+                .try_into()
+                .map_err(|_| SshError::InvalidEncoding)?;
+            PublicKey::Ed25519(
+                Ed25519PublicKey::from_bytes(bytes)
+                    .map_err(|e| SshError::Key(KeyError::Curve25519(e)))?,
+            )
+        }
+        "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" => {
+            let curve = curve_from_name(r.string()?)?;
+            if !name.ends_with(curve_name(&curve)?) {
+                return Err(SshError::InvalidEncoding);
+            }
+            PublicKey::Ec(
+                EcdsaPublicKey::from_sec1(&curve, r.string()?)
+                    .map_err(|e| SshError::Key(KeyError::Ec(e)))?,
+            )
+        }
+        _ => return Err(SshError::Unsupported(name.to_string())),
+    })
+}
+
+/// The key type and public fields. RSA puts e before n here.
+fn write_public(w: &mut Writer, key: &PublicKey) {
+    w.string(key_type(key).unwrap().as_bytes());
+    match key {
+        PublicKey::Rsa(key) => {
+            w.mpint(key.e());
+            w.mpint(key.n());
+        }
+        PublicKey::Ed25519(key) => w.string(key.as_bytes()),
+        PublicKey::Ec(key) => {
+            w.string(curve_name(key.curve()).unwrap().as_bytes());
+            w.string(&key.to_sec1(false));
+        }
+        PublicKey::X25519(_) => unreachable!("no SSH key type"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rsa::tests::test_key;
+
+    const OPENSSH_KEYS: &str = include_str!("../../testdata/openssh_keys.txt");
+
+    /// Each private key block in `OPENSSH_KEYS` with its .pub line.
+    pub(super) fn fixtures() -> Vec<(&'static str, &'static str)> {
+        const END: &str = "-----END OPENSSH PRIVATE KEY-----\n";
+        let mut fixtures = Vec::new();
+        let mut rest = OPENSSH_KEYS;
+        while let Some(begin) = rest.find("-----BEGIN") {
+            let end = rest.find(END).unwrap() + END.len();
+            let line_end = end + rest[end..].find('\n').unwrap() + 1;
+            fixtures.push((&rest[begin..end], &rest[end..line_end]));
+            rest = &rest[line_end..];
+        }
+        assert_eq!(fixtures.len(), 6);
+        fixtures
+    }
+
+    #[test]
+    fn test_public_keys_round_trip() {
+        let types = [
+            "ssh-rsa",
+            "ssh-ed25519",
+            "ecdsa-sha2-nistp256",
+            "ecdsa-sha2-nistp384",
+            "ssh-ed25519",
+            "ecdsa-sha2-nistp256",
+        ];
+        for ((_, line), name) in fixtures().into_iter().zip(types) {
+            let key = SshPublicKey::from_openssh(line).unwrap();
+            assert_eq!(key_type(key.key()), Ok(name));
+            assert!(key.comment().ends_with("@test.com"));
+            assert_eq!(key.to_openssh(), line);
+            assert_eq!(
+                SshPublicKey::from_blob(&key.to_blob()).unwrap().key,
+                key.key
+            );
+        }
+        let (_, rsa) = fixtures()[0];
+        let key = SshPublicKey::from_openssh(rsa).unwrap();
+        assert_eq!(key.key(), &PublicKey::Rsa(test_key().public_key().clone()));
+        // ssh-keygen -l -f rsa.pub
+        assert_eq!(
+            key.fingerprint(),
+            "SHA256:LWa2eJfx/JDo1XJ4+Xjk2sl2z9JUn7sbENnYL1Npclg"
+        );
+    }
+
+    #[test]
+    fn test_public_key_errors() {
+        let (_, line) = fixtures()[1];
+        let key = SshPublicKey::from_openssh(line).unwrap();
+        let blob = key.to_openssh().split(' ').nth(1).unwrap().to_string();
+
+        // No comment, and a type that disagrees with the blob.
+        let bare = SshPublicKey::from_openssh(&format!("ssh-ed25519 {}", blob)).unwrap();
+        assert_eq!(bare.comment(), "");
+        assert_eq!(bare.to_openssh(), format!("ssh-ed25519 {}\n", blob));
+        assert_eq!(
+            SshPublicKey::from_openssh(&format!("ssh-rsa {}", blob)),
+            Err(SshError::InvalidEncoding)
+        );
+        assert_eq!(
+            SshPublicKey::from_openssh("ssh-ed25519"),
+            Err(SshError::InvalidEncoding)
+        );
+
+        // Trailing bytes, and a truncated blob.
+        let mut long = key.to_blob();
+        long.push(0);
+        assert_eq!(
+            SshPublicKey::from_blob(&long),
+            Err(SshError::InvalidEncoding)
+        );
+        assert_eq!(
+            SshPublicKey::from_blob(&key.to_blob()[..40]),
+            Err(SshError::InvalidEncoding)
+        );
+
+        let mut w = Writer::new();
+        w.string(b"ssh-dss");
+        assert_eq!(
+            SshPublicKey::from_blob(&w.finish()),
+            Err(SshError::Unsupported("ssh-dss".to_string()))
+        );
+        // A P-256 blob labelled as P-384.
+        let (_, line) = fixtures()[2];
+        let mut blob = SshPublicKey::from_openssh(line).unwrap().to_blob();
+        blob[4..23].copy_from_slice(b"ecdsa-sha2-nistp384");
+        assert_eq!(
+            SshPublicKey::from_blob(&blob),
+            Err(SshError::InvalidEncoding)
+        );
+
+        let secp256k1 = crate::ec::ecdsa::EcdsaPrivateKey::from_scalar(
+            &Curve::secp256k1(),
+            crate::bigint::BigInt::from_u64(7),
+        )
+        .unwrap();
+        assert_eq!(
+            SshPublicKey::new(PublicKey::Ec(secp256k1.public_key().clone()), ""),
+            Err(SshError::Unsupported("secp256k1".to_string()))
+        );
+    }
+}
@@ -0,0 +1,461 @@
+//! The `openssh-key-v1` private key container (PROTOCOL.key in the
+//! OpenSSH sources): a header naming the cipher and KDF, the public key
+//! blob, then a section holding the private key and comment, encrypted as
+//! a whole when there is a passphrase.
+
+use super::wire::{Reader, Writer};
+use super::{curve_from_name, curve_name, key_type, SshError, SshPrivateKey, SshPublicKey};
+use crate::aes::gcm::{Gcm, TAG_LEN};
+use crate::aes::{ctr, Aes};
+use crate::curve25519::ed25519::Ed25519PrivateKey;
+use crate::ec::ecdsa::EcdsaPrivateKey;
+use crate::kdf::bcrypt_pbkdf;
+use crate::pem;
+use crate::pkcs8::{KeyError, PrivateKey};
+use crate::rsa::{RsaError, RsaPrivateKey};
+use rand::RngCore;
+
+const LABEL: &str = "OPENSSH PRIVATE KEY";
+const MAGIC: &[u8] = b"openssh-key-v1\0";
+/// ssh-keygen wraps the base64 at 70 columns rather than PEM's 64.
+const LINE_LEN: usize = 70;
+const SALT_LEN: usize = 16;
+
+/// The ciphers OpenSSH accepts for key files, with their key lengths.
+#[derive(Clone, Copy)]
+enum Cipher {
+    None,
+    Ctr(usize),
+    Gcm(usize),
+}
+
+impl Cipher {
+    fn from_name(name: &str) -> Result<Self, SshError> {
+        Ok(match name {
+            "none" => Cipher::None,
+            "aes128-ctr" => Cipher::Ctr(16),
+            "aes192-ctr" => Cipher::Ctr(24),
+            "aes256-ctr" => Cipher::Ctr(32),
+            "aes128-gcm@openssh.com" => Cipher::Gcm(16),
+            "aes256-gcm@openssh.com" => Cipher::Gcm(32),
+            _ => return Err(SshError::Unsupported(name.to_string())),
+        })
+    }
+
+    /// The private section is padded to this.
+    fn block_len(self) -> usize {
+        match self {
+            Cipher::None => 8,
+            _ => 16,
+        }
+    }
+
+    /// The GCM tag follows the encrypted section as raw bytes, outside
+    /// its length prefix.
+    fn tag_len(self) -> usize {
+        match self {
+            Cipher::Gcm(_) => TAG_LEN,
+            _ => 0,
+        }
+    }
+
+    /// The bcrypt-pbkdf output is the key followed by the IV.
+    fn decrypt(self, key_iv: &[u8], section: &[u8], tag: &[u8]) -> Result<Vec<u8>, SshError> {
+        match self {
+            Cipher::None => Ok(section.to_vec()),
+            Cipher::Ctr(key_len) => {
+                let (key, iv) = key_iv.split_at(key_len);
+                let mut out = section.to_vec();
+                ctr::apply_keystream(&Aes::new(key).unwrap(), iv.try_into().unwrap(), &mut out);
+                Ok(out)
+            }
+            // OpenSSH uses the IV as the first GCM nonce and no AAD.
+            Cipher::Gcm(key_len) => {
+                let (key, iv) = key_iv.split_at(key_len);
+                Gcm::new(key)
+                    .unwrap()
+                    .decrypt(iv, &[], &[section, tag].concat())
+                    .map_err(|_| SshError::Decryption)
+            }
+        }
+    }
+
+    fn key_iv_len(self) -> usize {
+        match self {
+            Cipher::None => 0,
+            Cipher::Ctr(key_len) => key_len + 16,
+            Cipher::Gcm(key_len) => key_len + 12,
+        }
+    }
+}
+
+/// The parameters of an encrypted key: always bcrypt-pbkdf and
+/// aes256-ctr, as ssh-keygen writes by default.
+struct Encryption<'a> {
+    passphrase: &'a [u8],
+    salt: [u8; SALT_LEN],
+    rounds: u32,
+}
+
+impl SshPrivateKey {
+    /// Parses an unencrypted key. An encrypted one fails with
+    /// `Encrypted`.
+    pub fn from_openssh(text: &str) -> Result<Self, SshError> {
+        Self::decode(text, None)
+    }
+
+    /// Parses a key encrypted with bcrypt-pbkdf and AES-CTR or AES-GCM.
+    /// Unencrypted keys are accepted too, and the passphrase is ignored.
+    pub fn from_openssh_encrypted(text: &str, passphrase: &[u8]) -> Result<Self, SshError> {
+        Self::decode(text, Some(passphrase))
+    }
+
+    /// Writes the key unencrypted. The check integers, which are only
+    /// meaningful for encrypted keys, are zero so the output is
+    /// deterministic.
+    pub fn to_openssh(&self) -> String {
+        self.encode(0, None)
+    }
+
+    /// Encrypts the key with aes256-ctr under a key derived by
+    /// bcrypt-pbkdf with a random salt. ssh-keygen uses 16 rounds.
+    ///
+    /// Panics if `rounds` is zero.
+    pub fn to_openssh_encrypted<G: RngCore + ?Sized>(
+        &self,
+        passphrase: &[u8],
+        rounds: u32,
+        rng: &mut G,
+    ) -> String {
+        assert!(rounds > 0, "bcrypt-pbkdf needs at least one round");
+        let mut salt = [0u8; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let encryption = Encryption {
+            passphrase,
+            salt,
+            rounds,
+        };
+        self.encode(rng.next_u32(), Some(encryption))
+    }
+
+    fn decode(text: &str, passphrase: Option<&[u8]>) -> Result<Self, SshError> {
+        let data = pem::parse(text)
+            .and_then(|block| block.expect(LABEL))
+            .map_err(|_| SshError::InvalidEncoding)?;
+        let data = data.strip_prefix(MAGIC).ok_or(SshError::InvalidEncoding)?;
+        let mut r = Reader::new(data);
+        let cipher = Cipher::from_name(r.utf8()?)?;
+        let kdf = r.utf8()?;
+        let kdf_options = r.string()?;
+        if r.u32()? != 1 {
+            return Err(SshError::InvalidEncoding);
+        }
+        let public = SshPublicKey::from_blob(r.string()?)?;
+        let section = r.string()?;
+        let tag = r.bytes(cipher.tag_len())?;
+        r.finish()?;
+        if !section.len().is_multiple_of(cipher.block_len()) {
+            return Err(SshError::InvalidEncoding);
+        }
+
+        let section = match (cipher, kdf) {
+            (Cipher::None, "none") if kdf_options.is_empty() => section.to_vec(),
+            (Cipher::None, _) => return Err(SshError::InvalidEncoding),
+            (_, "bcrypt") => {
+                let passphrase = passphrase.ok_or(SshError::Encrypted)?;
+                let mut r = Reader::new(kdf_options);
+                let salt = r.string()?;
+                let rounds = r.u32()?;
+                r.finish()?;
+                if rounds == 0 {
+                    return Err(SshError::InvalidEncoding);
+                }
+                let key_iv = bcrypt_pbkdf(passphrase, salt, rounds, cipher.key_iv_len());
+                cipher.decrypt(&key_iv, section, tag)?
+            }
+            (_, kdf) => return Err(SshError::Unsupported(kdf.to_string())),
+        };
+
+        // Matching check integers are how a wrong passphrase shows up.
+        let mut r = Reader::new(&section);
+        if r.u32()? != r.u32()? {
+            return Err(match cipher {
+                Cipher::None => SshError::InvalidEncoding,
+                _ => SshError::Decryption,
+            });
+        }
+        let key = read_private(&mut r)?;
+        let comment = r.utf8()?;
+        // OpenSSH pads 1, 2, 3, ... only up to the next block boundary.
+        let padding = r.rest();
+        if padding.len() >= cipher.block_len() || !padding.iter().zip(1u8..).all(|(&b, i)| b == i) {
+            return Err(SshError::InvalidEncoding);
+        }
+        if &key.public_key() != public.key() {
+            return Err(SshError::Key(KeyError::PublicKeyMismatch));
+        }
+        Self::new(key, comment)
+    }
+
+    fn encode(&self, check: u32, encryption: Option<Encryption>) -> String {
+        let block_len = match encryption {
+            Some(_) => Cipher::Ctr(32).block_len(),
+            None => Cipher::None.block_len(),
+        };
+        let mut section = Writer::new();
+        section.u32(check);
+        section.u32(check);
+        write_private(&mut section, &self.key);
+        section.string(self.comment.as_bytes());
+        let mut pad = 1;
+        while !section.len().is_multiple_of(block_len) {
+            section.raw(&[pad]);
+            pad += 1;
+        }
+        let mut section = section.finish();
+
+        let mut w = Writer::new();
+        w.raw(MAGIC);
+        match encryption {
+            None => {
+                w.string(b"none");
+                w.string(b"none");
+                w.string(&[]);
+            }
+            Some(Encryption {
+                passphrase,
+                salt,
+                rounds,
+            }) => {
+                w.string(b"aes256-ctr");
+                w.string(b"bcrypt");
+                let mut options = Writer::new();
+                options.string(&salt);
+                options.u32(rounds);
+                w.string(&options.finish());
+                let key_iv = bcrypt_pbkdf(passphrase, &salt, rounds, 48);
+                let (key, iv) = key_iv.split_at(32);
+                ctr::apply_keystream(
+                    &Aes::new(key).unwrap(),
+                    iv.try_into().unwrap(),
+                    &mut section,
+                );
+            }
+        }
+        w.u32(1);
+        w.string(&self.public_key().to_blob());
+        w.string(&section);
+        pem::Pem::new(LABEL, w.finish()).encode_with_line_len(LINE_LEN)
+    }
+}
+
+/// The key type, then the public and private fields. These repeat the
+/// public blob's fields but RSA has them in a different order.
+fn read_private(r: &mut Reader) -> Result<PrivateKey, SshError> {
+    let name = r.utf8()?;
+    Ok(match name {
+        "ssh-rsa" => {
+            let rsa = |e| SshError::Key(KeyError::Rsa(e));
+            let (n, e, d, iqmp) = (r.mpint()?, r.mpint()?, r.mpint()?, r.mpint()?);
+            let (p, q) = (r.mpint()?, r.mpint()?);
+            let key = RsaPrivateKey::from_components(n, e, d, p, q).map_err(rsa)?;
+            if key.qinv() != &iqmp {
+                return Err(rsa(RsaError::InvalidKey));
+            }
+            PrivateKey::Rsa(key)
+        }
+        // The 64-byte secret key is the seed followed by the public key.
+        "ssh-ed25519" => {
+            let public = r.string()?;
+            let secret = r.string()?;
+            if public.len() != 32 || secret.len() != 64 {
+                return Err(SshError::InvalidEncoding);
+            }
+            let key = Ed25519PrivateKey::from_seed(secret[..32].try_into().unwrap());
+            if key.public_key().as_bytes() != public || &secret[32..] != public {
+                return Err(SshError::Key(KeyError::PublicKeyMismatch));
+            }
+            PrivateKey::Ed25519(key)
+        }
+        "ecdsa-sha2-nistp256" | "ecdsa-sha2-nistp384" => {
+            let curve = curve_from_name(r.string()?)?;
+            if !name.ends_with(curve_name(&curve)?) {
+                return Err(SshError::InvalidEncoding);
+            }
+            let public = r.string()?;
+            let key = EcdsaPrivateKey::from_scalar(&curve, r.mpint()?)
+                .map_err(|e| SshError::Key(KeyError::Ec(e)))?;
+            if key.public_key().to_sec1(false) != public {
+                return Err(SshError::Key(KeyError::PublicKeyMismatch));
+            }
+            PrivateKey::Ec(key)
+        }
+        _ => return Err(SshError::Unsupported(name.to_string())),
+    })
+}
+
+fn write_private(w: &mut Writer, key: &PrivateKey) {
+    w.string(key_type(&key.public_key()).unwrap().as_bytes());
+    match key {
+        PrivateKey::Rsa(key) => {
+            for value in [
+                key.public_key().n(),
+                key.public_key().e(),
+                key.d(),
+                key.qinv(),
+                key.p(),
+                key.q(),
+            ] {
+                w.mpint(value);
+            }
+        }
+        PrivateKey::Ed25519(key) => {
+            let public = key.public_key().as_bytes();
+            w.string(public);
+            w.string(&[&key.seed()[..], public].concat());
+        }
+        PrivateKey::Ec(key) => {
+            w.string(curve_name(key.curve()).unwrap().as_bytes());
+            w.string(&key.public_key().to_sec1(false));
+            w.mpint(key.d());
+        }
+        PrivateKey::X25519(_) => unreachable!("no SSH key type"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drbg::HmacDrbg;
+    use crate::rsa::tests::test_key;
+    use crate::sha2::Sha256;
+    use crate::ssh::tests::fixtures;
+
+    const PASSPHRASE: &[u8] = b"correct horse";
+
+    /// The check integer of an unencrypted key file.
+    fn checkint(text: &str) -> u32 {
+        let data = pem::parse(text).unwrap().expect(LABEL).unwrap();
+        let mut r = Reader::new(&data[MAGIC.len()..]);
+        for _ in 0..3 {
+            r.string().unwrap();
+        }
+        r.u32().unwrap();
+        r.string().unwrap();
+        r.u32().unwrap();
+        r.u32().unwrap()
+    }
+
+    fn rearmor(text: &str, edit: impl FnOnce(&mut Vec<u8>)) -> String {
+        let mut data = pem::parse(text).unwrap().expect(LABEL).unwrap();
+        edit(&mut data);
+        pem::Pem::new(LABEL, data).encode_with_line_len(LINE_LEN)
+    }
+
+    #[test]
+    fn test_unencrypted_round_trip() {
+        for (text, line) in &fixtures()[..4] {
+            let key = SshPrivateKey::from_openssh(text).unwrap();
+            assert_eq!(key.comment(), "test@test.com");
+            assert_eq!(key.public_key().to_openssh(), *line);
+            // Byte for byte what ssh-keygen wrote, given its check integer.
+            assert_eq!(key.encode(checkint(text), None), *text);
+            let ours = key.to_openssh();
+            assert_eq!(checkint(&ours), 0);
+            assert_eq!(
+                SshPrivateKey::from_openssh(&ours).unwrap().to_openssh(),
+                ours
+            );
+        }
+        let key = SshPrivateKey::from_openssh(fixtures()[0].0).unwrap();
+        let PrivateKey::Rsa(rsa) = key.key() else {
+            panic!("not an RSA key");
+        };
+        assert_eq!(rsa.d(), test_key().d());
+    }
+
+    #[test]
+    fn test_encrypted_fixtures() {
+        // aes256-ctr, then aes256-gcm@openssh.com.
+        for (text, line) in &fixtures()[4..] {
+            assert_eq!(
+                SshPrivateKey::from_openssh(text).unwrap_err(),
+                SshError::Encrypted
+            );
+            assert_eq!(
+                SshPrivateKey::from_openssh_encrypted(text, b"wrong horse").unwrap_err(),
+                SshError::Decryption
+            );
+            let key = SshPrivateKey::from_openssh_encrypted(text, PASSPHRASE).unwrap();
+            assert_eq!(key.public_key().to_openssh(), *line);
+        }
+    }
+
+    #[test]
+    fn test_encrypted_round_trip() {
+        let mut rng = HmacDrbg::<Sha256>::new(b"openssh salt entropy 0123456789ab", b"", &[]);
+        for (text, _) in &fixtures()[..4] {
+            let key = SshPrivateKey::from_openssh(text).unwrap();
+            let encrypted = key.to_openssh_encrypted(PASSPHRASE, 2, &mut rng);
+            assert_eq!(
+                SshPrivateKey::from_openssh(&encrypted).unwrap_err(),
+                SshError::Encrypted
+            );
+            assert_eq!(
+                SshPrivateKey::from_openssh_encrypted(&encrypted, b"").unwrap_err(),
+                SshError::Decryption
+            );
+            let decrypted = SshPrivateKey::from_openssh_encrypted(&encrypted, PASSPHRASE).unwrap();
+            assert_eq!(decrypted.to_openssh(), key.to_openssh());
+        }
+    }
+
+    #[test]
+    fn test_rejects_malformed() {
+        let ed25519 = SshPrivateKey::from_openssh(fixtures()[1].0).unwrap();
+        // A comment that leaves four bytes of padding.
+        let text = SshPrivateKey::new(ed25519.key().clone(), "x")
+            .unwrap()
+            .to_openssh();
+        assert!(SshPrivateKey::from_openssh(&text).is_ok());
+
+        let bad_padding = rearmor(&text, |data| *data.last_mut().unwrap() ^= 1);
+        let no_magic = rearmor(&text, |data| data[0] = b'O');
+        let trailing = rearmor(&text, |data| data.push(0));
+        // A different check integer in the second copy.
+        let check = rearmor(&text, |data| {
+            let at = data.len() - 136 + 4;
+            data[at] = 1;
+        });
+        // Padding that runs on for whole blocks, past where a u8 count wraps.
+        let long_padding = rearmor(&text, |data| {
+            let at = data.len() - 140;
+            data.truncate(data.len() - 4);
+            data.extend((1..=260u32).map(|i| i as u8));
+            let len = (data.len() - at - 4) as u32;
+            data[at..at + 4].copy_from_slice(&len.to_be_bytes());
+        });
+        for text in [bad_padding, no_magic, trailing, check, long_padding] {
+            assert_eq!(
+                SshPrivateKey::from_openssh(&text).unwrap_err(),
+                SshError::InvalidEncoding
+            );
+        }
+        assert_eq!(
+            SshPrivateKey::from_openssh(&text.replace("OPENSSH", "RSA")).unwrap_err(),
+            SshError::InvalidEncoding
+        );
+
+        // The outer public key blob from another Ed25519 key.
+        let other = SshPrivateKey::from_openssh_encrypted(fixtures()[4].0, PASSPHRASE).unwrap();
+        let swapped = rearmor(&text, |data| {
+            let ours = ed25519.public_key().to_blob();
+            let at = data.windows(ours.len()).position(|w| w == ours).unwrap();
+            data[at..at + ours.len()].copy_from_slice(&other.public_key().to_blob());
+        });
+        assert_eq!(
+            SshPrivateKey::from_openssh(&swapped).unwrap_err(),
+            SshError::Key(KeyError::PublicKeyMismatch)
+        );
+    }
+}